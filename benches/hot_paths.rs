@@ -0,0 +1,71 @@
+//! How fast the parts of the game that run the most are: reading and
+//! writing tiles, saving, and drawing. Run with `cargo bench`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use minecraft_2d::{RenderOptions, Tile, make_worst_case_state, render_frame, write_frame};
+
+/// About the size of a big terminal, in tiles.
+const VIEW: (i32, i32) = (200, 60);
+/// The size of the frame that is drawn, in characters.
+const FRAME: (u16, u16) = (200, 60);
+
+fn get_tile(c: &mut Criterion) {
+    let state = make_worst_case_state(10_000);
+    c.bench_function("get_tile over a viewport", |b| {
+        b.iter(|| {
+            for y in -VIEW.1 / 2..VIEW.1 / 2 {
+                for x in -VIEW.0 / 2..VIEW.0 / 2 {
+                    black_box(state.get_tile((x, y)));
+                }
+            }
+        })
+    });
+}
+
+fn tiles_in_rect(c: &mut Criterion) {
+    let state = make_worst_case_state(10_000);
+    let top_left = (-VIEW.0 / 2, -VIEW.1 / 2);
+    c.bench_function("tiles_in_rect over a viewport", |b| {
+        b.iter(|| black_box(state.tiles_in_rect(top_left, VIEW.0 as u32, VIEW.1 as u32)))
+    });
+}
+
+fn set_tile(c: &mut Criterion) {
+    let mut state = make_worst_case_state(10_000);
+    c.bench_function("set_tile on 10k tiles", |b| {
+        b.iter(|| {
+            for i in 0..10_000 {
+                let tile = if i % 2 == 0 { Tile::WallFull } else { Tile::Empty };
+                state.set_tile((i % 100, i / 100), tile);
+            }
+        })
+    });
+}
+
+fn save(c: &mut Criterion) {
+    let state = make_worst_case_state(10_000);
+    c.bench_function("toml save of 10k tiles", |b| {
+        b.iter(|| toml::to_string_pretty(black_box(&state)).unwrap())
+    });
+}
+
+fn draw(c: &mut Criterion) {
+    let state = make_worst_case_state(10_000);
+    let opts = RenderOptions::default();
+    c.bench_function("render_frame", |b| {
+        b.iter(|| render_frame(black_box(&state), FRAME.0, FRAME.1, &opts))
+    });
+    let frame = render_frame(&state, FRAME.0, FRAME.1, &opts);
+    let mut out = Vec::new();
+    c.bench_function("write_frame", |b| {
+        b.iter(|| {
+            out.clear();
+            write_frame(black_box(&frame), &mut out).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, get_tile, tiles_in_rect, set_tile, save, draw);
+criterion_main!(benches);
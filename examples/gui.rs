@@ -0,0 +1,13 @@
+//! Plays the game in a window. Run with `cargo run --example gui --features gui`.
+
+use minecraft_2d::*;
+
+fn main() {
+    let res = FileSaveStore::in_data_dir()
+        .map_err(GameError::from)
+        .and_then(|store| start_game(&mut WindowPlatform::new(store)));
+    if let Err(e) = res {
+        eprintln!("The game stopped because of an error: {e}");
+        std::process::exit(1);
+    }
+}
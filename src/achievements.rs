@@ -0,0 +1,48 @@
+//! Goals for the player to reach. An achievement is unlocked once its
+//! condition is true, and stays unlocked from then on.
+
+use serde::{Deserialize, Serialize};
+
+use crate::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstBreak,
+    FirstBuild,
+    LongWalk,
+    FirstCraft,
+}
+
+type Condition = fn(&State) -> bool;
+
+/// Every achievement, with the condition for unlocking it.
+pub const ACHIEVEMENTS: &[(Achievement, Condition)] = &[
+    (Achievement::FirstBreak, |state| state.stats().tiles_broken >= 1),
+    (Achievement::FirstBuild, |state| state.stats().tiles_placed >= 1),
+    (Achievement::LongWalk, |state| state.stats().steps_taken >= 100),
+    (Achievement::FirstCraft, |state| state.stats().items_crafted >= 1),
+];
+
+impl Achievement {
+    pub fn all() -> impl Iterator<Item = Achievement> {
+        ACHIEVEMENTS.iter().map(|&(achievement, _)| achievement)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstBreak => "Breaking ground",
+            Achievement::FirstBuild => "Builder",
+            Achievement::LongWalk => "Taking a walk",
+            Achievement::FirstCraft => "Crafty",
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstBreak => "Break a tile all the way",
+            Achievement::FirstBuild => "Place a tile",
+            Achievement::LongWalk => "Walk 100 tiles",
+            Achievement::FirstCraft => "Trade for an item",
+        }
+    }
+}
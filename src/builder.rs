@@ -0,0 +1,144 @@
+//! Making a `State` to order, for tests and tools that need a particular
+//! scene instead of a new world.
+//!
+//! ```
+//! use minecraft_2d::{Item, StateBuilder, Tile};
+//!
+//! let state = StateBuilder::new()
+//!     .seed(7)
+//!     .player_pos((3, 4))
+//!     .with_tile((3, 5), Tile::Sand)
+//!     .with_item(Item::Torch, 5)
+//!     .build();
+//! assert_eq!(state.player_pos(), (3, 4));
+//! assert_eq!(state.get_tile((3, 5)), Tile::Sand);
+//! assert_eq!(state.inventory().count_of(&Item::Torch), 5);
+//! ```
+
+use crate::{Dir, Inventory, Item, Pos, State, Tile};
+
+/// Makes a `State` with chosen parts. Unlike `State::new`, the inventory
+/// starts empty, so it only has what is given with `with_item`.
+#[derive(Debug, Clone)]
+pub struct StateBuilder {
+    seed: Option<u32>,
+    player_pos: Pos,
+    facing: Dir,
+    tiles: Vec<(Pos, Tile)>,
+    items: Vec<(Item, usize)>,
+    bounds: Option<(Pos, Pos)>,
+}
+
+impl Default for StateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateBuilder {
+    pub fn new() -> Self {
+        Self {
+            seed: None,
+            player_pos: (0, 0),
+            facing: Dir::Down,
+            tiles: vec![],
+            items: vec![],
+            bounds: None,
+        }
+    }
+
+    /// The seed the world is generated from. `DEFAULT_SEED` if not set.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn player_pos(mut self, pos: Pos) -> Self {
+        self.player_pos = pos;
+        self
+    }
+
+    /// The direction the player looks in.
+    pub fn facing(mut self, dir: Dir) -> Self {
+        self.facing = dir;
+        self
+    }
+
+    /// Puts a tile somewhere, instead of the one that would be generated
+    /// there. Later tiles replace earlier ones at the same position.
+    pub fn with_tile(mut self, pos: Pos, tile: Tile) -> Self {
+        self.tiles.push((pos, tile));
+        self
+    }
+
+    /// Makes the world end at these corners. See `State::bounds`.
+    pub fn bounds(mut self, top_left: Pos, bottom_right: Pos) -> Self {
+        self.bounds = Some((top_left, bottom_right));
+        self
+    }
+
+    /// Puts `count` of an item in the inventory. `build` panics if they don't
+    /// all fit.
+    pub fn with_item(mut self, item: Item, count: usize) -> Self {
+        self.items.push((item, count));
+        self
+    }
+
+    pub fn build(self) -> State {
+        let mut state = match self.seed {
+            Some(seed) => State::with_seed(seed),
+            None => State::new(),
+        };
+        state.player_pos = self.player_pos;
+        state.player_dir = self.facing;
+        state.inventory = Inventory::default();
+        state.bounds = self.bounds;
+        for (item, count) in self.items {
+            for _ in 0..count {
+                state
+                    .inventory
+                    .insert(item.clone())
+                    .expect("The items given to a `StateBuilder` fit in the inventory");
+            }
+        }
+        for (pos, tile) in self.tiles {
+            state.set_tile(pos, tile);
+        }
+        state
+    }
+}
+
+/// A world with `n_tiles` placed tiles of every kind in a square around the
+/// player, and a full inventory. Slower to draw and save than any world a
+/// player is likely to make, for benchmarks and tests of how fast things are.
+/// Public only for the benchmarks, which can only see the public API.
+#[doc(hidden)]
+pub fn make_worst_case_state(n_tiles: usize) -> State {
+    const TILES: [Tile; 8] = [
+        Tile::WallFull,
+        Tile::WallHalf,
+        Tile::WallLow,
+        Tile::Torch,
+        Tile::Bed,
+        Tile::Ice,
+        Tile::Waystone,
+        Tile::Empty,
+    ];
+    let side = (n_tiles as f64).sqrt().ceil() as i32;
+    let mut builder = StateBuilder::new();
+    for i in 0..n_tiles {
+        let (x, y) = (i as i32 % side, i as i32 / side);
+        let pos = (x - side / 2, y - side / 2);
+        builder = builder.with_tile(pos, TILES[i % TILES.len()]);
+    }
+    let mut state = builder.build();
+    // One of each at a time, so every item gets some of the slots.
+    let mut added = true;
+    while added {
+        added = false;
+        for item in Item::ALL {
+            added |= state.inventory.insert(item.clone()).is_ok();
+        }
+    }
+    state
+}
@@ -0,0 +1,59 @@
+//! What changed in a `State`, for tools that follow the game from outside
+//! (like a map viewer) without comparing whole saves. See
+//! `State::take_changes` and `Platform::on_changes`.
+
+use crate::{Item, Pos, Tile};
+
+/// Everything that changed since the last `State::take_changes`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateChanges {
+    /// Every tile that was set to something else, in order.
+    pub tiles: Vec<TileChange>,
+    /// How many of each item the player has more of (or less, when
+    /// negative). Items that came and went again are left out.
+    pub inventory: Vec<(Item, i64)>,
+    /// Where the player was and where they are now, if they moved.
+    pub player_moved: Option<(Pos, Pos)>,
+    /// The messages the player was told, oldest first.
+    pub messages: Vec<String>,
+}
+
+impl StateChanges {
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+            && self.inventory.is_empty()
+            && self.player_moved.is_none()
+            && self.messages.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileChange {
+    pub pos: Pos,
+    pub old: Tile,
+    pub new: Tile,
+}
+
+/// What the player had and where they were at the last `take_changes`, to
+/// compare with. The inventory changes in too many places to follow every
+/// one of them.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Seen {
+    pub player_pos: Pos,
+    pub inventory: Vec<(Item, usize)>,
+}
+
+impl Seen {
+    /// How the counts of `now` differ from the ones that were seen.
+    pub fn inventory_changes(&self, now: &[(Item, usize)]) -> Vec<(Item, i64)> {
+        let count_in = |items: &[(Item, usize)], item: &Item| {
+            items.iter().find(|(i, _)| i == item).map_or(0, |&(_, count)| count as i64)
+        };
+        let gone = self.inventory.iter().filter(|(item, _)| count_in(now, item) == 0);
+        now.iter()
+            .chain(gone)
+            .map(|(item, _)| (item.clone(), count_in(now, item) - count_in(&self.inventory, item)))
+            .filter(|&(_, delta)| delta != 0)
+            .collect()
+    }
+}
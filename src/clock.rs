@@ -0,0 +1,27 @@
+//! Where the game loop gets the time from, so it can be faked.
+
+use std::time::{Instant, SystemTime};
+
+/// Tells the time. The game loop uses it to measure how long passed between
+/// two points, and to date worlds and saves.
+pub trait Clock {
+    fn now(&self) -> Instant;
+    /// Seconds since the Unix epoch.
+    fn unix_time(&self) -> u64;
+}
+
+/// The real time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_time(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs())
+    }
+}
@@ -0,0 +1,61 @@
+use std::io;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::save_store::SaveError;
+
+/// Everything that can stop the game from running.
+#[derive(Debug)]
+pub enum GameError {
+    /// Talking to the terminal failed.
+    Io(io::Error),
+    /// Saving or loading the game failed.
+    #[cfg(not(target_arch = "wasm32"))]
+    Save(SaveError),
+    /// Letting Ctrl-C stop the game failed.
+    #[cfg(not(target_arch = "wasm32"))]
+    CtrlC(ctrlc::Error),
+    /// Opening or drawing to the window failed.
+    #[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+    Window(minifb::Error),
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::Io(e) => write!(f, "{e}"),
+            #[cfg(not(target_arch = "wasm32"))]
+            GameError::Save(e) => write!(f, "{e}"),
+            #[cfg(not(target_arch = "wasm32"))]
+            GameError::CtrlC(e) => write!(f, "Could not handle Ctrl-C: {e}"),
+            #[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+            GameError::Window(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Io(e) => Some(e),
+            #[cfg(not(target_arch = "wasm32"))]
+            GameError::Save(e) => Some(e),
+            #[cfg(not(target_arch = "wasm32"))]
+            GameError::CtrlC(e) => Some(e),
+            #[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+            GameError::Window(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for GameError {
+    fn from(e: io::Error) -> Self {
+        GameError::Io(e)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<SaveError> for GameError {
+    fn from(e: SaveError) -> Self {
+        GameError::Save(e)
+    }
+}
@@ -0,0 +1,920 @@
+//! The whole screen, for platforms that show text: the world in its frame,
+//! the status line, the message and the menus.
+//!
+//! Everything is drawn to a `Frame`, a grid of characters with colors, which
+//! the platform then shows however it can. That keeps all of the layout in
+//! one place, and lets it be looked at without a terminal.
+
+use std::fmt;
+
+use crate::render::{self, Chars, Color};
+use crate::{Achievement, Item, MAX_HUNGER, Menu, PauseEntry, Pos, State, format_duration};
+
+mod border {
+    pub const TL: char = '┏';
+    pub const T: char = '━';
+    pub const TR: char = '┓';
+    pub const L: char = '┃';
+    pub const R: char = '┃';
+    pub const BL: char = '┗';
+    pub const B: char = '━';
+    pub const BR: char = '┛';
+}
+
+/// One character on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    const BLANK: Cell = Cell {
+        ch: ' ',
+        fg: Color::Reset,
+        bg: Color::Reset,
+    };
+}
+
+/// A screen's worth of cells, row by row. Drawing to it is like drawing to
+/// a terminal: move the cursor, choose the colors, and print. Whatever is
+/// printed past the edges is left out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    cursor: (u16, u16),
+    fg: Color,
+    bg: Color,
+}
+
+impl Frame {
+    /// A blank frame.
+    pub fn new(width: u16, height: u16) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Cell::BLANK; width as usize * height as usize],
+            cursor: (0, 0),
+            fg: Color::Reset,
+            bg: Color::Reset,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// The cell at a column and row, if it is on the frame.
+    pub fn get(&self, column: u16, row: u16) -> Option<Cell> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        Some(self.cells[row as usize * self.width as usize + column as usize])
+    }
+
+    /// The characters of the frame, without colors, a line for every row.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            text.extend(row.iter().map(|cell| cell.ch));
+            text.push('\n');
+        }
+        text
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) {
+        self.cursor = (column, row);
+    }
+
+    fn set_colors(&mut self, fg: Color, bg: Color) {
+        (self.fg, self.bg) = (fg, bg);
+    }
+
+    fn reset_colors(&mut self) {
+        self.set_colors(Color::Reset, Color::Reset);
+    }
+
+    /// Puts a character at the cursor, and moves the cursor right.
+    fn put(&mut self, ch: char) {
+        let (column, row) = self.cursor;
+        if column < self.width && row < self.height {
+            self.cells[row as usize * self.width as usize + column as usize] = Cell {
+                ch,
+                fg: self.fg,
+                bg: self.bg,
+            };
+        }
+        self.cursor.0 = column.saturating_add(1);
+    }
+
+    fn print(&mut self, text: &str) {
+        text.chars().for_each(|ch| self.put(ch));
+    }
+
+    /// Draws a tile or anything else that takes two characters, in its
+    /// colors. The colors stay chosen after.
+    fn put_chars(&mut self, chars: Chars) {
+        self.set_colors(chars.fg, chars.bg);
+        self.put(chars.left);
+        self.put(chars.right);
+    }
+
+    /// Grays out every cell that is not what it was in `before`.
+    fn dim_changed(&mut self, before: &[Cell]) {
+        for (cell, before) in self.cells.iter_mut().zip(before) {
+            if cell != before {
+                (cell.fg, cell.bg) = (Color::DarkGrey, Color::Reset);
+            }
+        }
+    }
+
+    fn top_row(&mut self, inner_width: u16) {
+        self.put(border::TL);
+        (0..inner_width).for_each(|_| self.put(border::T));
+        self.put(border::TR);
+    }
+
+    fn bottom_row(&mut self, inner_width: u16) {
+        self.put(border::BL);
+        (0..inner_width).for_each(|_| self.put(border::B));
+        self.put(border::BR);
+    }
+}
+
+impl fmt::Write for Frame {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.print(s);
+        Ok(())
+    }
+}
+
+/// What a platform adds to the screen besides the game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RenderOptions<'a> {
+    /// Lines that tell the player what the keys do, shown at the top left.
+    pub help: &'a [&'a str],
+}
+
+/// The smallest (width, height) that the game is drawn in. Smaller frames
+/// only say that they are too small.
+pub const MIN_SIZE: (u16, u16) = (20, 10);
+
+/// Draws the whole screen.
+pub fn render_frame(state: &State, width: u16, height: u16, opts: &RenderOptions) -> Frame {
+    let mut frame = Frame::new(width, height);
+    if width < MIN_SIZE.0 || height < MIN_SIZE.1 {
+        // Too small to draw the game in without everything overlapping.
+        frame.print("Terminal too small!");
+        return frame;
+    }
+    draw(state, &mut frame);
+    frame.reset_colors();
+    for (i, line) in opts.help.iter().enumerate() {
+        frame.move_to(1, 1 + i as u16);
+        frame.print(line);
+    }
+    frame
+}
+
+/// A box in the middle of a blank screen with a prompt, and the text the
+/// user typed so far below it.
+pub fn render_text_prompt(prompt: &str, text: &str, (width, height): (u16, u16)) -> Frame {
+    let mut frame = Frame::new(width, height);
+    let text_width = prompt.chars().count().max(text.chars().count() + 1) as u16;
+    let inner_width = text_width + 2 /* For padding */;
+    let left = (width.saturating_sub(inner_width + 2)) / 2;
+    let top = (height.saturating_sub(4)) / 2;
+
+    frame.move_to(left, top);
+    frame.top_row(inner_width);
+    for (i, line) in [prompt, text].into_iter().enumerate() {
+        let padding = (text_width as usize) - line.chars().count();
+        frame.move_to(left, top + 1 + i as u16);
+        frame.put(border::L);
+        frame.put(' ');
+        frame.print(line);
+        frame.print(&" ".repeat(padding + 1));
+        frame.put(border::R);
+    }
+    frame.move_to(left, top + 3);
+    frame.bottom_row(inner_width);
+    frame
+}
+
+/// The tiles shown on a screen of the given size, as the top-left and
+/// bottom-right corners. See `render::viewport`.
+pub fn viewport(state: &State, width: u16, height: u16) -> (Pos, Pos) {
+    let rows = height as i32 - 2 /* For the frame */;
+    let cells_in_a_row = ((width & !1) as i32 - 2 /* For the frame */) / 2;
+    render::viewport(state, cells_in_a_row, rows)
+}
+
+/// The position in the world that is drawn at a cell of the screen, for a
+/// viewport from `viewport`. `None` if the cell is not on the world (like on
+/// the frame).
+pub fn screen_to_world(
+    (top_left, bottom_right): (Pos, Pos),
+    column: u16,
+    row: u16,
+) -> Option<Pos> {
+    // The frame takes the first column and row, and every tile is two columns.
+    let x = top_left.0 + (column as i32 - 1).div_euclid(2);
+    let y = top_left.1 + row as i32 - 1;
+    let inside = (top_left.0..=bottom_right.0).contains(&x)
+        && (top_left.1..=bottom_right.1).contains(&y);
+    inside.then_some((x, y))
+}
+
+/// The cell of the screen that a position in the world is drawn at (its left
+/// character), for a viewport from `viewport` and a zoom from `State::zoom`.
+/// The opposite of `screen_to_world`. `None` if the position is not in the
+/// viewport.
+fn world_to_screen(
+    (top_left, bottom_right): (Pos, Pos),
+    (x, y): Pos,
+    zoom: i32,
+) -> Option<(u16, u16)> {
+    let inside = (top_left.0..=bottom_right.0).contains(&x)
+        && (top_left.1..=bottom_right.1).contains(&y);
+    // The frame takes the first column and row, and every tile is two columns.
+    let column = 1 + 2 * (x - top_left.0).div_euclid(zoom);
+    let row = 1 + (y - top_left.1).div_euclid(zoom);
+    inside.then_some((column as u16, row as u16))
+}
+
+/// An arrow on the frame around the world, pointing at a position that is
+/// not on the screen, for a viewport from `viewport` and a zoom from
+/// `State::zoom`. Returns its column, row and character, or `None` if the
+/// position is on the screen.
+fn edge_indicator(
+    (top_left, bottom_right): (Pos, Pos),
+    (x, y): Pos,
+    zoom: i32,
+) -> Option<(u16, u16, char)> {
+    // How far outside of the viewport the position is, on each axis.
+    let dx = if x < top_left.0 { x - top_left.0 } else { (x - bottom_right.0).max(0) };
+    let dy = if y < top_left.1 { y - top_left.1 } else { (y - bottom_right.1).max(0) };
+    if (dx, dy) == (0, 0) {
+        return None;
+    }
+    // The closest cell on the screen, moved out to the frame on the axes
+    // that the position is outside on.
+    let closest = (x.clamp(top_left.0, bottom_right.0), y.clamp(top_left.1, bottom_right.1));
+    let (column, row) = world_to_screen((top_left, bottom_right), closest, zoom)?;
+    let last_column = 1 + 2 * ((bottom_right.0 - top_left.0 + 1) / zoom) as u16;
+    let last_row = 1 + ((bottom_right.1 - top_left.1 + 1) / zoom) as u16;
+    let column = match dx.signum() {
+        -1 => 0,
+        1 => last_column,
+        _ => column,
+    };
+    let row = match dy.signum() {
+        -1 => 0,
+        1 => last_row,
+        _ => row,
+    };
+    let arrow = if dx.abs() > dy.abs() {
+        if dx < 0 { '◀' } else { '▶' }
+    } else if dy < 0 {
+        '▲'
+    } else {
+        '▼'
+    };
+    Some((column, row, arrow))
+}
+
+fn draw(state: &State, frame: &mut Frame) {
+    use std::fmt::Write;
+
+    let (width, height) = (frame.width(), frame.height());
+    let outer_width = width & !1 /* Ensure even */;
+    // let outer_height = height - 2 /* For living space for text below */;
+    let outer_height = height;
+    let inner_width = outer_width - 2 /* For the frame */;
+    let inner_height = outer_height - 2 /* For the frame */;
+    let rows = inner_height;
+    let viewport = viewport(state, width, height);
+
+    frame.move_to(0, 0);
+    frame.top_row(inner_width);
+
+    for (row, cells) in render::world_cells(state, viewport).into_iter().enumerate() {
+        frame.reset_colors();
+        frame.move_to(0, row as u16 + 1);
+        frame.put(border::L);
+        for chars in cells {
+            frame.put_chars(chars);
+        }
+        frame.reset_colors();
+        frame.put(border::R);
+    }
+
+    frame.move_to(0, rows + 1);
+    frame.bottom_row(inner_width);
+
+    if state.has_compass() {
+        let waypoints = state.waypoints().values().copied();
+        for target in std::iter::once(state.spawn_point()).chain(waypoints) {
+            if let Some((column, row, arrow)) = edge_indicator(viewport, target, state.zoom()) {
+                frame.move_to(column, row);
+                frame.put(arrow);
+            }
+        }
+    }
+
+    // Writing to a frame never fails.
+    frame.move_to(0, rows + 1);
+    let _ = write!(
+        frame,
+        "XY: {} {} {}",
+        state.player_pos.0,
+        state.player_pos.1,
+        if state.is_night() { "Night" } else { "Day" },
+    );
+    if state.has_compass() {
+        let (x, y) = state.player_pos;
+        let (spawn_x, spawn_y) = state.spawn_point();
+        let _ = write!(frame, " Spawn: {} tiles", x.abs_diff(spawn_x) + y.abs_diff(spawn_y));
+    }
+    if state.rules().hunger {
+        let _ = write!(frame, " Food: {}", hunger_meter(state.hunger()));
+    }
+    if let Some(item) = &state.selected_item
+        && item.variants() > 1
+        && let Some(tile) = state.tile_to_build(item)
+    {
+        let _ = write!(frame, " Building: {}", tile.name(state.language));
+    }
+
+    // The message is shown under the player. The viewport keeps them in
+    // view, but if they are not, it is in the middle of the screen instead.
+    let (column, row) = world_to_screen(viewport, state.player_pos, state.zoom())
+        .unwrap_or((width / 2, height / 2));
+    let message_width = state.message.chars().count() as u16;
+    frame.move_to(column.saturating_sub(message_width / 2), row + 2);
+    frame.print(&state.message);
+
+    if state.is_log_shown() {
+        draw_log(state, frame, rows);
+    }
+
+    // Menus under the one on top are still seen around it, but dimmed.
+    let menus = state.menus();
+    for (depth, &menu) in menus.iter().enumerate() {
+        let before = (depth + 1 < menus.len()).then(|| frame.cells.clone());
+        draw_menu(state, menu, frame, menu_panel((width, height), depth));
+        if let Some(before) = before {
+            frame.dim_changed(&before);
+        }
+    }
+}
+
+/// A bar of `MAX_HUNGER / 2` circles, each full, half full or empty.
+fn hunger_meter(hunger: u8) -> String {
+    (0..MAX_HUNGER / 2)
+        .map(|i| match hunger.saturating_sub(i * 2) {
+            0 => '○',
+            1 => '◐',
+            _ => '●',
+        })
+        .collect()
+}
+
+/// Where a menu panel is drawn, as its top left corner and size, in a frame
+/// of `size`. Every menu over another one is a bit further down and right,
+/// so the one under it shows.
+pub fn menu_panel((width, height): (u16, u16), depth: usize) -> ((u16, u16), (u16, u16)) {
+    let depth = depth as u16;
+    let corner = (width / 4 + 2 * depth, height / 4 + depth);
+    (corner, (width / 2, height / 2))
+}
+
+fn draw_menu(
+    state: &State,
+    menu: Menu,
+    frame: &mut Frame,
+    (corner, size): ((u16, u16), (u16, u16)),
+) {
+    match menu {
+        Menu::None => (),
+        Menu::Inventory => draw_inventory(state, frame, corner, size),
+        Menu::Stats => draw_stats(state, frame, corner, size),
+        Menu::Pause(entry) => draw_pause(state, entry, frame),
+        Menu::Waypoints(highlighted) => draw_waypoints(state, highlighted, frame, corner, size),
+        Menu::Trade { pos, offer } => draw_trade(state, pos, offer, frame, corner, size),
+        Menu::Confirm { action, yes } => draw_confirm(action.prompt(), yes, frame),
+    }
+}
+
+/// Draws a question with "No" and "Yes" under it, in a small box in the
+/// middle of the screen.
+fn draw_confirm(prompt: &str, yes: bool, frame: &mut Frame) {
+    let (width, height) = (frame.width(), frame.height());
+    let panel_width = (prompt.chars().count() as u16 + 6).min(width);
+    let panel_height = 7;
+    let left = width.saturating_sub(panel_width) / 2;
+    let top = height.saturating_sub(panel_height) / 2;
+    draw_panel(frame, (left, top), (panel_width, panel_height));
+    frame.move_to(left + 3, top + 2);
+    frame.print(prompt);
+    frame.move_to(left + 3, top + 4);
+    for (name, highlighted) in [("No", !yes), ("Yes", yes)] {
+        if highlighted {
+            frame.set_colors(HIGHLIGHTED.0, HIGHLIGHTED.1);
+            frame.print(&format!("> {name}"));
+            frame.reset_colors();
+        } else {
+            frame.print(&format!("  {name}"));
+        }
+        frame.print("   ");
+    }
+}
+
+/// The colors of the highlighted entry of a menu.
+const HIGHLIGHTED: (Color, Color) = (Color::Black, Color::White);
+
+/// Draws an empty bordered box, covering whatever was drawn there before.
+fn draw_panel(frame: &mut Frame, (left, top): (u16, u16), (width, height): (u16, u16)) {
+    let bottom = top + height - 1;
+    let inner_width = width - 2;
+
+    frame.reset_colors();
+    frame.move_to(left, top);
+    frame.top_row(inner_width);
+
+    // Clear the inside
+    for row in top + 1..bottom {
+        frame.move_to(left, row);
+        frame.put(border::L);
+        frame.print(&" ".repeat(inner_width as usize));
+        frame.put(border::R);
+    }
+
+    frame.move_to(left, bottom);
+    frame.bottom_row(inner_width);
+}
+
+/// Draws the last messages in a box at the bottom left of the world, which
+/// has `rows` rows.
+fn draw_log(state: &State, frame: &mut Frame, rows: u16) {
+    let log = state.log();
+    let longest = log.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16;
+    let width = (longest.max(3) + 4).min(frame.width() / 2);
+    let height = (log.len().max(1) as u16 + 4).min(rows);
+    if width < 5 || height < 5 {
+        return; // No room
+    }
+    let top = rows + 1 - height;
+    draw_panel(frame, (1, top), (width, height));
+    frame.move_to(3, top + 1);
+    frame.print("Log");
+    // The newest are at the bottom, so if some don't fit, the oldest go.
+    let room = (height - 4) as usize;
+    for (i, line) in log.iter().skip(log.len().saturating_sub(room)).enumerate() {
+        frame.move_to(3, top + 3 + i as u16);
+        frame.print(&line.chars().take(width as usize - 4).collect::<String>());
+    }
+}
+
+/// Draws the pause menu in the middle of the screen.
+fn draw_pause(state: &State, highlighted: PauseEntry, frame: &mut Frame) {
+    let (width, height) = (frame.width(), frame.height());
+    let world_code = format!("World code: {}", state.seed());
+    let panel_width = 8 + world_code.len().max(24) as u16;
+    let panel_height = PauseEntry::ALL.len() as u16 + 8;
+    let left = width.saturating_sub(panel_width) / 2;
+    let top = height.saturating_sub(panel_height) / 2;
+    draw_panel(frame, (left, top), (panel_width, panel_height));
+    frame.move_to(left + 3, top + 2);
+    frame.print("Paused");
+    for (i, entry) in PauseEntry::ALL.into_iter().enumerate() {
+        frame.move_to(left + 4, top + 4 + i as u16);
+        if entry == highlighted {
+            frame.set_colors(HIGHLIGHTED.0, HIGHLIGHTED.1);
+            frame.print(&format!("> {}", entry.name()));
+            frame.reset_colors();
+        } else {
+            frame.print(&format!("  {}", entry.name()));
+        }
+    }
+    frame.move_to(left + 3, top + panel_height - 2);
+    frame.print(&world_code);
+}
+
+fn draw_stats(
+    state: &State,
+    frame: &mut Frame,
+    (left, top): (u16, u16),
+    (width, height): (u16, u16),
+) {
+    draw_panel(frame, (left, top), (width, height));
+    frame.move_to(left + 3, top + 2);
+    match state.meta().name.as_str() {
+        "" => frame.print("Stats"),
+        name => frame.print(&format!("Stats - {name}")),
+    }
+    let lines = state.stats().lines();
+    for (i, (name, value)) in lines.iter().enumerate() {
+        frame.move_to(left + 6, top + 4 + i as u16);
+        frame.print(&format!("{name}: {value}"));
+    }
+    frame.move_to(left + 6, top + 4 + lines.len() as u16);
+    frame.print(&format!("Played: {}", format_duration(state.stats().play_time())));
+    frame.move_to(left + 6, top + 5 + lines.len() as u16);
+    frame.print(&format!("Difficulty: {}", state.meta().difficulty.name()));
+    if state.meta().cheats_used {
+        frame.print(" (cheats used)");
+    }
+    let achievements_top = top + 7 + lines.len() as u16;
+    frame.move_to(left + 3, achievements_top);
+    frame.print("Achievements");
+    for (i, achievement) in Achievement::all().enumerate() {
+        let row = achievements_top + 2 + i as u16;
+        if row >= top + height - 1 {
+            break; // No room
+        }
+        let check = if state.has_achievement(achievement) { 'x' } else { ' ' };
+        let (name, description) = (achievement.name(), achievement.description());
+        frame.move_to(left + 6, row);
+        frame.print(&format!("[{check}] {name} - {description}"));
+    }
+}
+
+fn draw_waypoints(
+    state: &State,
+    highlighted: usize,
+    frame: &mut Frame,
+    (left, top): (u16, u16),
+    (width, height): (u16, u16),
+) {
+    draw_panel(frame, (left, top), (width, height));
+    frame.move_to(left + 3, top + 2);
+    frame.print("Waypoints");
+    let hint = "Enter - go there, e - walk there";
+    let hint_left = (left + width).saturating_sub(3 + hint.chars().count() as u16);
+    frame.move_to(hint_left, top + 2);
+    frame.print(hint);
+    for (i, (name, (x, y))) in state.waypoints().iter().enumerate() {
+        let row = top + 4 + i as u16;
+        if row >= top + height - 1 {
+            break; // No room
+        }
+        let line = format!("{name} ({x}, {y})");
+        frame.move_to(left + 4, row);
+        if i == highlighted {
+            frame.set_colors(HIGHLIGHTED.0, HIGHLIGHTED.1);
+            frame.print(&format!("> {line}"));
+            frame.reset_colors();
+        } else {
+            frame.print(&format!("  {line}"));
+        }
+    }
+}
+
+fn draw_trade(
+    state: &State,
+    pos: Pos,
+    highlighted: usize,
+    frame: &mut Frame,
+    (left, top): (u16, u16),
+    (width, height): (u16, u16),
+) {
+    draw_panel(frame, (left, top), (width, height));
+    frame.move_to(left + 3, top + 2);
+    frame.print("Trader");
+    let hint = "Enter - trade";
+    let hint_left = (left + width).saturating_sub(3 + hint.chars().count() as u16);
+    frame.move_to(hint_left, top + 2);
+    frame.print(hint);
+    let lang = state.language;
+    for (i, trade) in state.trade_offers(pos).into_iter().enumerate() {
+        let row = top + 4 + i as u16;
+        if row >= top + height - 1 {
+            break; // No room
+        }
+        let ((price, cost), (goods, count)) = (&trade.price, &trade.goods);
+        let have = state.inventory().count_of(price);
+        let line = format!(
+            "{cost} {} → {count} {} (you have {have})",
+            price.name(lang),
+            goods.name(lang),
+        );
+        frame.move_to(left + 4, row);
+        if i == highlighted {
+            frame.set_colors(HIGHLIGHTED.0, HIGHLIGHTED.1);
+            frame.print(&format!("> {line}"));
+            frame.reset_colors();
+        } else {
+            frame.print(&format!("  {line}"));
+        }
+    }
+}
+
+/// How far below the top of the inventory panel the list of items starts.
+pub const ITEM_LIST_TOP: u16 = 6;
+
+/// Below this inner width, the inventory does not show the details pane.
+pub const MIN_WIDTH_FOR_DETAILS: u16 = 50;
+
+fn draw_inventory(
+    state: &State,
+    frame: &mut Frame,
+    (left, top): (u16, u16),
+    (width, height): (u16, u16),
+) {
+    let inner_width = width - 2;
+    draw_panel(frame, (left, top), (width, height));
+
+    frame.move_to(left + 3, top + 2);
+    frame.put_chars(render::player(state.player_dir));
+    frame.reset_colors();
+
+    let slots = state.inventory.slots().len();
+    let capacity = state.inventory.capacity();
+    let slots_text = format!("{slots}/{capacity} slots");
+    let slots_left = (left + width).saturating_sub(3 + slots_text.chars().count() as u16);
+    frame.move_to(slots_left, top + 2);
+    frame.print(&slots_text);
+
+    frame.move_to(left + 1, top + 4);
+    frame.print(&"-".repeat(inner_width as usize));
+
+    if let Some(item) = &state.selected_item {
+        if inner_width >= MIN_WIDTH_FOR_DETAILS {
+            let pane_left = left + width / 2;
+            for row in top + 5..top + height - 1 {
+                frame.move_to(pane_left, row);
+                frame.put('│');
+            }
+            draw_item_details(
+                state,
+                item,
+                frame,
+                (pane_left + 2, top + ITEM_LIST_TOP),
+                (width - width / 2 - 4, height.saturating_sub(8)),
+            );
+        } else {
+            // On narrow panels the description goes in a box below the list.
+            let list_bottom = top + ITEM_LIST_TOP + state.inventory.iter().count() as u16;
+            let lines = wrap_text(item.description(), inner_width.saturating_sub(2) as usize);
+            let box_top = list_bottom + 1;
+            if box_top < top + height - 1 {
+                frame.move_to(left + 1, box_top);
+                frame.print(&"-".repeat(inner_width as usize));
+            }
+            for (i, line) in lines.iter().enumerate() {
+                let row = box_top + 1 + i as u16;
+                if row >= top + height - 1 {
+                    break; // No room
+                }
+                frame.move_to(left + 2, row);
+                frame.print(line);
+            }
+        }
+    }
+
+    for (i, (item, count)) in state.inventory.iter().enumerate() {
+        let row = top + ITEM_LIST_TOP + i as u16;
+        if row >= top + height - 1 {
+            break; // No room
+        }
+        frame.move_to(left + 6, row);
+        let name = item.name(state.language);
+        let is_selected = Some(&item) == state.selected_item.as_ref();
+        if is_selected {
+            frame.set_colors(HIGHLIGHTED.0, HIGHLIGHTED.1);
+        }
+        let prefix = if is_selected { '>' } else { ' ' };
+        if count == 1 {
+            frame.print(&format!("{prefix} {name}"));
+        } else {
+            frame.print(&format!("{prefix} {name} ✗ {count}"));
+        }
+        frame.reset_colors();
+    }
+}
+
+/// Draws what we know about an item inside the given rectangle.
+fn draw_item_details(
+    state: &State,
+    item: &Item,
+    frame: &mut Frame,
+    (left, top): (u16, u16),
+    (width, height): (u16, u16),
+) {
+    let placeable = if item.to_tile().is_some() {
+        "Can be placed"
+    } else {
+        "Cannot be placed"
+    };
+    let lines = [
+        item.name(state.language).to_string(),
+        format!("{} (x{})", item.category().name(), state.inventory.count_of(item)),
+        placeable.to_string(),
+        String::new(),
+    ]
+    .into_iter()
+    .chain(wrap_text(item.description(), width as usize));
+    for (i, line) in lines.take(height as usize).enumerate() {
+        frame.move_to(left, top + i as u16);
+        // The first lines are not wrapped, so they may still be too long.
+        frame.print(&line.chars().take(width as usize).collect::<String>());
+    }
+}
+
+/// Splits text into lines of at most `width` characters, breaking between
+/// words. Words that are longer than a line are split.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Item, State, StateBuilder};
+
+    #[test]
+    fn wrapping_breaks_between_words() {
+        assert_eq!(wrap_text("a bb ccc  dddd", 6), ["a bb", "ccc", "dddd"]);
+        assert_eq!(wrap_text("a bb ccc", 8), ["a bb ccc"]);
+    }
+
+    #[test]
+    fn wrapping_splits_words_longer_than_a_line() {
+        assert_eq!(wrap_text("abcdefgh ij", 3), ["abc", "def", "gh", "ij"]);
+        // Too narrow for anything, so a character a line.
+        assert_eq!(wrap_text("ab", 0), ["a", "b"]);
+    }
+
+    #[test]
+    fn wrapping_nothing_gives_no_lines() {
+        assert!(wrap_text("", 5).is_empty());
+        assert!(wrap_text("   ", 5).is_empty());
+    }
+
+    #[test]
+    fn wrapped_descriptions_fit_and_keep_every_word() {
+        let letters = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        for item in Item::ALL {
+            let text = item.description();
+            for width in 1..40 {
+                let lines = wrap_text(text, width);
+                assert!(lines.iter().all(|line| line.chars().count() <= width), "{lines:?}");
+                assert_eq!(letters(&lines.concat()), letters(text));
+            }
+        }
+    }
+
+    /// The inventory with a wall selected, drawn in a frame of `width`.
+    fn inventory_in(width: u16) -> String {
+        let mut state = StateBuilder::new().with_item(Item::Wall, 3).build();
+        state.on_input(crate::Input::OpenInventory);
+        state.selected_item = Some(Item::Wall);
+        render_frame(&state, width, 40, &RenderOptions::default()).text()
+    }
+
+    #[test]
+    fn wide_inventories_have_a_details_pane() {
+        let text = inventory_in(120);
+        assert!(text.contains("Can be placed"));
+        assert!(text.contains("block (x3)"));
+    }
+
+    #[test]
+    fn a_player_scrolled_to_the_edge_is_drawn_where_they_are() {
+        // The view is between the players, so the first one is at its left.
+        let mut state = StateBuilder::new().build();
+        state.other_players.push(crate::Player::new((200, 0)));
+        state.message = "Hi".to_string();
+        let frame = render_frame(&state, 40, 20, &RenderOptions::default());
+        let viewport = viewport(&state, 40, 20);
+        assert_eq!(viewport.0.0, -1);
+        let (column, row) = world_to_screen(viewport, (0, 0), 1).unwrap();
+        assert_eq!(column, 3);
+        let drawn = render::player(state.player_dir);
+        assert_eq!(frame.get(column, row).unwrap().ch, drawn.left);
+        assert_eq!(frame.get(column + 1, row).unwrap().ch, drawn.right);
+        // The message is still under them, and not in the middle.
+        assert_eq!(frame.get(column - 1, row + 2).unwrap().ch, 'H');
+        assert_eq!(frame.get(column, row + 2).unwrap().ch, 'i');
+    }
+
+    #[test]
+    fn what_is_off_the_screen_has_no_cell() {
+        let viewport = ((-5, -5), (5, 5));
+        assert_eq!(world_to_screen(viewport, (-5, -5), 1), Some((1, 1)));
+        assert_eq!(world_to_screen(viewport, (5, 5), 1), Some((21, 11)));
+        assert_eq!(world_to_screen(viewport, (6, 0), 1), None);
+        assert_eq!(world_to_screen(viewport, (0, -6), 1), None);
+    }
+
+    #[test]
+    fn both_characters_of_a_tile_are_on_it() {
+        let viewport = ((-5, -5), (5, 5));
+        // The first tile is right after the frame.
+        assert_eq!(screen_to_world(viewport, 1, 1), Some((-5, -5)));
+        assert_eq!(screen_to_world(viewport, 2, 1), Some((-5, -5)));
+        assert_eq!(screen_to_world(viewport, 3, 1), Some((-4, -5)));
+        assert_eq!(screen_to_world(viewport, 22, 11), Some((5, 5)));
+        for y in -5..=5 {
+            for x in -5..=5 {
+                let (column, row) = world_to_screen(viewport, (x, y), 1).unwrap();
+                assert_eq!(screen_to_world(viewport, column, row), Some((x, y)));
+                assert_eq!(screen_to_world(viewport, column + 1, row), Some((x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn the_frame_and_past_it_are_not_on_the_world() {
+        let viewport = ((-5, -5), (5, 5));
+        let outside = [(0, 0), (0, 5), (5, 0), (23, 5), (5, 12), (23, 12), (200, 3), (3, 200)];
+        for (column, row) in outside {
+            assert_eq!(screen_to_world(viewport, column, row), None, "{column}, {row}");
+        }
+    }
+
+    #[test]
+    fn arrows_point_to_what_is_off_the_screen() {
+        let viewport = ((-5, -5), (5, 5));
+        let cases = [
+            // Straight out of each side.
+            ((20, 0), (23, 6, '▶')),
+            ((-20, 1), (0, 7, '◀')),
+            ((0, -20), (11, 0, '▲')),
+            ((2, 20), (15, 12, '▼')),
+            // Out of the corners, pointing on the axis that is further out.
+            ((30, -10), (23, 0, '▶')),
+            ((10, -30), (23, 0, '▲')),
+            ((-30, -10), (0, 0, '◀')),
+            ((-10, -30), (0, 0, '▲')),
+            ((-30, 10), (0, 12, '◀')),
+            ((-10, 30), (0, 12, '▼')),
+            ((30, 10), (23, 12, '▶')),
+            ((10, 30), (23, 12, '▼')),
+        ];
+        for (target, indicator) in cases {
+            assert_eq!(edge_indicator(viewport, target, 1), Some(indicator), "{target:?}");
+        }
+    }
+
+    #[test]
+    fn what_is_on_the_screen_has_no_arrow() {
+        let viewport = ((-5, -5), (5, 5));
+        for target in [(0, 0), (-5, -5), (5, 5), (5, -5), (-5, 5)] {
+            assert_eq!(edge_indicator(viewport, target, 1), None, "{target:?}");
+        }
+    }
+
+    #[test]
+    fn only_a_compass_shows_the_way_to_spawn() {
+        let away = StateBuilder::new().player_pos((100, 0));
+        let text = |state: &State| render_frame(state, 40, 20, &RenderOptions::default()).text();
+        let without = text(&away.clone().build());
+        assert!(!without.contains('◀'));
+        assert!(!without.contains("Spawn:"));
+        let with = text(&away.with_item(Item::Compass, 1).build());
+        assert!(with.contains('◀'));
+        assert!(with.contains("Spawn: 100 tiles"));
+    }
+
+    #[test]
+    fn narrow_inventories_only_have_the_description() {
+        let text = inventory_in(80);
+        assert!(!text.contains("Can be placed"));
+        assert!(text.contains("A solid block of stone."));
+    }
+}
@@ -1,21 +1,73 @@
+use crate::Clock;
+use crate::DEFAULT_SEED;
+use crate::DEFAULT_WALL_DENSITY;
 use crate::Input;
+use crate::Inventory;
+use crate::LoadResult;
+use crate::Menu;
+use crate::Outcome;
 use crate::Platform;
 use crate::State;
+use crate::SystemClock;
+use crate::WorldGen;
+use crate::seed_from_str;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
 
 /// This function starts a game loop with the provided platform.
 /// Returns an `Ok` when the game ended successfully (by quitting).
 /// If an error that cannot be handled occurs, returns an `Err`.
+/// If something panics, the platform is still cleaned up before the panic
+/// continues.
+/// However the game ends, unsaved changes (see `State::is_dirty`) are saved,
+/// unless the player quit without saving.
 pub fn start_game<P: Platform>(p: &mut P) -> Result<(), P::Error> {
+    start_game_with_clock(p, &SystemClock)
+}
+
+/// Like `start_game`, but telling the time with `clock`, for driving the
+/// game loop with a fake time.
+pub fn start_game_with_clock<P: Platform>(p: &mut P, clock: &impl Clock) -> Result<(), P::Error> {
     // This function wraps the function below, and just gracefully handles
     // errors.
-    let res = start_game_actual(p);
-    // Whether or not the game stopped due to error or quit input, we clean up.
+    // The platform is not used again after a panic other than to save and
+    // clean it up, so it being in a half-updated state is fine.
+    // The game is kept out here so it can still be saved after an error.
+    let mut game = None;
+    let res = panic::catch_unwind(AssertUnwindSafe(|| start_game_actual(p, clock, &mut game)));
+    // A panic while saving is already reported by the panic hook, and there
+    // is nothing else to do about it.
+    let save_res = panic::catch_unwind(AssertUnwindSafe(|| match &mut game {
+        Some(state) if state.is_dirty() => save_game(p, clock, state),
+        _ => Ok(()),
+    }))
+    .unwrap_or(Ok(()));
+    // Whether or not the game stopped due to error, panic or quit input, we
+    // clean up.
     let cleanup_res = p.cleanup();
-    match (res, cleanup_res) {
-        (Ok(()), Ok(())) => Ok(()),
-        (Err(e), Err(_)) => Err(e), // Prefer the first error
-        (Err(e), _) | (_, Err(e)) => Err(e),
-    }
+    let res = match res {
+        Ok(res) => res,
+        Err(payload) => {
+            // The panic message was printed when it happened, which might have
+            // been somewhere that the platform cleaned away (like the
+            // terminal's alternate screen). Now it can be seen.
+            if let Some(message) = panic_message(&*payload) {
+                eprintln!("The game panicked: {message}");
+            }
+            panic::resume_unwind(payload)
+        }
+    };
+    // Prefer the first error.
+    res.and(save_res).and(cleanup_res)
+}
+
+/// The message that a panic was started with, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
 }
 
 fn get_good_input<P: Platform>(p: &mut P) -> Result<Input, P::Error> {
@@ -27,20 +79,181 @@ fn get_good_input<P: Platform>(p: &mut P) -> Result<Input, P::Error> {
     }
 }
 
-fn start_game_actual<P: Platform>(p: &mut P) -> Result<(), P::Error> {
+/// Asks the player how to make a new world, and makes it. It starts with
+/// `inventory`, if given. See `State::new_world`.
+fn new_world<P: Platform>(p: &mut P, inventory: Option<Inventory>) -> Result<State, P::Error> {
+    let name = p.ask_for_text(NAME_PROMPT)?.unwrap_or_default();
+    let seed = match p.ask_for_text(SEED_PROMPT)? {
+        Some(text) => seed_from_str(&text),
+        None => DEFAULT_SEED,
+    };
+    let mut state = State::new_world(seed, inventory);
+    state.meta_mut().name = name.trim().to_string();
+    if p.confirm("Make it a superflat world?")? {
+        return Ok(state.with_world_gen(WorldGen::Flat));
+    }
+    let wall_density = p
+        .ask_for_text(WALL_DENSITY_PROMPT)?
+        .and_then(|text| text.trim().parse().ok())
+        .unwrap_or(DEFAULT_WALL_DENSITY);
+    Ok(state.with_wall_density(wall_density))
+}
+
+impl State {
+    /// Dates a world that was just made, in seconds since the Unix epoch.
+    fn created(mut self, unix_time: u64) -> Self {
+        self.created_at = unix_time;
+        self
+    }
+}
+
+/// Replaces the world with a new one, made at `unix_time`. See
+/// `Outcome::NewWorld`.
+pub(crate) fn replace_world<P: Platform>(
+    p: &mut P,
+    state: &mut State,
+    unix_time: u64,
+) -> Result<(), P::Error> {
+    let inventory = p.confirm(KEEP_INVENTORY_PROMPT)?.then(|| state.inventory().clone());
+    *state = new_world(p, inventory)?.created(unix_time);
+    // Changes are followed in the new world too, from its start.
+    state.take_changes();
+    Ok(())
+}
+
+/// Saving a big world takes a while, so we don't save after every input.
+/// Instead, we save after this many inputs, or this much time, whichever
+/// comes first (and always when quitting or closing a menu).
+const SAVE_EVERY_INPUTS: u32 = 50;
+const SAVE_EVERY: Duration = Duration::from_secs(30);
+
+/// The most time between two inputs that counts as playing. Any longer and
+/// the player probably walked away.
+const MAX_PLAY_TIME_PER_INPUT: Duration = Duration::from_secs(60);
+
+/// How long every step takes when the player walks by themselves.
+pub(crate) const WALK_STEP_EVERY: Duration = Duration::from_millis(100);
+
+const NAME_PROMPT: &str = "New world! Name it, or press enter to leave it unnamed:";
+const SEED_PROMPT: &str = "Type a seed, or press enter for the default:";
+const WALL_DENSITY_PROMPT: &str = "How much of the world is wall? From 0 to 1 (default 0.3):";
+const KEEP_INVENTORY_PROMPT: &str = "Bring your inventory to the new world?";
+
+fn start_game_actual<P: Platform>(
+    p: &mut P,
+    clock: &impl Clock,
+    game: &mut Option<State>,
+) -> Result<(), P::Error> {
     p.init()?;
-    let mut state = p.load()?.unwrap_or_else(State::new);
+    let state = match p.load()? {
+        LoadResult::Loaded(state) => *state,
+        LoadResult::NoSave => new_world(p, None)?.created(clock.unix_time()),
+        LoadResult::Corrupt { reason } => {
+            let msg = format!("Your save could not be loaded ({reason}). Start a new world?");
+            if !p.confirm(&msg)? {
+                return Ok(());
+            }
+            // Keep the old save around, the player might want to fix it.
+            p.back_up_save()?;
+            new_world(p, None)?.created(clock.unix_time())
+        }
+    };
+    let state = game.insert(state);
+    state.compact_tiles();
+    // Changes are followed from here on. See `Platform::on_changes`.
+    state.take_changes();
+    let mut last_save = clock.now();
+    let mut inputs_since_save = 0;
+    let mut last_step: Option<Instant> = None;
+    let mut last_input = clock.now();
     loop {
-        p.draw(&state)?;
-        let input = get_good_input(p)?;
-        match state.on_input(input) {
-            Some(s) => {
-                state = s;
-                p.save(&state)?;
+        p.draw(state)?;
+        let input = if state.is_walking() {
+            match p.poll_input(WALK_STEP_EVERY)? {
+                Some(input) => input,
+                None => {
+                    state.walk_step();
+                    for event in state.take_events() {
+                        p.notify(event)?;
+                    }
+                    p.on_changes(&state.take_changes())?;
+                    continue;
+                }
+            }
+        } else {
+            get_good_input(p)?
+        };
+        if let Input::Dir(..) = input
+            && state.current_menu() == Menu::None
+        {
+            // Steps that come too soon after the last one are from a key that
+            // repeats faster than the player should walk.
+            let now = clock.now();
+            if last_step.is_some_and(|last| now - last < state.move_cooldown()) {
+                continue;
+            }
+            last_step = Some(now);
+        }
+        if let Input::Command(command) = &input
+            && let Some(message) = p.run_command(state, command)?
+        {
+            state.message = message;
+            continue;
+        }
+        let now = clock.now();
+        state.stats.add_play_time((now - last_input).min(MAX_PLAY_TIME_PER_INPUT));
+        last_input = now;
+        let menu_before = state.current_menu();
+        let outcome = state.on_input(input);
+        for event in state.take_events() {
+            p.notify(event)?;
+        }
+        p.on_changes(&state.take_changes())?;
+        inputs_since_save += 1;
+        let closed_menu = menu_before != Menu::None && state.current_menu() == Menu::None;
+        let save = match outcome {
+            Outcome::Continue => {
+                closed_menu
+                    || inputs_since_save >= SAVE_EVERY_INPUTS
+                    || clock.now() - last_save >= SAVE_EVERY
+            }
+            Outcome::Save => true,
+            // The new world is saved right away, so the old one is not
+            // loaded by mistake.
+            Outcome::NewWorld => {
+                replace_world(p, state, clock.unix_time())?;
+                true
+            }
+            Outcome::Quit { save: true } => true,
+            Outcome::Quit { save: false } => {
+                // The player chose to throw away what they did since the
+                // last save.
+                state.dirty = false;
+                false
             }
-            None => break,
+        };
+        if save {
+            save_game(p, clock, state)?;
+            last_save = clock.now();
+            inputs_since_save = 0;
+        }
+        if let Outcome::Quit { .. } = outcome {
+            break;
         }
     }
-    // No call to `p.cleanup()`, the calling function calls it.
+    // No call to `p.cleanup()`, and no last save of a dirty game, the calling
+    // function does them.
+    Ok(())
+}
+
+fn save_game<P: Platform>(
+    p: &mut P,
+    clock: &impl Clock,
+    state: &mut State,
+) -> Result<(), P::Error> {
+    state.last_played = clock.unix_time();
+    state.compact_tiles();
+    p.save(state)?;
+    state.dirty = false;
     Ok(())
 }
@@ -1,18 +1,65 @@
-use crate::utils::Dir;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::utils::{Dir, Pos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IsShift {
     Yes,
     No,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Input {
     Dir(Dir, IsShift),
+    /// Keep moving in a direction until something interesting happens.
+    Run(Dir),
+    /// Turn to a direction without moving or digging, to see what is there.
+    Look(Dir),
     Build,
+    /// Use the tile the player is facing.
+    Interact,
     Quit,
     OpenInventory,
+    /// Open the list of waypoints, to go to one of them.
+    OpenWaypoints,
+    /// Zoom out one step, or all the way back in. See `State::zoom`.
+    CycleZoom,
+    /// Closes the open menu, or opens the pause menu if none is open.
     CloseMenu,
+    /// Chooses the highlighted entry in a menu.
+    Confirm,
+    /// Choose the next variant of the selected item to build. See
+    /// `Item::to_tile_with_variant`.
+    CycleVariant,
+    /// Toggle placing the selected item behind the player as they move.
+    ToggleAutoPlace,
+    /// Show or hide the last messages. See `State::log`.
+    ToggleLog,
+    /// A command the player typed out. Platforms give an empty one for the
+    /// key that opens the command prompt, and then ask for the command.
+    Command(String),
+    /// The player clicked on a tile in the world: digs it if it is next to
+    /// them, or walks there if it can be walked on.
+    ClickTile(Pos),
+    /// Digs at the tile next to the player in a direction, without turning.
+    DigDir(Dir),
+    /// Builds next to the player in a direction, without turning. See
+    /// `Input::Build`.
+    BuildDir(Dir),
+    /// Builds on a tile next to the player. See `Input::Build`.
+    BuildAt(Pos),
+    /// The player clicked on an item in the inventory, by its place in
+    /// `Inventory::iter`.
+    ClickItem(usize),
+    /// Walk to a tile by itself, going around what is in the way. See
+    /// `State::path_to`.
+    MoveTo(Pos),
+    /// Throw a rock in the direction the player is facing.
+    Throw,
+    /// Eat the selected item, if it is food.
+    Eat,
+    /// An input from another player than the first. 1 is the second player.
+    ForPlayer(u8, Box<Input>),
 }
 
 impl TryFrom<Input> for Dir {
@@ -20,7 +67,7 @@ impl TryFrom<Input> for Dir {
 
     fn try_from(input: Input) -> Result<Self, Self::Error> {
         match input {
-            Input::Dir(dir, _) => Ok(dir),
+            Input::Dir(dir, _) | Input::Run(dir) | Input::Look(dir) => Ok(dir),
             _ => Err(()),
         }
     }
@@ -9,63 +9,188 @@ use serde::{Deserialize, Serialize};
 
 use crate::Item;
 
+/// A stack of items of the same kind. The count is never more than the item's
+/// `max_stack`, and never 0.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slot {
+    pub item: Item,
+    pub count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedInventory")]
 pub struct Inventory {
-    items: HashMap<Item, usize>,
+    slots: Vec<Slot>,
+    /// The most slots there can be.
+    capacity: usize,
+}
+
+pub const DEFAULT_CAPACITY: usize = 20;
+
+fn default_capacity() -> usize {
+    DEFAULT_CAPACITY
+}
+
+/// The shapes an inventory can have in a save file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedInventory {
+    Slots {
+        slots: Vec<Slot>,
+        #[serde(default = "default_capacity")]
+        capacity: usize,
+    },
+    /// Before stacks had a limit, we kept a single count for every item.
+    Counts { items: HashMap<Item, usize> },
+}
+
+impl From<SavedInventory> for Inventory {
+    fn from(saved: SavedInventory) -> Self {
+        match saved {
+            SavedInventory::Slots { slots, capacity } => {
+                // A save that was edited by hand (or broken) can have empty
+                // slots, or more in a slot than fits. See `Slot`.
+                let slots = slots
+                    .into_iter()
+                    .flat_map(|Slot { item, count }| {
+                        let max_stack = item.max_stack();
+                        (0..count.div_ceil(max_stack)).map(move |i| Slot {
+                            item: item.clone(),
+                            count: (count - i * max_stack).min(max_stack),
+                        })
+                    })
+                    .collect();
+                Self { slots, capacity }
+            }
+            SavedInventory::Counts { items } => {
+                let mut inventory = Self::new();
+                for (item, count) in items {
+                    for _ in 0..count {
+                        // Old saves had no limit, and we don't want to throw
+                        // away anything the player had.
+                        inventory.insert_ignoring_capacity(item.clone());
+                    }
+                }
+                inventory
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct HasNone;
 
+#[derive(Debug, Clone, Copy)]
+pub struct InventoryFull;
+
 impl Inventory {
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            items: HashMap::new(),
+            slots: vec![],
+            capacity,
         }
     }
 
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Are all the slots taken? Even then, items can still go into stacks that
+    /// are not full. See `can_insert`.
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= self.capacity
+    }
+
+    pub fn can_insert(&self, item: &Item) -> bool {
+        !self.is_full()
+            || self
+                .slots
+                .iter()
+                .any(|slot| &slot.item == item && slot.count < item.max_stack())
+    }
+
+    /// How many of the item there are, in all slots.
     pub fn count_of(&self, item: &Item) -> usize {
-        self.items.get(item).cloned().unwrap_or(0)
+        self.slots
+            .iter()
+            .filter(|slot| &slot.item == item)
+            .map(|slot| slot.count)
+            .sum()
+    }
+
+    /// Adds the item to the first stack of it that is not full, or to a new
+    /// stack if they all are. Fails if that would take more slots than there
+    /// are.
+    pub fn insert(&mut self, item: Item) -> Result<(), InventoryFull> {
+        if !self.can_insert(&item) {
+            return Err(InventoryFull);
+        }
+        self.insert_ignoring_capacity(item);
+        Ok(())
     }
 
-    pub fn insert(&mut self, item: Item) {
-        *self.items.entry(item).or_insert(0) += 1;
+    fn insert_ignoring_capacity(&mut self, item: Item) {
+        let max_stack = item.max_stack();
+        match self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.item == item && slot.count < max_stack)
+        {
+            Some(slot) => slot.count += 1,
+            None => self.slots.push(Slot { item, count: 1 }),
+        }
     }
 
+    /// Takes one of the item from the last stack of it.
     pub fn remove(&mut self, item: &Item) -> Result<(), HasNone> {
-        if let Some(count) = self.items.get_mut(item) {
-            assert!(*count > 0, "All items in the inventory must have count > 0");
-            *count -= 1;
-            if *count == 0 {
-                self.items.remove(item);
-            }
-            Ok(())
-        } else {
-            Err(HasNone)
+        let index = self
+            .slots
+            .iter()
+            .rposition(|slot| &slot.item == item)
+            .ok_or(HasNone)?;
+        let slot = &mut self.slots[index];
+        assert!(slot.count > 0, "All slots in the inventory must have count > 0");
+        slot.count -= 1;
+        if slot.count == 0 {
+            self.slots.remove(index);
         }
+        Ok(())
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
     }
 
+    /// Every kind of item in the inventory with how many of it there are, in
+    /// the order of their first slots.
     pub fn iter(&self) -> impl Iterator<Item = (Item, usize)> {
-        self.items
+        self.items().map(|item| (item.clone(), self.count_of(item)))
+    }
+
+    /// Every kind of item in the inventory, once.
+    fn items(&self) -> impl Iterator<Item = &Item> {
+        self.slots
             .iter()
-            .map(|(item, &count)| (item.clone(), count))
+            .enumerate()
+            .filter(|&(i, slot)| self.slots[..i].iter().all(|s| s.item != slot.item))
+            .map(|(_, slot)| &slot.item)
     }
 
     pub fn first(&self) -> Option<&Item> {
-        self.items.keys().next()
+        self.slots.first().map(|slot| &slot.item)
     }
 
-    /// Returns the next item to come after the given item (in some not really
-    /// specified order). Wraps.
+    /// Returns the next item to come after the given item (in the order of
+    /// `iter`). Wraps. If the item is not in the inventory, returns the first
+    /// item.
     ///
-    /// NOTE: The item must be in the inventory.
-    pub fn next(&self, item: &Item) -> Item {
-        debug_assert!(
-            self.items.contains_key(item),
-            "Item must be in the inventory"
-        );
-        self.items
-            .keys()
+    /// Returns `None` only if the inventory is empty.
+    pub fn next(&self, item: &Item) -> Option<Item> {
+        self.items()
             // Get to the item in the iterator
             .skip_while(|&i| i != item)
             // Get the next item after it
@@ -73,25 +198,19 @@ impl Inventory {
             // Or the first
             .or_else(|| self.first())
             .cloned()
-            .expect("The inventory here should not be empty")
     }
 
-    /// Returns the previous item. See `next`.
-    pub fn prev(&self, item: &Item) -> Item {
-        debug_assert!(
-            self.items.contains_key(item),
-            "Item must be in the inventory"
-        );
+    /// Returns the previous item. See `next`. If the item is not in the
+    /// inventory, returns the last item.
+    pub fn prev(&self, item: &Item) -> Option<Item> {
         let mut prev = None;
-        for i in self.items.keys() {
+        for i in self.items() {
             if i == item {
-                return prev
-                    .or_else(|| self.items.keys().last().cloned())
-                    .expect("Inventory should not be empty");
+                break;
             }
-            prev = Some(i.clone());
+            prev = Some(i);
         }
-        panic!("The item {item:?} was not in the inventory!");
+        prev.or_else(|| self.items().last()).cloned()
     }
 }
 
@@ -100,3 +219,132 @@ impl Default for Inventory {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An inventory with a torch, a bed and a waystone, in that order.
+    fn three_items() -> Inventory {
+        let mut inventory = Inventory::new();
+        for item in [Item::Torch, Item::Bed, Item::Torch, Item::Waystone] {
+            inventory.insert(item).unwrap();
+        }
+        inventory
+    }
+
+    #[test]
+    fn next_goes_in_order_and_wraps() {
+        let inventory = three_items();
+        assert_eq!(inventory.next(&Item::Torch), Some(Item::Bed));
+        assert_eq!(inventory.next(&Item::Bed), Some(Item::Waystone));
+        assert_eq!(inventory.next(&Item::Waystone), Some(Item::Torch));
+    }
+
+    #[test]
+    fn prev_goes_in_order_and_wraps() {
+        let inventory = three_items();
+        assert_eq!(inventory.prev(&Item::Waystone), Some(Item::Bed));
+        assert_eq!(inventory.prev(&Item::Bed), Some(Item::Torch));
+        assert_eq!(inventory.prev(&Item::Torch), Some(Item::Waystone));
+    }
+
+    #[test]
+    fn prev_undoes_next() {
+        let inventory = three_items();
+        for (item, _) in inventory.iter() {
+            let next = inventory.next(&item).unwrap();
+            assert_eq!(inventory.prev(&next), Some(item));
+        }
+    }
+
+    #[test]
+    fn a_single_item_is_its_own_next_and_prev() {
+        let mut inventory = Inventory::new();
+        inventory.insert(Item::Bed).unwrap();
+        assert_eq!(inventory.next(&Item::Bed), Some(Item::Bed));
+        assert_eq!(inventory.prev(&Item::Bed), Some(Item::Bed));
+    }
+
+    #[test]
+    fn a_missing_item_goes_to_the_ends() {
+        let inventory = three_items();
+        assert_eq!(inventory.next(&Item::Wall), Some(Item::Torch));
+        assert_eq!(inventory.prev(&Item::Wall), Some(Item::Waystone));
+    }
+
+    #[test]
+    fn removing_everything_leaves_nothing_first() {
+        let mut inventory = three_items();
+        for item in [Item::Waystone, Item::Torch, Item::Bed, Item::Torch] {
+            assert!(inventory.first().is_some());
+            inventory.remove(&item).unwrap();
+        }
+        assert_eq!(inventory.first(), None);
+        assert_eq!(inventory.next(&Item::Torch), None);
+    }
+
+    #[test]
+    fn a_full_stack_overflows_into_another() {
+        let mut inventory = Inventory::new();
+        for _ in 0..Item::Wall.max_stack() + 1 {
+            inventory.insert(Item::Wall).unwrap();
+        }
+        let counts: Vec<_> = inventory.slots().iter().map(|slot| slot.count).collect();
+        assert_eq!(counts, [64, 1]);
+        assert_eq!(inventory.count_of(&Item::Wall), 65);
+        assert_eq!(inventory.iter().collect::<Vec<_>>(), [(Item::Wall, 65)]);
+        // Taken from the last stack.
+        inventory.remove(&Item::Wall).unwrap();
+        assert_eq!(inventory.slots(), [Slot { item: Item::Wall, count: 64 }]);
+    }
+
+    #[test]
+    fn tools_never_stack() {
+        let mut inventory = Inventory::new();
+        for _ in 0..3 {
+            inventory.insert(Item::Compass).unwrap();
+        }
+        assert_eq!(inventory.slots().len(), 3);
+        assert_eq!(inventory.count_of(&Item::Compass), 3);
+    }
+
+    #[test]
+    fn old_saves_with_counts_load_into_stacks() {
+        let text = "[items]\nWall = 70\nCompass = 2\n";
+        let inventory: Inventory = toml::from_str(text).unwrap();
+        assert_eq!(inventory.count_of(&Item::Wall), 70);
+        assert_eq!(inventory.count_of(&Item::Compass), 2);
+        assert_eq!(inventory.slots().len(), 4);
+        assert_eq!(inventory.capacity(), DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn bad_slots_in_a_save_are_fixed_when_loaded() {
+        let text = "slots = [
+            { item = \"Wall\", count = 0 },
+            { item = \"Torch\", count = 200 },
+            { item = \"Bed\", count = 1 },
+        ]";
+        let mut inventory: Inventory = toml::from_str(text).unwrap();
+        let counts: Vec<_> = inventory.slots().iter().map(|slot| slot.count).collect();
+        assert_eq!(counts, [64, 64, 64, 8, 1]);
+        assert_eq!(inventory.count_of(&Item::Torch), 200);
+        assert!(inventory.remove(&Item::Wall).is_err());
+        inventory.remove(&Item::Torch).unwrap();
+        assert_eq!(inventory.count_of(&Item::Torch), 199);
+    }
+
+    #[test]
+    fn a_full_inventory_only_takes_more_of_a_stack() {
+        let mut inventory = Inventory::with_capacity(2);
+        inventory.insert(Item::Compass).unwrap();
+        inventory.insert(Item::Wall).unwrap();
+        assert!(inventory.is_full());
+        assert!(inventory.insert(Item::Compass).is_err());
+        assert!(inventory.insert(Item::Torch).is_err());
+        inventory.insert(Item::Wall).unwrap();
+        assert_eq!(inventory.count_of(&Item::Wall), 2);
+        assert_eq!(inventory.count_of(&Item::Torch), 0);
+    }
+}
@@ -1,25 +1,250 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 
-use crate::Tile;
+use crate::tiles::WOOD_MAX;
+use crate::{Language, Tile};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The kinds of trees that wood can come from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WoodKind {
+    /// Wood from before there were kinds of wood is oak.
+    #[default]
+    Oak,
+    Birch,
+}
+
+/// Broad kinds of items, to help the player know what an item is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemCategory {
+    Block,
+    Tool,
+    Material,
+    Food,
+}
+
+impl ItemCategory {
+    pub const fn name(self) -> &'static str {
+        match self {
+            ItemCategory::Block => "block",
+            ItemCategory::Tool => "tool",
+            ItemCategory::Material => "material",
+            ItemCategory::Food => "food",
+        }
+    }
+}
+
+/// Items are saved as strings (see `save_name`), because they are the keys of
+/// the inventory and TOML only has string keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Item {
     Wall,
-    Wood,
+    Wood(WoodKind),
+    Bed,
+    Torch,
+    Sand,
+    Sapling(WoodKind),
+    /// Can be thrown, but not placed.
+    Rock,
+    Waystone,
+    /// Points the way to the spawn point and waypoints, while it is in the
+    /// inventory.
+    Compass,
+    /// Food, from bushes.
+    Berry,
 }
 
 impl Item {
-    pub fn name(&self) -> String {
+    /// Every item there is.
+    pub const ALL: &[Item] = &[
+        Item::Wall,
+        Item::Wood(WoodKind::Oak),
+        Item::Wood(WoodKind::Birch),
+        Item::Bed,
+        Item::Torch,
+        Item::Sand,
+        Item::Sapling(WoodKind::Oak),
+        Item::Sapling(WoodKind::Birch),
+        Item::Rock,
+        Item::Waystone,
+        Item::Compass,
+        Item::Berry,
+    ];
+
+    pub const fn name(&self, lang: Language) -> &'static str {
+        lang.item_name(self)
+    }
+
+    /// The item with this name in any language, or with this save name (so
+    /// "wood" is oak wood). Ignores case. The opposite of `name`.
+    pub fn from_name(name: &str) -> Option<Item> {
+        // Lowercase and not `eq_ignore_ascii_case`, for names like "brújula".
+        let name = name.trim().to_lowercase();
+        Item::ALL
+            .iter()
+            .find(|item| {
+                item.save_name().to_lowercase() == name
+                    || Language::ALL.iter().any(|&lang| item.name(lang).to_lowercase() == name)
+            })
+            .cloned()
+    }
+
+    /// A sentence or two telling the player what the item is.
+    pub const fn description(&self) -> &'static str {
         match self {
-            Item::Wall => "wall".into(),
-            Item::Wood => "wood".into()
+            Item::Wall => "A solid block of stone. Place it to block the way.",
+            Item::Wood(WoodKind::Oak) => "Sturdy oak planks. Takes a few hits to break.",
+            Item::Wood(WoodKind::Birch) => "Pale birch planks. Takes a few hits to break.",
+            Item::Bed => "Use it to sleep and to set where you wake up after dying.",
+            Item::Torch => "Lights up the area around it at night.",
+            Item::Sand => "Falls down when there is nothing below it.",
+            Item::Sapling(_) => "Plant it and wait, and it will grow into a log.",
+            Item::Rock => "Throw it to hit something far away.",
+            Item::Waystone => "Place it and use it, and you can come back to it from anywhere.",
+            Item::Compass => "Shows where your spawn point and waypoints are.",
+            Item::Berry => "Small and sweet. Eat it when you are hungry.",
         }
     }
 
+    pub const fn category(&self) -> ItemCategory {
+        match self {
+            Item::Wall
+            | Item::Bed
+            | Item::Torch
+            | Item::Sand
+            | Item::Sapling(_)
+            | Item::Waystone => ItemCategory::Block,
+            Item::Wood(_) | Item::Rock => ItemCategory::Material,
+            Item::Compass => ItemCategory::Tool,
+            Item::Berry => ItemCategory::Food,
+        }
+    }
+
+    pub const fn is_food(&self) -> bool {
+        matches!(self.category(), ItemCategory::Food)
+    }
+
+    /// How much hunger eating the item takes away. 0 for what isn't food.
+    /// See `State::hunger`.
+    pub const fn nutrition(&self) -> u8 {
+        match self {
+            Item::Berry => 3,
+            Item::Wall
+            | Item::Wood(_)
+            | Item::Bed
+            | Item::Torch
+            | Item::Sand
+            | Item::Sapling(_)
+            | Item::Rock
+            | Item::Waystone
+            | Item::Compass => 0,
+        }
+    }
+
+    /// How many of the item fit in one inventory slot.
+    pub const fn max_stack(&self) -> usize {
+        match self.category() {
+            ItemCategory::Tool => 1,
+            ItemCategory::Block | ItemCategory::Material | ItemCategory::Food => 64,
+        }
+    }
+
+    /// How many different tiles the item can be placed as. See
+    /// `to_tile_with_variant`.
+    pub const fn variants(&self) -> u8 {
+        match self {
+            Item::Wall => 3,
+            Item::Wood(_) => WOOD_MAX + 1,
+            Item::Bed
+            | Item::Torch
+            | Item::Sand
+            | Item::Sapling(_)
+            | Item::Rock
+            | Item::Waystone
+            | Item::Compass
+            | Item::Berry => 1,
+        }
+    }
+
+    /// The tile the item is placed as, whole.
     pub fn to_tile(&self) -> Option<Tile> {
+        self.to_tile_with_variant(0)
+    }
+
+    /// The tile the item is placed as, broken `variant` times (up to
+    /// `variants`). Breaking any of them gives back just the one item.
+    pub fn to_tile_with_variant(&self, variant: u8) -> Option<Tile> {
+        let variant = variant.min(self.variants() - 1);
         match self {
-            Item::Wall => Some(Tile::WallFull),
-            Item::Wood => Some(Tile::Wood(5)),
+            Item::Wall => Some([Tile::WallFull, Tile::WallHalf, Tile::WallLow][variant as usize]),
+            Item::Wood(kind) => Some(Tile::wood(*kind, WOOD_MAX - variant)),
+            Item::Bed => Some(Tile::Bed),
+            Item::Torch => Some(Tile::Torch),
+            Item::Sand => Some(Tile::Sand),
+            Item::Sapling(kind) => Some(Tile::Sapling(*kind)),
+            Item::Rock | Item::Compass | Item::Berry => None,
+            Item::Waystone => Some(Tile::Waystone),
+        }
+    }
+
+    /// The name of the item in save files. Never change these, or old saves
+    /// will stop loading.
+    fn save_name(&self) -> &'static str {
+        match self {
+            Item::Wall => "Wall",
+            // Oak is the wood from before there were kinds of wood.
+            Item::Wood(WoodKind::Oak) => "Wood",
+            Item::Wood(WoodKind::Birch) => "BirchWood",
+            Item::Bed => "Bed",
+            Item::Torch => "Torch",
+            Item::Sand => "Sand",
+            Item::Sapling(WoodKind::Oak) => "OakSapling",
+            Item::Sapling(WoodKind::Birch) => "BirchSapling",
+            Item::Rock => "Rock",
+            Item::Waystone => "Waystone",
+            Item::Compass => "Compass",
+            Item::Berry => "Berry",
+        }
+    }
+}
+
+impl Serialize for Item {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.save_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Item::ALL
+            .iter()
+            .find(|item| item.save_name() == name)
+            .cloned()
+            .ok_or_else(|| de::Error::custom(format!("unknown item `{name}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_item_is_found_by_its_names() {
+        for item in Item::ALL {
+            assert_eq!(Item::from_name(item.save_name()).as_ref(), Some(item));
+            for lang in Language::ALL {
+                assert_eq!(Item::from_name(item.name(lang)).as_ref(), Some(item), "{lang:?}");
+                let shouted = item.name(lang).to_uppercase();
+                assert_eq!(Item::from_name(&format!(" {shouted} ")).as_ref(), Some(item));
+            }
+        }
+        assert_eq!(Item::from_name("wood"), Some(Item::Wood(WoodKind::Oak)));
+        assert_eq!(Item::from_name("no such item"), None);
+    }
+
+    #[test]
+    fn every_item_has_a_description() {
+        for item in Item::ALL {
+            assert!(!item.description().trim().is_empty(), "{item:?}");
         }
     }
 }
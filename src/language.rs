@@ -0,0 +1,209 @@
+//! The languages the game can be played in.
+//!
+//! Every text that mentions the name of an item or a tile goes through here,
+//! so the names and the sentences around them always match.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Item, Tile, WoodKind};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+/// A message to show the player, before it is put in a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// The first tile in front of the player, and how many tiles away it is.
+    Facing { name: &'a str, distance: u32 },
+    Selected(&'a str),
+    Placed { name: &'a str, left: usize },
+    OutOf(&'a str),
+    CannotBuild(&'a str),
+    CannotUse(&'a str),
+    StoppedAutoPlace(&'a str),
+    LanguageSet,
+    UnknownLanguage(&'a str),
+    InventoryFull(&'a str),
+    PickedUp(&'a str),
+    PickedUpItems(usize),
+    /// A thrown rock hit a tile.
+    ThrownAt(&'a str),
+    Given { name: &'a str, count: usize },
+    UnknownItem(&'a str),
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// The short code that the player types to choose the language.
+    pub const fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    pub const fn tile_name(self, tile: Tile) -> &'static str {
+        match self {
+            Language::English => match tile {
+                Tile::Empty => "empty",
+                Tile::WallFull => "wall",
+                Tile::WallHalf => "broken wall",
+                Tile::WallLow => "very broken wall",
+                Tile::Wood(kind, stage) => self.wood_name(kind, stage),
+                Tile::Bed => "bed",
+                Tile::Torch => "torch",
+                Tile::Sand => "sand",
+                Tile::Sapling(WoodKind::Oak) => "oak sapling",
+                Tile::Sapling(WoodKind::Birch) => "birch sapling",
+                Tile::Ice => "ice",
+                Tile::Waystone => "waystone",
+                Tile::Barrier => "barrier",
+                Tile::Trader => "trader",
+                Tile::Bush(true) => "berry bush",
+                Tile::Bush(false) => "bush",
+            },
+            Language::Spanish => match tile {
+                Tile::Empty => "vacío",
+                Tile::WallFull => "muro",
+                Tile::WallHalf => "muro roto",
+                Tile::WallLow => "muro muy roto",
+                Tile::Wood(kind, stage) => self.wood_name(kind, stage),
+                Tile::Bed => "cama",
+                Tile::Torch => "antorcha",
+                Tile::Sand => "arena",
+                Tile::Sapling(WoodKind::Oak) => "brote de roble",
+                Tile::Sapling(WoodKind::Birch) => "brote de abedul",
+                Tile::Ice => "hielo",
+                Tile::Waystone => "piedra de paso",
+                Tile::Barrier => "barrera",
+                Tile::Trader => "comerciante",
+                Tile::Bush(true) => "arbusto de bayas",
+                Tile::Bush(false) => "arbusto",
+            },
+        }
+    }
+
+    /// The name of a wood tile, with what is left of it. See `Tile::Wood`.
+    const fn wood_name(self, kind: WoodKind, stage: u8) -> &'static str {
+        match (self, kind, stage) {
+            (Language::English, WoodKind::Oak, 3..) => "oak log",
+            (Language::English, WoodKind::Oak, 2) => "chopped oak log",
+            (Language::English, WoodKind::Oak, 1) => "split oak wood",
+            (Language::English, WoodKind::Oak, 0) => "oak splinters",
+            (Language::English, WoodKind::Birch, 3..) => "birch log",
+            (Language::English, WoodKind::Birch, 2) => "chopped birch log",
+            (Language::English, WoodKind::Birch, 1) => "split birch wood",
+            (Language::English, WoodKind::Birch, 0) => "birch splinters",
+            (Language::Spanish, WoodKind::Oak, 3..) => "tronco de roble",
+            (Language::Spanish, WoodKind::Oak, 2) => "tronco de roble cortado",
+            (Language::Spanish, WoodKind::Oak, 1) => "madera de roble partida",
+            (Language::Spanish, WoodKind::Oak, 0) => "astillas de roble",
+            (Language::Spanish, WoodKind::Birch, 3..) => "tronco de abedul",
+            (Language::Spanish, WoodKind::Birch, 2) => "tronco de abedul cortado",
+            (Language::Spanish, WoodKind::Birch, 1) => "madera de abedul partida",
+            (Language::Spanish, WoodKind::Birch, 0) => "astillas de abedul",
+        }
+    }
+
+    pub const fn item_name(self, item: &Item) -> &'static str {
+        match self {
+            Language::English => match item {
+                Item::Wall => "wall",
+                Item::Wood(WoodKind::Oak) => "oak wood",
+                Item::Wood(WoodKind::Birch) => "birch wood",
+                Item::Bed => "bed",
+                Item::Torch => "torch",
+                Item::Sand => "sand",
+                Item::Sapling(WoodKind::Oak) => "oak sapling",
+                Item::Sapling(WoodKind::Birch) => "birch sapling",
+                Item::Rock => "rock",
+                Item::Waystone => "waystone",
+                Item::Compass => "compass",
+                Item::Berry => "berry",
+            },
+            Language::Spanish => match item {
+                Item::Wall => "muro",
+                Item::Wood(WoodKind::Oak) => "madera de roble",
+                Item::Wood(WoodKind::Birch) => "madera de abedul",
+                Item::Bed => "cama",
+                Item::Torch => "antorcha",
+                Item::Sand => "arena",
+                Item::Sapling(WoodKind::Oak) => "brote de roble",
+                Item::Sapling(WoodKind::Birch) => "brote de abedul",
+                Item::Rock => "piedra",
+                Item::Waystone => "piedra de paso",
+                Item::Compass => "brújula",
+                Item::Berry => "baya",
+            },
+        }
+    }
+
+    pub fn message(self, message: Message) -> String {
+        match self {
+            Language::English => match message {
+                Message::Facing { name, distance: 1 } => format!("You are facing a {name}"),
+                Message::Facing { name, distance } => format!("A {name}, {distance} tiles ahead"),
+                Message::Selected(name) => format!("Selected {name}"),
+                Message::Placed { name, left } => format!("Placed {name} ({left} left)"),
+                Message::OutOf(name) => format!("You are out of {name}!"),
+                Message::CannotBuild(name) => format!("You cannot build a {name}."),
+                Message::CannotUse(name) => format!("You cannot use a {name}."),
+                Message::StoppedAutoPlace(name) => {
+                    format!("You ran out of {name}, so you stopped placing it.")
+                }
+                Message::LanguageSet => "The game is now in English.".to_string(),
+                Message::UnknownLanguage(code) => {
+                    format!("Unknown language: {code} (try {})", codes())
+                }
+                Message::InventoryFull(name) => {
+                    format!("Your inventory is full, so the {name} fell on the ground.")
+                }
+                Message::PickedUp(name) => format!("Picked up {name}"),
+                Message::PickedUpItems(count) => format!("Picked up {count} items"),
+                Message::ThrownAt(name) => format!("Your rock hit a {name}."),
+                Message::Given { name, count } => format!("You got {count} {name}"),
+                Message::UnknownItem(name) => format!("Unknown item: {name}"),
+            },
+            Language::Spanish => match message {
+                Message::Facing { name, distance: 1 } => format!("Frente a ti: {name}"),
+                Message::Facing { name, distance } => {
+                    format!("Más adelante, a {distance} casillas: {name}")
+                }
+                Message::Selected(name) => format!("Elegiste: {name}"),
+                Message::Placed { name, left } => format!("Pusiste {name} (quedan {left})"),
+                Message::OutOf(name) => format!("¡Se te acabó: {name}!"),
+                Message::CannotBuild(name) => format!("No se puede construir: {name}."),
+                Message::CannotUse(name) => format!("No se puede usar: {name}."),
+                Message::StoppedAutoPlace(name) => {
+                    format!("Se te acabó {name}, así que dejaste de ponerlo.")
+                }
+                Message::LanguageSet => "El juego ahora está en español.".to_string(),
+                Message::UnknownLanguage(code) => {
+                    format!("Idioma desconocido: {code} (prueba {})", codes())
+                }
+                Message::InventoryFull(name) => {
+                    format!("Tu inventario está lleno, así que esto cayó al suelo: {name}.")
+                }
+                Message::PickedUp(name) => format!("Recogiste: {name}"),
+                Message::PickedUpItems(count) => format!("Recogiste {count} cosas"),
+                Message::ThrownAt(name) => format!("Tu piedra golpeó: {name}."),
+                Message::Given { name, count } => format!("Recibiste {count}: {name}"),
+                Message::UnknownItem(name) => format!("Objeto desconocido: {name}"),
+            },
+        }
+    }
+}
+
+/// The codes of all languages, for telling the player what they can choose.
+fn codes() -> String {
+    Language::ALL.map(Language::code).join(", ")
+}
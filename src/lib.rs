@@ -1,15 +1,17 @@
 // Std imports
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 // Third party
 use noise::{NoiseFn, Perlin};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 /// Some utility types.
 mod utils;
-use utils::{Dir, Pos};
+pub use utils::{Dir, Pos};
 
 /// Defines the kind of input that the game can receive. Input is not direct
 /// keyboard and mouse presses, but a higher-level what-action-to-take kind of
@@ -30,30 +32,223 @@ pub trait Platform {
     fn ask_for_input(&mut self) -> Result<Option<Input>, Self::Error>;
     fn draw(&mut self, state: &State) -> Result<(), Self::Error>;
     fn save(&mut self, state: &State) -> Result<(), Self::Error>;
-    fn load(&mut self) -> Result<Option<State>, Self::Error>;
+    fn load(&mut self) -> Result<LoadResult, Self::Error>;
+    /// Moves the saved game aside, so it isn't overwritten by the next save
+    /// but can still be recovered by hand.
+    fn back_up_save(&mut self) -> Result<(), Self::Error>;
+    /// Asks the player a yes or no question. By default, the answer is
+    /// always yes, for platforms that can't ask.
+    fn confirm(&mut self, msg: &str) -> Result<bool, Self::Error> {
+        let _ = msg;
+        Ok(true)
+    }
+    /// Runs a command that only the platform knows how to run, like exporting
+    /// the map to a file. Returns a message for the player, or `None` if this
+    /// is not a platform command, so the game should run it instead.
+    fn run_command(&mut self, state: &State, command: &str)
+    -> Result<Option<String>, Self::Error>;
+    /// Asks the player to type some text. Returns `None` if they didn't want
+    /// to type anything.
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, Self::Error>;
+    /// Like `ask_for_input`, but gives up after `timeout`, returning `None`.
+    /// Used while the player walks by themselves (see `State::is_walking`),
+    /// so they can stop. By default, doesn't wait at all, so the walk takes
+    /// no time: platforms that can wait should.
+    fn poll_input(&mut self, timeout: Duration) -> Result<Option<Input>, Self::Error> {
+        let _ = timeout;
+        Ok(None)
+    }
+    /// Gives the player feedback for something that happened in the game,
+    /// like a sound. Does nothing by default.
+    fn notify(&mut self, event: GameEvent) -> Result<(), Self::Error> {
+        let _ = event;
+        Ok(())
+    }
+    /// Told what changed in the game after every input, for drawing only
+    /// what changed, or logging. Does nothing by default.
+    fn on_changes(&mut self, changes: &StateChanges) -> Result<(), Self::Error> {
+        let _ = changes;
+        Ok(())
+    }
 }
 
+/// What came out of trying to load a saved game.
+#[derive(Debug)]
+pub enum LoadResult {
+    /// There is no saved game to load.
+    NoSave,
+    Loaded(Box<State>),
+    /// There is a saved game, but it could not be loaded.
+    Corrupt { reason: String },
+}
+
+/// The errors of the game.
+mod error;
+pub use error::GameError;
+
 mod game_loop;
-pub use game_loop::start_game;
+pub use game_loop::{start_game, start_game_with_clock};
+
+/// Telling the time in the game loop.
+mod clock;
+pub use clock::{Clock, SystemClock};
 
+/// Saving the game to files.
+#[cfg(not(target_arch = "wasm32"))]
+mod save_store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use save_store::{FileSaveStore, SaveError, SaveFormat, SaveReport};
+
+#[cfg(not(target_arch = "wasm32"))]
 mod terminal_platform;
-pub use terminal_platform::TerminalPlatform;
+#[cfg(not(target_arch = "wasm32"))]
+pub use terminal_platform::{TerminalPlatform, write_frame};
+
+/// How the world looks, for every platform.
+mod render;
+pub use render::{Chars, Color};
+
+/// The whole screen as a grid of characters, for platforms that show text.
+mod frame;
+pub use frame::{Cell, Frame, RenderOptions, render_frame, render_text_prompt, screen_to_world};
+
+/// Playing in a window.
+#[cfg(feature = "gui")]
+mod window_platform;
+#[cfg(feature = "gui")]
+pub use window_platform::WindowPlatform;
+
+/// Playing in a browser.
+#[cfg(feature = "wasm")]
+mod web;
+#[cfg(feature = "wasm")]
+pub use web::WebPlatform;
 
 /// Defines everything to do with the tiles in the game's map.
 mod tiles;
 pub use tiles::Tile;
 
+/// World seeds and world codes.
+mod seed;
+pub use seed::{DEFAULT_SEED, seed_from_str};
+
+/// The languages the game can be played in.
+mod language;
+pub use language::{Language, Message};
+
 mod items;
-pub use items::Item;
+pub use items::{Item, ItemCategory, WoodKind};
 
 mod inventory;
-pub use inventory::Inventory;
+pub use inventory::{Inventory, InventoryFull, Slot};
+
+/// Keeping count of what the player did.
+mod stats;
+pub use stats::{Stats, format_duration};
+
+/// The name and difficulty of a world.
+mod meta;
+pub use meta::{Difficulty, Rules, WorldMeta};
+
+/// Day, night and torches.
+mod light;
+pub use light::MAX_LIGHT;
+
+/// Finding the way around walls.
+mod path;
+
+/// Randomness that is the same for the same world every time.
+mod random;
+pub use random::{HashRng, hash_rng};
+use random::salt;
+
+/// Rain and clear skies.
+mod weather;
+pub use weather::Weather;
+
+mod achievements;
+pub use achievements::Achievement;
+
+/// What traders sell.
+mod trades;
+pub use trades::{OFFERS_PER_TRADER, TRADES, Trade};
+
+/// The menus that can be open over the world.
+mod menu;
+pub use menu::{ConfirmAction, Menu, PauseEntry};
+
+mod player;
+pub use player::Player;
+
+/// Following what changes in the game from outside.
+mod changes;
+pub use changes::{StateChanges, TileChange};
+
+/// Making states for tests and tools.
+mod builder;
+pub use builder::{StateBuilder, make_worst_case_state};
+
+/// Playing together over the network.
+#[cfg(not(target_arch = "wasm32"))]
+mod net;
+#[cfg(not(target_arch = "wasm32"))]
+pub use net::{NetworkPlatform, serve};
+
+/// Tests of how the game handles input.
+#[cfg(test)]
+mod tests;
+
+/// What the game loop should do after the game handled an input. New kinds
+/// of feedback for the platform (like asking the player something) go here,
+/// so `on_input` itself never has to change shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Keep going, nothing special happened.
+    Continue,
+    /// Keep going, but the player asked to save first.
+    Save,
+    /// Stop the game, saving it first if `save` is true. The player already
+    /// confirmed quitting without saving (see `Menu::Confirm`).
+    Quit { save: bool },
+    /// The player asked for a new world instead of this one, and is sure. The
+    /// platform should ask if they want to keep their inventory, and then
+    /// replace the state with `State::new_world`.
+    NewWorld,
+}
+
+/// Something that happened in the game that the player might want feedback
+/// for. See `Platform::notify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameEvent {
+    /// The player got hurt. Nothing hurts the player yet.
+    Damage,
+    /// The player picked up items from the ground.
+    Pickup,
+    /// The player placed a tile.
+    Build,
+    /// The player broke a tile.
+    Break,
+}
+
+/// A character drawn over a tile for a few ticks, to show that something
+/// happened there. Only for show. See `State::effects`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Effect {
+    pub pos: Pos,
+    pub glyph: char,
+    pub color: Color,
+    /// How many more ticks it is shown for.
+    pub ttl: u8,
+}
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Menu {
+/// How the parts of the world that weren't changed yet are generated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WorldGen {
+    /// Walls and open spaces, shaped by noise.
     #[default]
-    None,
-    Inventory,
+    Noise,
+    /// An infinite flat world: empty, with a ground of walls below y = 0.
+    Flat,
 }
 
 /// The full state of the game in any given moment.
@@ -76,12 +271,282 @@ pub struct State {
     message: String,
     #[serde(default)]
     inventory: Inventory,
+    /// The open menus, from the first opened to the one on top, which is the
+    /// one that gets input. Never has `Menu::None`. See `current_menu`.
+    ///
+    /// Not saved, so a loaded game always starts with no menu open. This is
+    /// also what lets a server send every player their own menus along with
+    /// the world (see `net`).
     #[serde(skip)]
-    menu: Menu,
+    menus: Vec<Menu>,
+    /// Saved, unlike `menus`, so after loading the player keeps building with
+    /// what they chose.
     #[serde(default)]
     selected_item: Option<Item>,
+    /// Where `selected_item` was in the inventory when last seen, so if it
+    /// runs out the item that took its place is selected. See
+    /// `open_inventory`.
+    #[serde(skip)]
+    selected_index: usize,
+    /// The item the player last built, which building goes back to when the
+    /// selected item can't be built (like after eating or throwing). See
+    /// `item_to_build`.
+    #[serde(default)]
+    last_built: Option<Item>,
+    /// The seed that the world is generated from.
+    #[serde(default = "default_seed")]
+    seed: u32,
+    #[serde(default)]
+    world_gen: WorldGen,
+    /// The `GEN_VERSION` the world was made with. Saves from before it was
+    /// kept were made with 0.
+    #[serde(default)]
+    gen_version: u32,
+    /// How much of a noise world is wall, from 0 to 1.
+    #[serde(default = "default_wall_density")]
+    wall_density: f64,
+    /// See `hunger`.
+    #[serde(default = "default_hunger")]
+    hunger: u8,
+    /// The top left and bottom right corners of the world, if it isn't
+    /// endless. See `bounds`.
+    #[serde(default)]
+    bounds: Option<(Pos, Pos)>,
+    /// The most tiles the player moves in a single run.
+    #[serde(default = "default_run_steps")]
+    run_steps: u32,
+    #[serde(default)]
+    meta: WorldMeta,
+    /// When the world was made, and when it was last saved, in seconds since
+    /// the Unix epoch. 0 if not known, like for worlds made before these were
+    /// kept. See `Clock::unix_time`.
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    last_played: u64,
+    /// In milliseconds. See `move_cooldown`.
+    #[serde(default = "default_move_cooldown_ms")]
+    move_cooldown_ms: u32,
+    /// When on, the selected item is placed behind the player whenever they
+    /// move, like bridging in Minecraft.
+    #[serde(default)]
+    auto_place: bool,
+    /// The chunks (see `chunk_of`) that the player has seen.
+    #[serde(default)]
+    explored: HashSet<Pos>,
+    /// When off, the player can see the whole world, explored or not.
+    #[serde(default = "default_fog_of_war")]
+    fog_of_war: bool,
+    /// When on, moving into a tile that can't be walked into starts digging
+    /// it right away, instead of only turning to it first.
+    #[serde(default)]
+    dig_on_turn: bool,
+    /// The variant of each item to build it as, when not 0. See
+    /// `Item::to_tile_with_variant`.
+    #[serde(default)]
+    variants: HashMap<Item, u8>,
+    /// When on, commands that make things out of nothing (like `give`) can
+    /// be used.
+    #[serde(default)]
+    creative: bool,
+    #[serde(default)]
+    stats: Stats,
+    #[serde(default)]
+    achievements: HashSet<Achievement>,
+    /// Where the player comes back to after dying.
+    #[serde(default)]
+    spawn_point: Pos,
+    /// The language of names and messages. Not everything is translated yet.
+    #[serde(default)]
+    language: Language,
+    /// The positions of the tiles that `needs_tick`, so we don't have to look
+    /// at every tile every tick. Kept up to date by `set_tile`.
+    #[serde(default)]
+    ticking: HashSet<Pos>,
+    /// The positions where a tile with gravity might have nothing below it
+    /// anymore, so only they are looked at by `apply_gravity`. Kept up to
+    /// date by `set_tile` and `move_player`.
+    #[serde(default)]
+    unstable: HashSet<Pos>,
+    /// Items lying on the ground. The player picks them up by walking next to
+    /// them.
+    #[serde_as(as = "Vec<(_, _)>")]
+    #[serde(default)]
+    drops: HashMap<Pos, Vec<Item>>,
+    #[serde(default)]
+    weather: Weather,
+    /// The steps left to walk by themselves, first to last. See
+    /// `is_walking`.
+    #[serde(skip)]
+    auto_path: VecDeque<Dir>,
+    /// The tile the player is digging at, and how many times they hit it.
+    /// See `Tile::hits_to_break`.
+    #[serde(skip)]
+    breaking: Option<(Pos, u8)>,
+    /// Which of `render::ZOOM_LEVELS` the world is drawn at. See `zoom`.
+    #[serde(skip)]
+    zoom_level: usize,
+    /// The last messages, oldest first. See `log`.
+    #[serde(skip)]
+    log: VecDeque<String>,
+    #[serde(skip)]
+    show_log: bool,
+    /// See `effects`.
+    #[serde(skip)]
+    effects: Vec<Effect>,
+    /// Waystones the player used, by name. See `waypoints`.
+    #[serde(default)]
+    waypoints: BTreeMap<String, Pos>,
+    /// The players other than the one being handled right now. See the
+    /// `player` module.
+    #[serde(default)]
+    other_players: Vec<Player>,
+    /// What we last told the player is in front of them. See
+    /// `describe_facing`.
+    #[serde(skip)]
+    last_facing: String,
+    /// Has anything happened since the game was last saved? See `is_dirty`.
+    #[serde(skip)]
+    dirty: bool,
+    /// What happened since the platform was last told. See `take_events`.
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    /// What changed since `take_changes` was last called. Nothing is kept
+    /// until it is first called.
+    #[serde(skip)]
+    changes: StateChanges,
+    #[serde(skip)]
+    seen: Option<changes::Seen>,
+    /// See `light_at`. Cleared by `set_tile` when a light source changes.
+    #[serde(skip)]
+    light_cache: RefCell<light::LightCache>,
+}
+
+fn default_seed() -> u32 {
+    DEFAULT_SEED
+}
+
+fn default_wall_density() -> f64 {
+    DEFAULT_WALL_DENSITY
+}
+
+pub const DEFAULT_WALL_DENSITY: f64 = 0.3;
+
+fn default_hunger() -> u8 {
+    MAX_HUNGER
+}
+
+/// How full the player is when they are not hungry at all. See
+/// `State::hunger`.
+pub const MAX_HUNGER: u8 = 20;
+
+fn default_fog_of_war() -> bool {
+    true
+}
+
+/// The world is split into chunks of `CHUNK_SIZE` by `CHUNK_SIZE` tiles, to
+/// keep track of what the player explored.
+pub const CHUNK_SIZE: i32 = 16;
+
+/// How far the player sees in every direction.
+pub const VIEW_RADIUS: i32 = 12;
+
+/// The position of the chunk that a tile is in.
+pub fn chunk_of(pos: Pos) -> Pos {
+    (pos.0.div_euclid(CHUNK_SIZE), pos.1.div_euclid(CHUNK_SIZE))
+}
+
+fn default_run_steps() -> u32 {
+    DEFAULT_RUN_STEPS
+}
+
+pub const DEFAULT_RUN_STEPS: u32 = 10;
+
+fn default_move_cooldown_ms() -> u32 {
+    DEFAULT_MOVE_COOLDOWN_MS
 }
 
+/// The least time between two steps, so held keys move the player at the
+/// same speed whatever the keyboard repeats at. See `State::move_cooldown`.
+pub const DEFAULT_MOVE_COOLDOWN_MS: u32 = 60;
+
+/// The longest `move_cooldown` can be set to.
+const MAX_MOVE_COOLDOWN_MS: u32 = 1000;
+
+/// How far a thrown rock flies.
+const THROW_RANGE: u32 = 10;
+
+/// The chance of getting a rock out of hitting a wall.
+const ROCK_CHANCE: f64 = 0.2;
+/// The chance of getting another rock out of breaking the last of a wall.
+const BONUS_ROCK_CHANCE: f64 = 0.1;
+
+/// How far ahead the player is told about what they are facing.
+const FACING_RANGE: u32 = 8;
+
+const STARTING_TORCHES: usize = 4;
+const STARTING_SAPLINGS: usize = 2;
+const STARTING_WAYSTONES: usize = 2;
+
+/// How far from a waystone the player can end up when going to it, if the
+/// tiles next to it are taken.
+const WAYPOINT_RANGE: i32 = 3;
+
+/// The most items that one `give` command gives.
+const MAX_GIVE: usize = 1000;
+
+/// The most tiles that one `regen` command regenerates.
+const MAX_REGEN_AREA: u64 = 100 * 100;
+
+/// The longest way the player walks by themselves. See `path_to`.
+const MAX_PATH_COST: u32 = 200;
+
+/// How many messages `State::log` keeps.
+const LOG_LEN: usize = 10;
+
+/// The most effects there are at once. See `State::effects`.
+const MAX_EFFECTS: usize = 16;
+/// How many ticks an effect stays.
+const EFFECT_TICKS: u8 = 3;
+
+/// How worlds are generated now. Bump this whenever generation changes, and
+/// keep generating worlds with an older `State::gen_version` the way they
+/// were (see `generate_noise_tile`), or the tiles that weren't changed in
+/// them (which aren't saved) would change. 0 is walls and open ground only,
+/// and 1 added everything else.
+pub const GEN_VERSION: u32 = 1;
+
+/// How far from the edge of a wall (in noise) it is generated broken.
+const WEATHERED_EDGE: f64 = 0.03;
+
+/// Open spaces where the temperature (in noise, from 0 to 1) is below this
+/// are ice.
+const ICE_TEMPERATURE: f64 = 0.25;
+/// Walls where the temperature is above this are sand.
+const SAND_TEMPERATURE: f64 = 0.75;
+/// How fast the noise that caves follow changes. Slower than the walls, so
+/// caves go on for a while.
+const CAVE_SCALE: f64 = 0.03;
+/// How close to 0 the cave noise is inside a cave. Bigger is wider caves.
+const CAVE_WIDTH: f64 = 0.04;
+/// The chance of a trader standing on an open tile of a new world.
+const TRADER_CHANCE: f64 = 0.0005;
+/// The chance of a berry bush on an open tile of a new world.
+const BUSH_CHANCE: f64 = 0.01;
+/// The player gets a bit hungrier every this many ticks (faster on harder
+/// difficulties, see `Rules::hunger_speed`)...
+const HUNGER_TICKS: u64 = 300;
+/// ...and every this many steps.
+const HUNGER_STEPS: u64 = 150;
+/// At this hunger or below, the player is told they are hungry.
+const HUNGRY_AT: u8 = 6;
+/// The hunger the player wakes up with after fainting from hunger.
+const FAINTED_HUNGER: u8 = 6;
+
+/// The most tiles the player slides on ice at once, in case there is a lot of
+/// it.
+const MAX_SLIDE: u32 = 50;
+
 impl Default for State {
     fn default() -> Self {
         Self::new()
@@ -90,129 +555,1939 @@ impl Default for State {
 
 impl State {
     pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// A new world, like `with_seed`, but with `inventory` instead of the
+    /// starter kit if it is given, for bringing it over from another world.
+    pub fn new_world(seed: u32, inventory: Option<Inventory>) -> Self {
+        let mut state = Self::with_seed(seed);
+        if let Some(inventory) = inventory {
+            state.inventory = inventory;
+        }
+        state
+    }
+
+    pub fn with_seed(seed: u32) -> Self {
+        // A starter kit: something to set a spawn point with, something to
+        // get through the first night, something to grow wood from, and
+        // something to find the way back with.
+        let mut inventory = Inventory::default();
+        let starter_kit = std::iter::once(Item::Bed)
+            .chain(std::iter::repeat_n(Item::Torch, STARTING_TORCHES))
+            .chain(std::iter::repeat_n(Item::Sapling(WoodKind::Oak), STARTING_SAPLINGS))
+            .chain(std::iter::repeat_n(Item::Waystone, STARTING_WAYSTONES))
+            .chain(std::iter::once(Item::Compass));
+        for item in starter_kit {
+            inventory
+                .insert(item)
+                .expect("The starter kit fits in an empty inventory");
+        }
         Self {
             tiles: HashMap::new().into(),
             player_pos: (0, 0),
             player_dir: Dir::Down,
             message: String::new(),
-            inventory: Inventory::default(),
-            menu: Menu::default(),
+            inventory,
+            menus: vec![],
             selected_item: None,
+            selected_index: 0,
+            last_built: None,
+            seed,
+            world_gen: WorldGen::default(),
+            gen_version: GEN_VERSION,
+            wall_density: DEFAULT_WALL_DENSITY,
+            hunger: MAX_HUNGER,
+            bounds: None,
+            run_steps: DEFAULT_RUN_STEPS,
+            meta: WorldMeta::default(),
+            created_at: 0,
+            last_played: 0,
+            move_cooldown_ms: DEFAULT_MOVE_COOLDOWN_MS,
+            auto_place: false,
+            explored: HashSet::new(),
+            fog_of_war: true,
+            dig_on_turn: false,
+            creative: false,
+            variants: HashMap::new(),
+            stats: Stats::default(),
+            achievements: HashSet::new(),
+            spawn_point: (0, 0),
+            language: Language::default(),
+            ticking: HashSet::new(),
+            unstable: HashSet::new(),
+            drops: HashMap::new(),
+            weather: Weather::default(),
+            auto_path: VecDeque::new(),
+            breaking: None,
+            zoom_level: 0,
+            log: VecDeque::new(),
+            show_log: false,
+            effects: vec![],
+            waypoints: BTreeMap::new(),
+            other_players: vec![],
+            last_facing: String::new(),
+            dirty: false,
+            events: vec![],
+            changes: StateChanges::default(),
+            seen: None,
+            light_cache: RefCell::default(),
         }
     }
 
-    fn generate_tile(pos: Pos) -> Tile {
-        let f = Perlin::new(12412).get([pos.0 as f64 * 0.1, pos.1 as f64 * 0.1]);
-        // now `f` is a value between -1.0 and 1.0
-        let f = (f + 1.0) / 2.0; // normalize to [0.0, 1.0]
-        if f < 0.3 { Tile::WallFull } else { Tile::Empty }
+    // NOTE: Tiles that are the same as their generated tile are not stored, so
+    // changing how the world generates after it was played in would change
+    // those tiles too. That's why these are only for creating new worlds, and
+    // why worlds keep the `GEN_VERSION` they were made with.
+
+    pub fn with_world_gen(mut self, world_gen: WorldGen) -> Self {
+        self.world_gen = world_gen;
+        self
     }
 
-    pub fn get_tile(&self, pos: Pos) -> Tile {
-        self.tiles
-            .borrow()
-            .get(&pos)
-            .cloned()
-            .unwrap_or_else(|| Self::generate_tile(pos))
+    /// Sets how much of the world is wall. Clamped to be between 0 and 1.
+    pub fn with_wall_density(mut self, wall_density: f64) -> Self {
+        self.wall_density = wall_density.clamp(0.0, 1.0);
+        self
     }
 
-    pub fn set_tile(&mut self, pos: Pos, tile: Tile) {
-        let mut tiles = self.tiles.borrow_mut();
-        if tile == Self::generate_tile(pos) {
-            tiles.remove(&pos);
-        } else {
-            tiles.insert(pos, tile);
-        }
+    pub fn seed(&self) -> u32 {
+        self.seed
     }
 
-    fn on_dir_input_no_menu(&mut self, dir: Dir, shift: IsShift) {
-        let dir_same = self.player_dir == dir;
+    pub fn player_pos(&self) -> Pos {
+        self.player_pos
+    }
 
-        if shift == IsShift::No {
-            self.player_dir = dir;
-        };
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
 
-        let new_pos = self.player_pos + dir;
-        let try_move = match shift {
-            IsShift::Yes => true,
-            IsShift::No => dir_same,
-        };
-        let can_dig = dir_same;
-        let tile = self.get_tile(new_pos);
-        if Tile::Empty == tile {
-            if try_move {
-                self.player_pos = new_pos;
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn meta(&self) -> &WorldMeta {
+        &self.meta
+    }
+
+    /// How the game plays at the difficulty of the world.
+    pub fn rules(&self) -> Rules {
+        self.meta.difficulty.rules()
+    }
+
+    /// The metadata can be changed freely, but `cheats_used` should never go
+    /// back to false.
+    pub fn meta_mut(&mut self) -> &mut WorldMeta {
+        &mut self.meta
+    }
+
+    /// When the world was made, in seconds since the Unix epoch, or 0 if not
+    /// known.
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// When the world was last saved, in seconds since the Unix epoch, or 0
+    /// if not known.
+    pub fn last_played(&self) -> u64 {
+        self.last_played
+    }
+
+    /// Does the player have a compass, to show them the way? See
+    /// `Item::Compass`.
+    pub fn has_compass(&self) -> bool {
+        self.inventory.count_of(&Item::Compass) > 0
+    }
+
+    /// The waystones the player can go back to, by name. A waystone that was
+    /// broken stays here until the player tries to go to it.
+    pub fn waypoints(&self) -> &BTreeMap<String, Pos> {
+        &self.waypoints
+    }
+
+    pub fn spawn_point(&self) -> Pos {
+        self.spawn_point
+    }
+
+    /// The top left and bottom right corners of the world, both in it, or
+    /// `None` if the world goes on forever. Outside them is only
+    /// `Tile::Barrier`.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        self.bounds
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<(Pos, Pos)>) {
+        self.bounds = bounds;
+        self.light_cache.get_mut().clear();
+    }
+
+    /// Is a position inside the bounds of the world (if it has any)?
+    pub fn in_bounds(&self, (x, y): Pos) -> bool {
+        match self.bounds {
+            Some(((left, top), (right, bottom))) => {
+                (left..=right).contains(&x) && (top..=bottom).contains(&y)
             }
-        } else if can_dig {
-            // We are breaking the tile!
-            match tile.breaks_into() {
-                tiles::BreakResult::Tile(tile) => self.set_tile(new_pos, tile),
-                tiles::BreakResult::Item(item) => {
-                    // TODO: Save in some inventory
-                    self.inventory.insert(item);
-                    self.set_tile(new_pos, Tile::Empty);
-                }
-                tiles::BreakResult::CannotBeBroken => (),
+            None => true,
+        }
+    }
+
+    /// Was there input since the game was last saved? The game loop saves
+    /// only every so often, and always saves a dirty game when it ends.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// The events that happened since this was last called, in order.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// What changed since this was last called. The first call is where
+    /// following the changes starts, so it has none.
+    pub fn take_changes(&mut self) -> StateChanges {
+        let mut changes = std::mem::take(&mut self.changes);
+        let inventory: Vec<(Item, usize)> = self.inventory.iter().collect();
+        if let Some(seen) = &self.seen {
+            changes.inventory = seen.inventory_changes(&inventory);
+            if seen.player_pos != self.player_pos {
+                changes.player_moved = Some((seen.player_pos, self.player_pos));
             }
         }
+        self.seen = Some(changes::Seen {
+            player_pos: self.player_pos,
+            inventory,
+        });
+        changes
     }
 
-    fn on_dir_input_inventory(&mut self, dir: Dir, shift: IsShift) {
-        let is_advancing = dir == Dir::Right || dir == Dir::Down;
-        match &mut self.selected_item {
-            Some(item) => *item = if is_advancing { 
-                self.inventory.next(item) 
-            } else { 
-                self.inventory.prev(item) 
+    /// How full the player is, from 0 to `MAX_HUNGER`. It goes down as time
+    /// passes and as the player walks, and up when they eat. When it would go
+    /// below 0, the player faints and wakes up at their spawn point.
+    pub fn hunger(&self) -> u8 {
+        self.hunger
+    }
+
+    /// Takes one off `hunger`, if the difficulty has hunger at all.
+    fn get_hungrier(&mut self) {
+        if !self.rules().hunger {
+            return;
+        }
+        if self.hunger == 0 {
+            self.respawn();
+            self.hunger = FAINTED_HUNGER;
+            self.message = "You fainted from hunger, and woke up at your spawn point.".to_string();
+            return;
+        }
+        self.hunger -= 1;
+        if self.hunger == HUNGRY_AT {
+            self.message = "You are getting hungry.".to_string();
+        }
+    }
+
+    /// Eats one of the selected item, if it is food and the player is hungry.
+    fn on_eat(&mut self) {
+        let Some(item) = self.selected_item.clone() else {
+            self.message = "You have no item selected to eat.".to_string();
+            return;
+        };
+        let name = item.name(self.language);
+        if !item.is_food() {
+            self.message = format!("You can't eat {name}.");
+        } else if self.hunger >= MAX_HUNGER {
+            self.message = "You are not hungry.".to_string();
+        } else if self.inventory.remove(&item).is_err() {
+            self.message = format!("You have no {name} left.");
+        } else {
+            self.hunger = self.hunger.saturating_add(item.nutrition()).min(MAX_HUNGER);
+            self.message = format!("You ate a {name}.");
+        }
+    }
+
+    /// Brings the player back to their spawn point.
+    pub fn respawn(&mut self) {
+        self.player_pos = self.spawn_point;
+        self.message = "You woke up at your spawn point.".to_string();
+    }
+
+    pub fn has_achievement(&self, achievement: Achievement) -> bool {
+        self.achievements.contains(&achievement)
+    }
+
+    pub fn run_steps(&self) -> u32 {
+        self.run_steps
+    }
+
+    pub fn set_run_steps(&mut self, run_steps: u32) {
+        self.run_steps = run_steps;
+    }
+
+    /// The least time between two steps. Steps that come sooner are ignored
+    /// by the game loop.
+    pub fn move_cooldown(&self) -> Duration {
+        Duration::from_millis(self.move_cooldown_ms.into())
+    }
+
+    pub fn is_creative(&self) -> bool {
+        self.creative
+    }
+
+    /// Turning creative mode on counts as cheating. See
+    /// `WorldMeta::cheats_used`.
+    pub fn set_creative(&mut self, creative: bool) {
+        self.creative = creative;
+        self.meta.cheats_used |= creative;
+    }
+
+    /// Clamped to be at most a second.
+    pub fn set_move_cooldown(&mut self, cooldown: Duration) {
+        let ms = cooldown.as_millis().min(MAX_MOVE_COOLDOWN_MS.into());
+        self.move_cooldown_ms = ms as u32;
+    }
+
+    pub fn dig_on_turn(&self) -> bool {
+        self.dig_on_turn
+    }
+
+    pub fn set_dig_on_turn(&mut self, dig_on_turn: bool) {
+        self.dig_on_turn = dig_on_turn;
+    }
+
+    /// The smallest area that has everything the player explored and did in
+    /// it (including the player).
+    pub fn explored_area(&self) -> (Pos, Pos) {
+        let tiles = self.tiles.borrow();
+        let chunk_corners = self.explored.iter().flat_map(|&(x, y)| {
+            let top_left = (x * CHUNK_SIZE, y * CHUNK_SIZE);
+            let bottom_right = (top_left.0 + CHUNK_SIZE - 1, top_left.1 + CHUNK_SIZE - 1);
+            [top_left, bottom_right]
+        });
+        tiles.keys().copied().chain(chunk_corners).fold(
+            (self.player_pos, self.player_pos),
+            |((left, top), (right, bottom)), (x, y)| {
+                ((left.min(x), top.min(y)), (right.max(x), bottom.max(y)))
             },
-            None => self.selected_item = self.inventory.first().cloned(),
+        )
+    }
+
+    /// Did the player see this position? Always true without fog of war.
+    pub fn is_explored(&self, pos: Pos) -> bool {
+        let (dx, dy) = (pos.0 - self.player_pos.0, pos.1 - self.player_pos.1);
+        let in_view = dx.abs() <= VIEW_RADIUS && dy.abs() <= VIEW_RADIUS;
+        !self.fog_of_war || in_view || self.explored.contains(&chunk_of(pos))
+    }
+
+    /// Marks every chunk the player can see as explored.
+    fn explore(&mut self) {
+        let (left, top) = chunk_of((
+            self.player_pos.0 - VIEW_RADIUS,
+            self.player_pos.1 - VIEW_RADIUS,
+        ));
+        let (right, bottom) = chunk_of((
+            self.player_pos.0 + VIEW_RADIUS,
+            self.player_pos.1 + VIEW_RADIUS,
+        ));
+        for x in left..=right {
+            for y in top..=bottom {
+                self.explored.insert((x, y));
+            }
         }
     }
 
-    fn on_dir_input(&mut self, dir: Dir, shift: IsShift) {
-        match self.menu {
-            Menu::None => self.on_dir_input_no_menu(dir, shift),
-            Menu::Inventory => self.on_dir_input_inventory(dir, shift),
+    /// The tile that the world starts with at a position, before anything
+    /// changed it: walls, with caves through them, and open ground between.
+    pub fn generate_tile(&self, pos: Pos) -> Tile {
+        self.generate_tile_with(&Perlin::new(self.seed), pos)
+    }
+
+    /// Like `generate_tile`, but with noise that was already made, because
+    /// making it isn't free.
+    fn generate_tile_with(&self, noise: &Perlin, pos: Pos) -> Tile {
+        match self.world_gen {
+            WorldGen::Noise => self.generate_noise_tile(noise, pos),
+            // Remember that y grows downwards.
+            WorldGen::Flat if pos.1 > 0 => Tile::WallFull,
+            WorldGen::Flat => Tile::Empty,
         }
     }
 
-    fn on_build(&mut self) {
-        let build_pos = self.player_pos + self.player_dir;
-        if self.get_tile(build_pos) != Tile::Empty {
-            self.message = "You cannot build on existing tiles.".to_string();
-            return; // Do not build on existing tiles
+    fn generate_noise_tile(&self, noise: &Perlin, pos: Pos) -> Tile {
+        let f = noise.get([pos.0 as f64 * 0.1, pos.1 as f64 * 0.1]);
+        // now `f` is a value between -1.0 and 1.0
+        let f = (f + 1.0) / 2.0; // normalize to [0.0, 1.0]
+        if self.gen_version == 0 {
+            return if f < self.wall_density { Tile::WallFull } else { Tile::Empty };
+        }
+        // Temperature changes slower than walls do, so it is cold or hot in
+        // big regions. It is taken far away from the walls' noise, so the two
+        // don't line up.
+        let t = noise.get([pos.0 as f64 * 0.02 + 1000.5, pos.1 as f64 * 0.02 + 1000.5]);
+        let t = (t + 1.0) / 2.0;
+        // Caves are where another noise crosses 0, which is along thin lines
+        // that go on and meet, so they make tunnels through the walls.
+        let c = noise.get([pos.0 as f64 * CAVE_SCALE - 2000.5, pos.1 as f64 * CAVE_SCALE - 2000.5]);
+        let cave = c.abs() < CAVE_WIDTH;
+        if f >= self.wall_density || cave {
+            let mut rng = hash_rng(self.seed as u64, pos, 0, salt::TRADER);
+            if t < ICE_TEMPERATURE {
+                Tile::Ice
+            } else if rng.gen_bool(TRADER_CHANCE) {
+                Tile::Trader
+            } else if rng.gen_bool(BUSH_CHANCE) {
+                Tile::Bush(true)
+            } else {
+                Tile::Empty
+            }
+        } else if f >= self.wall_density - WEATHERED_EDGE / 2.0 {
+            // Walls are a bit broken at the edges, so they look weathered.
+            Tile::WallLow
+        } else if f >= self.wall_density - WEATHERED_EDGE {
+            Tile::WallHalf
+        } else if t > SAND_TEMPERATURE {
+            // Inside the weathered edges, so it has something to stand on.
+            Tile::Sand
+        } else {
+            Tile::WallFull
         }
-        let Some(selected_item) = self.selected_item.clone() else {
-            self.message = "You have no item selected to build.".to_string();
-            return; // Do not build if no selected item
-        };
-        let Some(tile) = selected_item.to_tile() else {
-            let name = selected_item.name();
-            self.message = format!("You cannot build a {name}.");
-            return;
-        };
-        self.inventory.remove(&selected_item).expect("Item should be in the inventory");
-        self.set_tile(build_pos, tile);
     }
 
-    fn tick(&mut self) {
-        let tile_in_front = self.get_tile(self.player_pos + self.player_dir);
-        let tile_name = tile_in_front.name();
-        if self.message.is_empty() && tile_in_front != Tile::Empty {
-            self.message = format!("You are facing a {tile_name}")
+    pub fn get_tile(&self, pos: Pos) -> Tile {
+        if !self.in_bounds(pos) {
+            return Tile::Barrier;
         }
+        self.tiles
+            .borrow()
+            .get(&pos)
+            .cloned()
+            .unwrap_or_else(|| self.generate_tile(pos))
     }
 
-    pub fn on_input(mut self, input: Input) -> Option<Self> {
-        self.message.clear();
-        match input {
-            Input::Dir(dir, shift) => self.on_dir_input(dir, shift),
-            Input::Build => self.on_build(),
-            Input::Quit => return None,
-            Input::OpenInventory => self.menu = Menu::Inventory,
-            Input::CloseMenu => self.menu = Menu::None,
+    /// The tiles in a rectangle, row by row. Same as calling `get_tile` for
+    /// each position, but faster.
+    pub fn tiles_in_rect(&self, top_left: Pos, width: u32, height: u32) -> Vec<Tile> {
+        self.region(top_left, width, height).map(|(_, tile)| tile).collect()
+    }
+
+    /// The tiles in a rectangle with their positions, row by row. Tiles are
+    /// only generated as they are reached, so nothing outside the rectangle
+    /// (or after where iterating stopped) is generated. Drawing and exporting
+    /// the map get their tiles from here, or from `tiles_at` when sampling.
+    ///
+    /// Don't change tiles while iterating, as the tiles are borrowed.
+    pub fn region(
+        &self,
+        top_left: Pos,
+        width: u32,
+        height: u32,
+    ) -> impl Iterator<Item = (Pos, Tile)> + '_ {
+        let tiles = self.tiles.borrow();
+        let noise = Perlin::new(self.seed);
+        (0..height as i32)
+            .flat_map(move |y| (0..width as i32).map(move |x| (top_left.0 + x, top_left.1 + y)))
+            .map(move |pos| {
+                if !self.in_bounds(pos) {
+                    return (pos, Tile::Barrier);
+                }
+                let tile = tiles.get(&pos).copied();
+                (pos, tile.unwrap_or_else(|| self.generate_tile_with(&noise, pos)))
+            })
+    }
+
+    /// The tiles at some positions, in order. Same as calling `get_tile` for
+    /// each position, but faster.
+    pub fn tiles_at(&self, positions: impl IntoIterator<Item = Pos>) -> Vec<Tile> {
+        let tiles = self.tiles.borrow();
+        let noise = Perlin::new(self.seed);
+        positions
+            .into_iter()
+            .map(|pos| {
+                if !self.in_bounds(pos) {
+                    return Tile::Barrier;
+                }
+                let tile = tiles.get(&pos).copied();
+                tile.unwrap_or_else(|| self.generate_tile_with(&noise, pos))
+            })
+            .collect()
+    }
+
+    pub fn is_night(&self) -> bool {
+        light::is_night(self.stats.ticks_survived)
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    /// How many tiles wide and tall every cell on the screen is. More than 1
+    /// when the player zoomed out to look around.
+    pub fn zoom(&self) -> i32 {
+        render::ZOOM_LEVELS[self.zoom_level]
+    }
+
+    fn on_cycle_zoom(&mut self) {
+        self.zoom_level = (self.zoom_level + 1) % render::ZOOM_LEVELS.len();
+        self.message = match self.zoom() {
+            1 => "Zoomed back in.".to_string(),
+            zoom => format!("Zoomed out: every tile you see is {zoom} by {zoom} tiles."),
+        };
+    }
+
+    /// The light level everywhere, before light sources. Rain makes it a bit
+    /// darker.
+    pub fn ambient_light(&self) -> u8 {
+        let light = if self.is_night() { light::NIGHT_LIGHT } else { MAX_LIGHT };
+        match self.weather {
+            Weather::Clear => light,
+            Weather::Rain => light.saturating_sub(1),
         }
+    }
+
+    /// The light level at a tile. Remembered until a light source is placed
+    /// or broken, or the ambient light changes.
+    pub fn light_at(&self, pos: Pos) -> u8 {
+        self.light_in_rect(pos, 1, 1)[0]
+    }
+
+    /// The light levels in a rectangle, row by row like `tiles_in_rect`. Only
+    /// light sources close enough to the rectangle are looked at, so keep it
+    /// to what's on the screen.
+    pub fn light_in_rect(&self, top_left: Pos, width: u32, height: u32) -> Vec<u8> {
+        let ambient = self.ambient_light();
+        let mut light = vec![ambient; width as usize * height as usize];
+        if ambient == MAX_LIGHT {
+            return light; // Nothing can be brighter
+        }
+        let positions = || {
+            (0..height as i32)
+                .flat_map(move |y| (0..width as i32).map(move |x| (top_left.0 + x, top_left.1 + y)))
+        };
+        let mut cache = self.light_cache.borrow_mut();
+        if let Some(cached) = positions().map(|pos| cache.get(ambient, pos)).collect() {
+            return cached;
+        }
+        // Light sources this far out can still reach into the rectangle.
+        let margin = MAX_LIGHT as i32;
+        let outer_top_left = (top_left.0 - margin, top_left.1 - margin);
+        let outer_width = width + 2 * margin as u32;
+        let outer_height = height + 2 * margin as u32;
+        let outer = self.tiles_in_rect(outer_top_left, outer_width, outer_height);
+        for (i, tile) in outer.into_iter().enumerate() {
+            let emission = tile.light_emission();
+            if emission == 0 {
+                continue;
+            }
+            let source = (
+                outer_top_left.0 + (i as u32 % outer_width) as i32,
+                outer_top_left.1 + (i as u32 / outer_width) as i32,
+            );
+            for y in 0..height as i32 {
+                for x in 0..width as i32 {
+                    let pos = (top_left.0 + x, top_left.1 + y);
+                    let level = light::falloff(emission, (pos.0 - source.0, pos.1 - source.1));
+                    let cell = &mut light[(y * width as i32 + x) as usize];
+                    *cell = (*cell).max(level);
+                }
+            }
+        }
+        for (pos, &level) in positions().zip(&light) {
+            cache.insert(pos, level);
+        }
+        light
+    }
+
+    /// Stops storing tiles that are the same as their generated tile. `set_tile`
+    /// already does this, but old saves and changes to generation can still
+    /// leave some behind.
+    pub fn compact_tiles(&mut self) {
+        let noise = Perlin::new(self.seed);
+        let mut tiles = self.tiles.borrow_mut();
+        tiles.retain(|&pos, &mut tile| tile != self.generate_tile_with(&noise, pos));
+    }
+
+    /// Does nothing outside the bounds of the world.
+    pub fn set_tile(&mut self, pos: Pos, tile: Tile) {
+        if !self.in_bounds(pos) {
+            return;
+        }
+        let old = self.get_tile(pos);
+        if self.seen.is_some() && old != tile {
+            self.changes.tiles.push(TileChange { pos, old, new: tile });
+        }
+        if tile.light_emission() > 0 || old.light_emission() > 0 {
+            self.light_cache.get_mut().clear();
+        }
+        if tile.needs_tick() {
+            self.ticking.insert(pos);
+        } else {
+            self.ticking.remove(&pos);
+        }
+        // The tile might fall, or stop holding up the one above it.
+        self.unstable.insert(pos);
+        self.unstable.insert(pos + Dir::Up);
+        let mut tiles = self.tiles.borrow_mut();
+        if tile == self.generate_tile(pos) {
+            tiles.remove(&pos);
+        } else {
+            tiles.insert(pos, tile);
+        }
+    }
+
+    /// The items lying on the ground at a position.
+    pub fn drops_at(&self, pos: Pos) -> &[Item] {
+        self.drops.get(&pos).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn drop_item(&mut self, pos: Pos, item: Item) {
+        self.drops.entry(pos).or_default().push(item);
+    }
+
+    /// Picks up the items on the ground where the player is and next to
+    /// them. What doesn't fit in the inventory stays on the ground.
+    fn pick_up_drops(&mut self) {
+        let here = self.player_pos;
+        let mut picked_up = vec![];
+        let mut selected = None;
+        for pos in [here, here + Dir::Up, here + Dir::Down, here + Dir::Left, here + Dir::Right] {
+            let Some(items) = self.drops.remove(&pos) else {
+                continue;
+            };
+            let mut left = vec![];
+            for item in items {
+                match self.inventory.insert(item.clone()) {
+                    Ok(()) => {
+                        if self.select_if_new(&item) {
+                            selected = Some(item.clone());
+                        }
+                        picked_up.push(item);
+                    }
+                    Err(InventoryFull) => left.push(item),
+                }
+            }
+            if !left.is_empty() {
+                self.drops.insert(pos, left);
+            }
+        }
+        if !picked_up.is_empty() {
+            self.events.push(GameEvent::Pickup);
+            self.add_effect(here + Dir::Up, '+', Color::Green);
+        }
+        if let Some(item) = selected {
+            self.message = self.language.message(Message::Selected(item.name(self.language)));
+            return;
+        }
+        self.message = match picked_up.as_slice() {
+            [] => return,
+            [item] => self.language.message(Message::PickedUp(item.name(self.language))),
+            items => self.language.message(Message::PickedUpItems(items.len())),
+        };
+    }
+
+    /// Can the player move into this position? It needs a passable tile and no
+    /// other player on it.
+    fn is_free(&self, pos: Pos) -> bool {
+        self.get_tile(pos).is_passable() && self.other_players.iter().all(|p| p.pos != pos)
+    }
+
+    pub fn other_players(&self) -> &[Player] {
+        &self.other_players
+    }
+
+    /// Runs `f` with the player at `index` (0 is the first player) as the one
+    /// in `State`'s own fields. Does nothing if there is no such player.
+    pub(crate) fn as_player(&mut self, index: usize, f: impl FnOnce(&mut Self)) {
+        if index == 0 {
+            return f(self);
+        }
+        if index > self.other_players.len() {
+            return;
+        }
+        self.swap_player(index - 1);
+        f(self);
+        self.swap_player(index - 1);
+    }
+
+    /// Swaps the player in `State`'s own fields with another player.
+    pub(crate) fn swap_player(&mut self, other: usize) {
+        let other = &mut self.other_players[other];
+        std::mem::swap(&mut self.player_pos, &mut other.pos);
+        std::mem::swap(&mut self.player_dir, &mut other.dir);
+        std::mem::swap(&mut self.inventory, &mut other.inventory);
+        std::mem::swap(&mut self.selected_item, &mut other.selected_item);
+        std::mem::swap(&mut self.last_built, &mut other.last_built);
+        std::mem::swap(&mut self.hunger, &mut other.hunger);
+    }
+
+    /// Adds a second player next to the first.
+    fn on_join(&mut self) {
+        if !self.other_players.is_empty() {
+            self.message = "There is already a second player.".to_string();
+            return;
+        }
+        self.message = match self.add_player() {
+            Some(_) => "A second player joined!".to_string(),
+            None => "There is no room for a second player here.".to_string(),
+        };
+    }
+
+    /// Adds a player next to the first one, and returns their index (see
+    /// `as_player`). `None` if there is no room.
+    pub(crate) fn add_player(&mut self) -> Option<usize> {
+        let here = self.player_pos;
+        let pos = [Dir::Right, Dir::Left, Dir::Down, Dir::Up]
+            .map(|dir| here + dir)
+            .into_iter()
+            .find(|&pos| self.is_free(pos))?;
+        self.other_players.push(Player::new(pos));
+        Some(self.other_players.len())
+    }
+
+    /// Removes the second player. What they had is left on the ground.
+    fn on_leave(&mut self) {
+        let Some(player) = self.other_players.pop() else {
+            self.message = "There is no second player.".to_string();
+            return;
+        };
+        for (item, count) in player.inventory.iter() {
+            for _ in 0..count {
+                self.drop_item(player.pos, item.clone());
+            }
+        }
+        self.message = "The second player left.".to_string();
+    }
+
+    /// Moves the player to a new position, which should be empty. Places the
+    /// selected item behind them if auto placing is on.
+    fn move_player(&mut self, new_pos: Pos) {
+        let old_pos = self.player_pos;
+        self.player_pos = new_pos;
+        self.stats.steps_taken += 1;
+        if self.stats.steps_taken.is_multiple_of(HUNGER_STEPS / self.rules().hunger_speed) {
+            self.get_hungrier();
+        }
+        // Walking away from a tile stops digging at it.
+        self.breaking = None;
+        // The player was holding up what is above them. See `fall`.
+        self.unstable.insert(old_pos + Dir::Up);
+        if self.auto_place {
+            self.auto_place_at(old_pos);
+        }
+    }
+
+    fn auto_place_at(&mut self, pos: Pos) {
+        let Some(item) = self.selected_item.clone() else {
+            return;
+        };
+        let Some(tile) = self.tile_to_build(&item) else {
+            return;
+        };
+        if self.inventory.remove(&item).is_err() {
+            return;
+        }
+        // No need to check if the position is empty: the player was just
+        // standing on it. And if `set_tile` stops storing the tile because it
+        // is the generated one, it is still there when we get it.
+        self.set_tile(pos, tile);
+        self.stats.tiles_placed += 1;
+        self.events.push(GameEvent::Build);
+        if self.inventory.count_of(&item) == 0 {
+            let name = item.name(self.language);
+            self.message = self.language.message(Message::StoppedAutoPlace(name));
+            self.auto_place = false;
+        }
+    }
+
+    /// Keeps moving the player while they stand on a slippery tile, until
+    /// something is in the way or they get off it.
+    fn slide(&mut self, dir: Dir) {
+        for _ in 0..MAX_SLIDE {
+            let new_pos = self.player_pos + dir;
+            if !self.get_tile(self.player_pos).is_slippery() || !self.is_free(new_pos) {
+                break;
+            }
+            self.move_player(new_pos);
+        }
+    }
+
+    fn on_toggle_auto_place(&mut self) {
+        self.auto_place = !self.auto_place;
+        self.message = if self.auto_place {
+            "You will place your selected item behind you.".to_string()
+        } else {
+            "You stopped placing items behind you.".to_string()
+        };
+    }
+
+    fn on_dir_input_no_menu(&mut self, dir: Dir, shift: IsShift) {
+        let dir_same = self.player_dir == dir;
+
+        if shift == IsShift::No {
+            self.player_dir = dir;
+        };
+
+        let new_pos = self.player_pos + dir;
+        let try_move = match shift {
+            IsShift::Yes => true,
+            IsShift::No => dir_same,
+        };
+        // Moving without turning never digs to the side.
+        let can_dig = dir_same || (self.dig_on_turn && shift == IsShift::No);
+        if self.is_free(new_pos) {
+            if try_move {
+                self.move_player(new_pos);
+                self.slide(dir);
+            }
+        } else if can_dig {
+            self.dig(new_pos);
+        }
+    }
+
+    /// Digs at the tile next to the player in a direction, whichever way they
+    /// are facing.
+    fn dig_at(&mut self, dir: Dir) {
+        let pos = self.player_pos + dir;
+        if !self.get_tile(pos).is_passable() {
+            self.dig(pos);
+        }
+    }
+
+    /// Digs at a tile once. When it was dug at enough times (see
+    /// `Tile::hits_to_break`), it is hit, and the items from it are given to
+    /// the player.
+    fn dig(&mut self, pos: Pos) {
+        let hits = match self.breaking {
+            Some((breaking, hits)) if breaking == pos => hits + 1,
+            // A different tile, so start over.
+            _ => 1,
+        };
+        let tile = self.get_tile(pos);
+        if hits < tile.hits_to_break(self.selected_item.as_ref(), &self.rules()) {
+            self.breaking = Some((pos, hits));
+            return;
+        }
+        self.breaking = None;
+        // We are breaking the tile!
+        for item in self.hit_tile(pos) {
+            self.give_item(item, pos);
+        }
+    }
+
+    /// The shortest way for the player to walk to `target`, as the
+    /// directions to step in, going around what's in the way (and around
+    /// ice, which can't be walked on carefully). `None` if it's more than
+    /// `max_cost` steps away, or can't be reached.
+    pub fn path_to(&self, target: Pos, max_cost: u32) -> Option<Vec<Dir>> {
+        let tiles = self.tiles.borrow();
+        let noise = Perlin::new(self.seed);
+        let is_free = |pos: Pos| {
+            if !self.in_bounds(pos) {
+                return false;
+            }
+            let tile = tiles.get(&pos).copied();
+            let tile = tile.unwrap_or_else(|| self.generate_tile_with(&noise, pos));
+            tile.is_passable()
+                && !tile.is_slippery()
+                && self.other_players.iter().all(|p| p.pos != pos)
+        };
+        path::find_path(self.player_pos, target, max_cost, is_free)
+    }
+
+    /// Is the player walking somewhere by themselves? If so, the platform
+    /// should call `walk_step` every so often. See `Platform::poll_input`.
+    pub fn is_walking(&self) -> bool {
+        self.menus.is_empty() && !self.auto_path.is_empty()
+    }
+
+    /// Starts walking to `target` by itself, if there is a way.
+    fn walk_to(&mut self, target: Pos) {
+        match self.path_to(target, MAX_PATH_COST) {
+            Some(path) => self.auto_path = path.into(),
+            None => self.message = "You can't find a way there.".to_string(),
+        }
+    }
+
+    /// Takes the next step of walking by itself, and moves the game one tick
+    /// forward. Stops walking if something got in the way.
+    pub fn walk_step(&mut self) {
+        let Some(dir) = self.auto_path.pop_front() else {
+            return;
+        };
+        self.dirty = true;
+        self.message.clear();
+        let new_pos = self.player_pos + dir;
+        self.player_dir = dir;
+        if self.is_free(new_pos) {
+            self.move_player(new_pos);
+        } else {
+            self.auto_path.clear();
+            self.message = "Something is in the way.".to_string();
+        }
+        self.tick();
+    }
+
+    /// The tile the player is digging at, and how many times they hit it.
+    pub fn breaking(&self) -> Option<(Pos, u8)> {
+        self.breaking
+    }
+
+    /// Hits a tile once, breaking it a bit. Returns the items that came out
+    /// of it.
+    fn hit_tile(&mut self, pos: Pos) -> Vec<Item> {
+        let tile = self.get_tile(pos);
+        let mut items = vec![];
+        if tile.drops_rocks() && self.rng(pos, salt::ROCK).gen_bool(ROCK_CHANCE) {
+            items.push(Item::Rock);
+        }
+        match tile.breaks_into() {
+            tiles::BreakResult::Tile(tile) => {
+                self.set_tile(pos, tile);
+                self.add_effect(pos, '*', Color::Yellow);
+            }
+            tiles::BreakResult::Item(item) => {
+                // The last of a wall crumbles, so there is more to find in it.
+                let mut rng = self.rng(pos, salt::BONUS_ROCK);
+                if tile == Tile::WallLow && rng.gen_bool(BONUS_ROCK_CHANCE) {
+                    items.push(Item::Rock);
+                }
+                items.push(item);
+                self.set_tile(pos, Tile::Empty);
+                self.stats.tiles_broken += 1;
+                self.events.push(GameEvent::Break);
+                self.add_effect(pos, '✶', Color::Yellow);
+            }
+            tiles::BreakResult::Harvest(tile, item) => {
+                items.push(item);
+                self.set_tile(pos, tile);
+                self.add_effect(pos, '✶', Color::Yellow);
+            }
+            tiles::BreakResult::CannotBeBroken => (),
+        }
+        items
+    }
+
+    /// What to draw over tiles where something just happened, oldest first:
+    /// a tile that was hit, or items that were picked up.
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// Shows `glyph` at `pos` for a few ticks. The oldest effect goes if
+    /// there are too many.
+    fn add_effect(&mut self, pos: Pos, glyph: char, color: Color) {
+        if self.effects.len() >= MAX_EFFECTS {
+            self.effects.remove(0);
+        }
+        self.effects.push(Effect {
+            pos,
+            glyph,
+            color,
+            ttl: EFFECT_TICKS,
+        });
+    }
+
+    /// Puts an item in the inventory, or drops it at `pos` if it doesn't fit.
+    fn give_item(&mut self, item: Item, pos: Pos) {
+        if self.inventory.insert(item.clone()).is_err() {
+            // Drop it instead, so the item is not lost.
+            let name = item.name(self.language);
+            self.message = self.language.message(Message::InventoryFull(name));
+            self.drop_item(pos, item);
+        } else if self.select_if_new(&item) {
+            let name = item.name(self.language);
+            self.message = self.language.message(Message::Selected(name));
+        }
+    }
+
+    /// Selects an item that was just got for the first time, if nothing is
+    /// selected. Returns whether it did.
+    fn select_if_new(&mut self, item: &Item) -> bool {
+        if self.selected_item.is_some() || self.inventory.count_of(item) != 1 {
+            return false;
+        }
+        self.selected_item = Some(item.clone());
+        true
+    }
+
+    /// Opens the inventory. If the selected item ran out, selects the one that
+    /// is where it was instead, or the last one.
+    fn open_inventory(&mut self) {
+        self.push_menu(Menu::Inventory);
+        if let Some(item) = &self.selected_item
+            && self.inventory.count_of(item) == 0
+        {
+            let mut items = self.inventory.iter().map(|(item, _)| item);
+            self.selected_item = match items.nth(self.selected_index) {
+                Some(item) => Some(item),
+                None => self.inventory.iter().last().map(|(item, _)| item),
+            };
+        }
+    }
+
+    /// Keeps `selected_index` up to date, while the selected item is still
+    /// there.
+    fn remember_selection(&mut self) {
+        let Some(selected) = &self.selected_item else {
+            return;
+        };
+        if let Some(index) = self.inventory.iter().position(|(item, _)| &item == selected) {
+            self.selected_index = index;
+        }
+    }
+
+    /// Throws a rock ahead, which hits the first tile in the way once. A rock
+    /// that hits nothing lands on the ground.
+    fn on_throw(&mut self) {
+        if self.inventory.remove(&Item::Rock).is_err() {
+            let name = Item::Rock.name(self.language);
+            self.message = self.language.message(Message::OutOf(name));
+            return;
+        }
+        let dir = self.player_dir;
+        match self.first_obstacle(self.player_pos, dir, THROW_RANGE) {
+            Some((pos, tile)) => {
+                // The rock breaks, and whatever it knocked off the tile lands
+                // where the tile is.
+                for item in self.hit_tile(pos) {
+                    self.drop_item(pos, item);
+                }
+                let name = tile.name(self.language);
+                self.message = self.language.message(Message::ThrownAt(name));
+            }
+            None => {
+                let mut landed = self.player_pos;
+                for _ in 0..THROW_RANGE {
+                    landed = landed + dir;
+                }
+                self.drop_item(landed, Item::Rock);
+            }
+        }
+    }
+
+    /// Randomness for something at `pos` on this tick. See `hash_rng`.
+    fn rng(&self, pos: Pos, salt: u64) -> HashRng {
+        hash_rng(self.seed as u64, pos, self.stats.ticks_survived, salt)
+    }
+
+    /// Moves the player in a direction until they are blocked, they moved
+    /// `run_steps` tiles, or they pass something interesting on the sides.
+    /// Walls that keep going on the side (like in a corridor) are not
+    /// interesting, but them starting or ending is.
+    fn on_run(&mut self, dir: Dir) {
+        self.player_dir = dir;
+        let sides = |state: &Self| dir.sides().map(|side| state.get_tile(state.player_pos + side));
+        let start_sides = sides(self);
+        for _ in 0..self.run_steps {
+            let new_pos = self.player_pos + dir;
+            if !self.is_free(new_pos) {
+                break;
+            }
+            self.move_player(new_pos);
+            self.slide(dir);
+            if sides(self) != start_sides {
+                break;
+            }
+        }
+    }
+
+    /// Clicking a tile that can be walked on starts walking there by itself
+    /// (see `is_walking`). Clicking a tile next to the player that can't be
+    /// walked on digs it, like pressing its direction twice. Clicking such a
+    /// tile further away only turns the player towards it.
+    fn on_click_tile(&mut self, pos: Pos) {
+        let Some(dir) = Dir::towards(self.player_pos, pos) else {
+            return;
+        };
+        if self.is_free(pos) {
+            self.walk_to(pos);
+        } else if pos == self.player_pos + dir {
+            self.player_dir = dir;
+            self.on_dir_input_no_menu(dir, IsShift::No);
+        } else {
+            self.player_dir = dir;
+        }
+    }
+
+    /// Builds on a tile that was clicked, if it is next to the player.
+    fn on_build_at(&mut self, pos: Pos) {
+        let Some(dir) = Dir::towards(self.player_pos, pos) else {
+            return;
+        };
+        self.player_dir = dir;
+        if pos == self.player_pos + dir {
+            self.build_at(dir);
+        }
+    }
+
+    fn on_dir_input_inventory(&mut self, dir: Dir) {
+        let is_advancing = dir == Dir::Right || dir == Dir::Down;
+        self.selected_item = match &self.selected_item {
+            Some(item) if is_advancing => self.inventory.next(item),
+            Some(item) => self.inventory.prev(item),
+            None => self.inventory.first().cloned(),
+        };
+    }
+
+    /// Confirms the selected item and closes the inventory.
+    fn on_confirm_inventory(&mut self) {
+        if self.selected_item.is_none() {
+            self.selected_item = self.inventory.first().cloned();
+        }
+        if let Some(item) = &self.selected_item {
+            let name = item.name(self.language);
+            self.message = self.language.message(Message::Selected(name));
+        }
+        self.pop_menu();
+    }
+
+    fn on_build(&mut self) {
+        self.build_at(self.player_dir);
+    }
+
+    /// Builds the selected item next to the player in a direction, whichever
+    /// way they are facing.
+    fn build_at(&mut self, dir: Dir) {
+        let build_pos = self.player_pos + dir;
+        if !self.is_free(build_pos) {
+            self.message = "You cannot build on existing tiles.".to_string();
+            return; // Do not build on existing tiles
+        }
+        let Some(selected_item) = self.item_to_build() else {
+            self.message = "You have no item selected to build.".to_string();
+            return; // Do not build if no selected item
+        };
+        self.selected_item = Some(selected_item.clone());
+        let Some(tile) = self.tile_to_build(&selected_item) else {
+            let name = selected_item.name(self.language);
+            self.message = self.language.message(Message::CannotBuild(name));
+            return;
+        };
+        let name = selected_item.name(self.language);
+        if self.inventory.remove(&selected_item).is_err() {
+            self.message = self.language.message(Message::OutOf(name));
+            self.select_next_buildable(&selected_item);
+            return;
+        }
+        self.last_built = Some(selected_item.clone());
+        self.set_tile(build_pos, tile);
+        self.stats.tiles_placed += 1;
+        self.events.push(GameEvent::Build);
+        let left = self.inventory.count_of(&selected_item);
+        self.message = self.language.message(Message::Placed { name, left });
+        if left == 0 {
+            self.message += " ";
+            self.message += &self.language.message(Message::OutOf(name));
+            self.select_next_buildable(&selected_item);
+        }
+    }
+
+    /// What building now builds: the selected item, or if it can't be built
+    /// (or ran out), the item that was built last, if there is some left.
+    fn item_to_build(&self) -> Option<Item> {
+        let can_build =
+            |item: &Item| self.tile_to_build(item).is_some() && self.inventory.count_of(item) > 0;
+        let selected = self.selected_item.clone();
+        if selected.as_ref().is_some_and(can_build) {
+            return selected;
+        }
+        self.last_built.clone().filter(can_build).or(selected)
+    }
+
+    /// Selects the first item after `item` in the inventory that can be
+    /// built, and builds it from now on. If none can, just the next item.
+    fn select_next_buildable(&mut self, item: &Item) {
+        let mut next = item.clone();
+        for _ in 0..self.inventory.iter().count() {
+            let Some(item) = self.inventory.next(&next) else {
+                break;
+            };
+            next = item;
+            if self.tile_to_build(&next).is_some() {
+                self.selected_item = Some(next.clone());
+                self.last_built = Some(next);
+                return;
+            }
+        }
+        self.selected_item = self.inventory.next(item);
+    }
+
+    /// Where building now would put an item (see `item_to_build`), or `None`
+    /// if it can't be built there, or there is nothing to build.
+    pub fn build_target(&self) -> Option<Pos> {
+        let item = self.item_to_build()?;
+        let pos = self.player_pos + self.player_dir;
+        let can_build = self.tile_to_build(&item).is_some()
+            && self.inventory.count_of(&item) > 0
+            && self.is_free(pos);
+        can_build.then_some(pos)
+    }
+
+    /// The tile that building an item makes, in the variant chosen for it.
+    pub fn tile_to_build(&self, item: &Item) -> Option<Tile> {
+        item.to_tile_with_variant(self.variants.get(item).copied().unwrap_or(0))
+    }
+
+    /// Chooses the next variant of the selected item, going back to the
+    /// whole tile after the last one.
+    fn on_cycle_variant(&mut self) {
+        let Some(item) = self.selected_item.clone() else {
+            self.message = "You have no item selected to build.".to_string();
+            return;
+        };
+        let variants = item.variants();
+        let variant = self.variants.get(&item).copied().unwrap_or(0);
+        let variant = (variant + 1) % variants;
+        if variant == 0 {
+            self.variants.remove(&item);
+        } else {
+            self.variants.insert(item.clone(), variant);
+        }
+        if let Some(tile) = self.tile_to_build(&item)
+            && variants > 1
+        {
+            self.message = format!("Building: {}", tile.name(self.language));
+        } else {
+            let name = item.name(self.language);
+            self.message = format!("A {name} can only be built one way.");
+        }
+    }
+
+    fn on_interact(&mut self) {
+        let pos = self.player_pos + self.player_dir;
+        let tile = self.get_tile(pos);
+        match tile.on_interact() {
+            tiles::Interaction::Become(new_tile) => self.set_tile(pos, new_tile),
+            tiles::Interaction::SetSpawn => {
+                // The tile itself is not passable, so the player spawns by it.
+                self.spawn_point = self.player_pos;
+                self.message = "You will now respawn next to this bed.".to_string();
+            }
+            tiles::Interaction::SaveWaypoint => self.on_use_waystone(pos),
+            tiles::Interaction::Trade => self.push_menu(Menu::Trade { pos, offer: 0 }),
+            tiles::Interaction::Nothing if tile == Tile::Empty => {
+                self.message = "There is nothing to use there.".to_string();
+            }
+            tiles::Interaction::Nothing => {
+                let name = tile.name(self.language);
+                self.message = self.language.message(Message::CannotUse(name));
+            }
+        }
+    }
+
+    /// Saves the waystone at `pos` as a waypoint, named by how many there
+    /// were before it.
+    fn on_use_waystone(&mut self, pos: Pos) {
+        if let Some(name) = self.waypoints.iter().find(|&(_, &p)| p == pos).map(|(name, _)| name) {
+            self.message = format!("This waystone is {name}.");
+            return;
+        }
+        let name = (1..)
+            .map(|n| format!("Waypoint {n}"))
+            .find(|name| !self.waypoints.contains_key(name))
+            .expect("There are infinitely many names");
+        self.message = format!("Saved this waystone as {name}. Press n to come back to it.");
+        self.waypoints.insert(name, pos);
+    }
+
+    /// Forgets the waypoints whose waystone is not there anymore.
+    fn prune_waypoints(&mut self) {
+        let gone: Vec<String> = self
+            .waypoints
+            .iter()
+            .filter(|&(_, &pos)| self.get_tile(pos) != Tile::Waystone)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in gone {
+            self.waypoints.remove(&name);
+        }
+    }
+
+    fn on_open_waypoints(&mut self) {
+        if self.waypoints.is_empty() {
+            self.message = "You have no waypoints. Use a waystone to make one.".to_string();
+        } else {
+            self.push_menu(Menu::Waypoints(0));
+        }
+    }
+
+    /// Takes the player to the waypoint at `index` (in the order of
+    /// `waypoints`), next to its waystone. When `walk`, they walk there by
+    /// themselves instead of going there at once.
+    fn go_to_waypoint(&mut self, index: usize, walk: bool) {
+        self.pop_menu();
+        let Some((name, pos)) = self.waypoints.iter().nth(index).map(|(n, &p)| (n.clone(), p))
+        else {
+            return;
+        };
+        if self.get_tile(pos) != Tile::Waystone {
+            self.prune_waypoints();
+            self.message = format!("The waystone of {name} is gone.");
+            return;
+        }
+        match self.free_pos_near(pos) {
+            Some(target) if walk => self.walk_to(target),
+            Some(target) => {
+                self.player_pos = target;
+                self.message = format!("You went to {name}.");
+            }
+            None => self.message = format!("There is no room next to {name}."),
+        }
+    }
+
+    /// What the trader at `pos` sells. See the `trades` module.
+    pub fn trade_offers(&self, pos: Pos) -> Vec<&'static Trade> {
+        trades::offers_at(self.seed, pos)
+    }
+
+    /// Makes the trade at `index` (in the order of `trade_offers`) with the
+    /// trader at `pos`, if the player has what it costs and room for what
+    /// they get.
+    fn trade(&mut self, pos: Pos, index: usize) {
+        if self.get_tile(pos) != Tile::Trader {
+            self.pop_menu();
+            self.message = "The trader is gone.".to_string();
+            return;
+        }
+        let Some(trade) = self.trade_offers(pos).get(index).copied() else {
+            return;
+        };
+        let ((price, cost), (goods, count)) = (&trade.price, &trade.goods);
+        let (price_name, goods_name) = (price.name(self.language), goods.name(self.language));
+        let have = self.inventory.count_of(price);
+        if have < *cost {
+            self.message = format!("You need {cost} {price_name} for that, but have {have}.");
+            return;
+        }
+        // Made on a copy, so nothing is taken if what they get doesn't fit.
+        let mut inventory = self.inventory.clone();
+        for _ in 0..*cost {
+            inventory.remove(price).expect("The price was counted above");
+        }
+        for _ in 0..*count {
+            if inventory.insert(goods.clone()).is_err() {
+                self.message = "There is no room for that in your inventory.".to_string();
+                return;
+            }
+        }
+        self.inventory = inventory;
+        self.stats.items_crafted += *count as u64;
+        self.message = format!("You traded {cost} {price_name} for {count} {goods_name}.");
+    }
+
+    /// The free position closest to `pos` (but not `pos` itself), up to
+    /// `WAYPOINT_RANGE` tiles away.
+    fn free_pos_near(&self, pos: Pos) -> Option<Pos> {
+        (1..=WAYPOINT_RANGE).find_map(|distance| {
+            (-distance..=distance)
+                .flat_map(|dx| {
+                    let dy = distance - dx.abs();
+                    [(pos.0 + dx, pos.1 + dy), (pos.0 + dx, pos.1 - dy)]
+                })
+                .find(|&pos| self.is_free(pos))
+        })
+    }
+
+    fn on_command(&mut self, command: &str) {
+        let command = command.trim();
+        let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+        let args = args.trim();
+        match name {
+            "fog" => {
+                self.fog_of_war = !self.fog_of_war;
+                // Seeing the whole world is cheating.
+                self.meta.cheats_used |= !self.fog_of_war;
+                let state = if self.fog_of_war { "on" } else { "off" };
+                self.message = format!("Fog of war is {state}.");
+            }
+            "dig" => {
+                self.dig_on_turn = !self.dig_on_turn;
+                self.message = if self.dig_on_turn {
+                    "You will dig as soon as you turn to a tile.".to_string()
+                } else {
+                    "You will turn to a tile before digging it.".to_string()
+                };
+            }
+            "creative" => {
+                self.set_creative(!self.creative);
+                let state = if self.creative { "on" } else { "off" };
+                self.message = format!("Creative mode is {state}.");
+            }
+            "give" => self.on_give(args),
+            "regen" => self.on_regen(args),
+            "trader" => self.on_place_trader(),
+            "difficulty" => match Difficulty::from_name(args) {
+                Some(difficulty) => {
+                    self.meta.difficulty = difficulty;
+                    self.message = format!("The difficulty is {}.", difficulty.name());
+                }
+                None => self.message = "Usage: difficulty <peaceful, normal or hard>".to_string(),
+            },
+            "speed" => match args.parse() {
+                Ok(ms) => {
+                    self.set_move_cooldown(Duration::from_millis(ms));
+                    let ms = self.move_cooldown().as_millis();
+                    self.message = format!("You take a step at most every {ms} ms.");
+                }
+                Err(_) => self.message = "Usage: speed <milliseconds between steps>".to_string(),
+            },
+            "stats" => self.push_menu(Menu::Stats),
+            "join" => self.on_join(),
+            "leave" => self.on_leave(),
+            "lang" => match Language::from_code(args) {
+                Some(language) => {
+                    self.language = language;
+                    self.message = language.message(Message::LanguageSet);
+                }
+                None => self.message = self.language.message(Message::UnknownLanguage(args)),
+            },
+            _ => self.message = format!("Unknown command: {command}"),
+        }
+    }
+
+    /// Puts a trader where the player is facing. Only in creative mode.
+    fn on_place_trader(&mut self) {
+        if !self.creative {
+            self.message = "You can only do that in creative mode.".to_string();
+            return;
+        }
+        let pos = self.player_pos + self.player_dir;
+        if self.get_tile(pos) == Tile::Empty {
+            self.set_tile(pos, Tile::Trader);
+            self.message = "A trader is here to trade.".to_string();
+        } else {
+            self.message = "There is no room for a trader there.".to_string();
+        }
+    }
+
+    /// Puts the tiles in a rectangle back to how they were generated, in
+    /// creative mode. `args` are two corners, like "-10 -10 10 10".
+    fn on_regen(&mut self, args: &str) {
+        if !self.creative {
+            self.message = "You can only do that in creative mode.".to_string();
+            return;
+        }
+        let corners: Result<Vec<i32>, _> = args.split_whitespace().map(str::parse).collect();
+        let Ok(&[x1, y1, x2, y2]) = corners.as_deref() else {
+            self.message = "Usage: regen <x1> <y1> <x2> <y2>".to_string();
+            return;
+        };
+        let (left, right) = (x1.min(x2), x1.max(x2));
+        let (top, bottom) = (y1.min(y2), y1.max(y2));
+        let (width, height) = (right.abs_diff(left) as u64 + 1, bottom.abs_diff(top) as u64 + 1);
+        let area = width.saturating_mul(height);
+        if area > MAX_REGEN_AREA {
+            self.message = format!("That is too big, regen at most {MAX_REGEN_AREA} tiles.");
+            return;
+        }
+        for y in top..=bottom {
+            for x in left..=right {
+                // `set_tile` forgets tiles that are the same as generated.
+                self.set_tile((x, y), self.generate_tile((x, y)));
+            }
+        }
+        self.message = format!("Regenerated {area} tiles.");
+    }
+
+    /// Gives the player items out of nothing, in creative mode. `args` is an
+    /// item name and an optional count, like "oak wood 10".
+    fn on_give(&mut self, args: &str) {
+        if !self.creative {
+            self.message = "You can only do that in creative mode.".to_string();
+            return;
+        }
+        let (name, count) = match args.rsplit_once(' ') {
+            Some((name, count)) if let Ok(count) = count.parse::<usize>() => (name, count),
+            _ => (args, 1),
+        };
+        let Some(item) = Item::from_name(name) else {
+            self.message = self.language.message(Message::UnknownItem(name));
+            return;
+        };
+        let count = count.min(MAX_GIVE);
+        let item_name = item.name(self.language);
+        self.message = self.language.message(Message::Given { name: item_name, count });
+        for _ in 0..count {
+            // Whatever doesn't fit is dropped, and says so.
+            self.give_item(item.clone(), self.player_pos);
+        }
+    }
+
+    fn unlock_achievements(&mut self) {
+        for &(achievement, condition) in achievements::ACHIEVEMENTS {
+            if !self.achievements.contains(&achievement) && condition(self) {
+                self.achievements.insert(achievement);
+                let name = achievement.name();
+                self.message = format!("Achievement unlocked: {name}!");
+            }
+        }
+    }
+
+    /// Moves every unstable tile with gravity that has room below it one tile
+    /// down, along with everything with gravity on top of it.
+    fn apply_gravity(&mut self) {
+        // Falling marks more positions as unstable, which are looked at next
+        // tick.
+        let mut unstable: Vec<Pos> = self.unstable.drain().collect();
+        // Bottom first, so whole columns fall together. Remember that y grows
+        // downwards.
+        unstable.sort_by_key(|&(x, y)| (std::cmp::Reverse(y), x));
+        let mut fallen = HashSet::new();
+        for mut pos in unstable {
+            while !fallen.contains(&pos) && self.fall(pos) {
+                fallen.insert(pos + Dir::Down);
+                pos = pos + Dir::Up;
+            }
+        }
+    }
+
+    /// Moves a tile with gravity one tile down, if there is room. Returns
+    /// whether it fell.
+    ///
+    /// Sand falling on the player pushes them to the side, if there is room.
+    /// If not, they hold it up until they move.
+    fn fall(&mut self, pos: Pos) -> bool {
+        let tile = self.get_tile(pos);
+        let below = pos + Dir::Down;
+        if !tile.has_gravity() || !self.is_free(below) {
+            return false;
+        }
+        if below == self.player_pos {
+            let side = Dir::Down.sides().map(|side| below + side);
+            let Some(side) = side.into_iter().find(|&side| self.is_free(side)) else {
+                return false;
+            };
+            self.player_pos = side;
+        }
+        self.set_tile(below, tile);
+        self.set_tile(pos, Tile::Empty);
+        true
+    }
+
+    /// Ticks every tile that `needs_tick`.
+    fn tick_tiles(&mut self) {
+        // Sorted, so the same world always ticks the same way.
+        let mut ticking: Vec<Pos> = self.ticking.iter().copied().collect();
+        ticking.sort();
+        for pos in ticking {
+            let tile = self.get_tile(pos);
+            let new_tile = tile.tick(&mut self.rng(pos, salt::TILE_TICK), self.weather);
+            if new_tile != tile {
+                self.set_tile(pos, new_tile);
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        self.stats.ticks_survived += 1;
+        self.effects.retain_mut(|effect| {
+            effect.ttl -= 1;
+            effect.ttl > 0
+        });
+        self.weather = weather::next_weather(self.weather, self.stats.ticks_survived, self.seed);
+        if self.stats.ticks_survived.is_multiple_of(HUNGER_TICKS / self.rules().hunger_speed) {
+            self.get_hungrier();
+            for index in 1..=self.other_players.len() {
+                self.as_player(index, Self::get_hungrier);
+            }
+        }
+        self.tick_tiles();
+        self.apply_gravity();
+        self.pick_up_drops();
+        self.explore();
+        self.unlock_achievements();
+        self.log_message();
+        self.describe_facing();
+    }
+
+    /// The last messages the player was told, oldest first. What is ahead of
+    /// the player (see `describe_facing`) is left out, as it is said all the
+    /// time.
+    pub fn log(&self) -> &VecDeque<String> {
+        &self.log
+    }
+
+    /// Should the log be shown?
+    pub fn is_log_shown(&self) -> bool {
+        self.show_log
+    }
+
+    /// Keeps the message of this tick in the log.
+    fn log_message(&mut self) {
+        if self.message.is_empty() {
+            return;
+        }
+        if self.seen.is_some() {
+            self.changes.messages.push(self.message.clone());
+        }
+        if self.log.len() >= LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(self.message.clone());
+    }
+
+    /// Tells the player what is ahead of them, if nothing else was said this
+    /// tick. Saying the same thing twice in a row is skipped.
+    fn describe_facing(&mut self) {
+        let facing = self
+            .first_obstacle(self.player_pos, self.player_dir, FACING_RANGE)
+            .map(|(pos, tile)| {
+                let (x, y) = self.player_pos;
+                let distance = pos.0.abs_diff(x) + pos.1.abs_diff(y);
+                let name = tile.name(self.language);
+                self.language.message(Message::Facing { name, distance })
+            })
+            .unwrap_or_default();
+        if self.message.is_empty() && facing != self.last_facing {
+            self.message = facing.clone();
+        }
+        self.last_facing = facing;
+    }
+
+    /// Looks from a position (not including it) in a direction for up to `max`
+    /// tiles, and returns the first one that can't be passed through.
+    pub fn first_obstacle(&self, from: Pos, dir: Dir, max: u32) -> Option<(Pos, Tile)> {
+        let mut pos = from;
+        for _ in 0..max {
+            pos = pos + dir;
+            let tile = self.get_tile(pos);
+            if !tile.is_passable() {
+                return Some((pos, tile));
+            }
+        }
+        None
+    }
+
+    fn on_input_no_menu(&mut self, input: Input) {
+        if let Input::Dir(..) | Input::Run(_) | Input::Look(_) = input {
+            // Walking by hand stops walking by itself.
+            self.auto_path.clear();
+        }
+        match input {
+            Input::Dir(dir, shift) => self.on_dir_input_no_menu(dir, shift),
+            Input::Run(dir) => self.on_run(dir),
+            Input::Look(dir) => self.player_dir = dir,
+            Input::DigDir(dir) => self.dig_at(dir),
+            Input::BuildDir(dir) => self.build_at(dir),
+            Input::Build => self.on_build(),
+            Input::Interact => self.on_interact(),
+            Input::OpenInventory => self.open_inventory(),
+            Input::OpenWaypoints => self.on_open_waypoints(),
+            Input::CycleZoom => self.on_cycle_zoom(),
+            Input::ToggleAutoPlace => self.on_toggle_auto_place(),
+            Input::ToggleLog => self.show_log = !self.show_log,
+            Input::CycleVariant => self.on_cycle_variant(),
+            Input::Command(command) => self.on_command(&command),
+            Input::CloseMenu => self.push_menu(Menu::Pause(PauseEntry::default())),
+            Input::ClickTile(pos) => self.on_click_tile(pos),
+            Input::BuildAt(pos) => self.on_build_at(pos),
+            Input::MoveTo(pos) => self.walk_to(pos),
+            Input::Throw => self.on_throw(),
+            Input::Eat => self.on_eat(),
+            Input::Confirm | Input::ClickItem(_) | Input::ForPlayer(..) | Input::Quit => (),
+        }
+    }
+
+    /// The menu on top, which gets the input, or `Menu::None` if no menu is
+    /// open.
+    pub fn current_menu(&self) -> Menu {
+        self.menus.last().copied().unwrap_or_default()
+    }
+
+    /// Every open menu, from the first opened to the one on top.
+    pub fn menus(&self) -> &[Menu] {
+        &self.menus
+    }
+
+    /// Opens a menu over the open ones. Opening the menu that is already on
+    /// top does nothing.
+    fn push_menu(&mut self, menu: Menu) {
+        if menu != Menu::None && menu != self.current_menu() {
+            self.menus.push(menu);
+        }
+    }
+
+    /// Closes the menu on top, going back to the one under it.
+    fn pop_menu(&mut self) {
+        self.menus.pop();
+    }
+
+    /// Changes the menu on top, like which entry of it is highlighted.
+    fn replace_menu(&mut self, menu: Menu) {
+        self.pop_menu();
+        self.push_menu(menu);
+    }
+
+    fn on_input_inventory(&mut self, input: Input) {
+        match input {
+            Input::Dir(dir, _)
+            | Input::Run(dir)
+            | Input::Look(dir)
+            | Input::DigDir(dir)
+            | Input::BuildDir(dir) => self.on_dir_input_inventory(dir),
+            Input::Build | Input::Confirm => self.on_confirm_inventory(),
+            Input::ClickItem(index) => {
+                if let Some((item, _)) = self.inventory.iter().nth(index) {
+                    self.selected_item = Some(item);
+                }
+            }
+            Input::OpenInventory | Input::CloseMenu => self.pop_menu(),
+            Input::Command(command) => self.on_command(&command),
+            Input::Eat => self.on_eat(),
+            // The world is behind the menu, so leave it alone.
+            Input::Interact
+            | Input::OpenWaypoints
+            | Input::CycleZoom
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+    }
+
+    fn on_input_stats(&mut self, input: Input) {
+        match input {
+            Input::CloseMenu => self.pop_menu(),
+            Input::OpenInventory => self.open_inventory(),
+            Input::Command(command) => self.on_command(&command),
+            Input::Dir(..)
+            | Input::Run(_)
+            | Input::Look(_)
+            | Input::Build
+            | Input::Interact
+            | Input::OpenWaypoints
+            | Input::CycleZoom
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::Confirm
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::ClickItem(_)
+            | Input::DigDir(_)
+            | Input::BuildDir(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::Eat
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+    }
+
+    fn on_input_pause(&mut self, input: Input, entry: PauseEntry) -> Outcome {
+        match input {
+            Input::Dir(Dir::Up | Dir::Left, _)
+            | Input::Run(Dir::Up | Dir::Left)
+            | Input::Look(Dir::Up | Dir::Left)
+            | Input::DigDir(Dir::Up | Dir::Left)
+            | Input::BuildDir(Dir::Up | Dir::Left) => self.replace_menu(Menu::Pause(entry.prev())),
+            Input::Dir(Dir::Down | Dir::Right, _)
+            | Input::Run(Dir::Down | Dir::Right)
+            | Input::Look(Dir::Down | Dir::Right)
+            | Input::DigDir(Dir::Down | Dir::Right)
+            | Input::BuildDir(Dir::Down | Dir::Right) => {
+                self.replace_menu(Menu::Pause(entry.next()))
+            }
+            Input::Confirm | Input::Build => {
+                let confirm = |action| Menu::Confirm { action, yes: false };
+                match entry {
+                    PauseEntry::Resume => self.pop_menu(),
+                    PauseEntry::Save => {
+                        self.pop_menu();
+                        self.message = "Game saved.".to_string();
+                        return Outcome::Save;
+                    }
+                    PauseEntry::NewWorld => self.push_menu(confirm(ConfirmAction::NewWorld)),
+                    PauseEntry::SaveAndQuit => {
+                        self.pop_menu();
+                        return Outcome::Quit { save: true };
+                    }
+                    PauseEntry::QuitWithoutSaving => {
+                        self.push_menu(confirm(ConfirmAction::QuitWithoutSaving))
+                    }
+                }
+            }
+            Input::CloseMenu => self.pop_menu(),
+            Input::Command(command) => self.on_command(&command),
+            Input::OpenInventory
+            | Input::OpenWaypoints
+            | Input::CycleZoom
+            | Input::Interact
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::ClickItem(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::Eat
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+        Outcome::Continue
+    }
+
+    fn on_input_waypoints(&mut self, input: Input, index: usize) {
+        let count = self.waypoints.len().max(1);
+        match input {
+            Input::Dir(Dir::Up | Dir::Left, _)
+            | Input::Run(Dir::Up | Dir::Left)
+            | Input::Look(Dir::Up | Dir::Left)
+            | Input::DigDir(Dir::Up | Dir::Left)
+            | Input::BuildDir(Dir::Up | Dir::Left) => {
+                self.replace_menu(Menu::Waypoints((index + count - 1) % count))
+            }
+            Input::Dir(Dir::Down | Dir::Right, _)
+            | Input::Run(Dir::Down | Dir::Right)
+            | Input::Look(Dir::Down | Dir::Right)
+            | Input::DigDir(Dir::Down | Dir::Right)
+            | Input::BuildDir(Dir::Down | Dir::Right) => {
+                self.replace_menu(Menu::Waypoints((index + 1) % count))
+            }
+            Input::Confirm | Input::Build => self.go_to_waypoint(index, false),
+            Input::Interact => self.go_to_waypoint(index, true),
+            Input::OpenWaypoints | Input::CloseMenu => self.pop_menu(),
+            Input::Command(command) => self.on_command(&command),
+            Input::OpenInventory
+            | Input::CycleZoom
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::ClickItem(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::Eat
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+    }
+
+    fn on_input_trade(&mut self, input: Input, pos: Pos, offer: usize) {
+        let count = OFFERS_PER_TRADER;
+        match input {
+            Input::Dir(Dir::Up | Dir::Left, _)
+            | Input::Run(Dir::Up | Dir::Left)
+            | Input::Look(Dir::Up | Dir::Left)
+            | Input::DigDir(Dir::Up | Dir::Left)
+            | Input::BuildDir(Dir::Up | Dir::Left) => {
+                self.replace_menu(Menu::Trade { pos, offer: (offer + count - 1) % count })
+            }
+            Input::Dir(Dir::Down | Dir::Right, _)
+            | Input::Run(Dir::Down | Dir::Right)
+            | Input::Look(Dir::Down | Dir::Right)
+            | Input::DigDir(Dir::Down | Dir::Right)
+            | Input::BuildDir(Dir::Down | Dir::Right) => {
+                self.replace_menu(Menu::Trade { pos, offer: (offer + 1) % count })
+            }
+            Input::Confirm | Input::Build | Input::Interact => self.trade(pos, offer),
+            Input::CloseMenu => self.pop_menu(),
+            Input::Command(command) => self.on_command(&command),
+            Input::OpenInventory
+            | Input::OpenWaypoints
+            | Input::CycleZoom
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::ClickItem(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::Eat
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+    }
+
+    fn on_input_confirm(&mut self, input: Input, action: ConfirmAction, yes: bool) -> Outcome {
+        match input {
+            Input::Dir(..)
+            | Input::Run(_)
+            | Input::Look(_)
+            | Input::DigDir(_)
+            | Input::BuildDir(_) => self.replace_menu(Menu::Confirm { action, yes: !yes }),
+            Input::Confirm | Input::Build if yes => {
+                // Whatever it was done from is done with too.
+                self.menus.clear();
+                return match action {
+                    ConfirmAction::QuitWithoutSaving => Outcome::Quit { save: false },
+                    ConfirmAction::NewWorld => Outcome::NewWorld,
+                };
+            }
+            Input::Confirm | Input::Build | Input::CloseMenu => self.pop_menu(),
+            Input::Command(command) => self.on_command(&command),
+            Input::OpenInventory
+            | Input::OpenWaypoints
+            | Input::CycleZoom
+            | Input::Interact
+            | Input::ToggleAutoPlace
+            | Input::ToggleLog
+            | Input::CycleVariant
+            | Input::ClickTile(_)
+            | Input::BuildAt(_)
+            | Input::ClickItem(_)
+            | Input::MoveTo(_)
+            | Input::Throw
+            | Input::Eat
+            | Input::ForPlayer(..)
+            | Input::Quit => (),
+        }
+        Outcome::Continue
+    }
+
+    /// Handles one input from the player and moves the game one tick
+    /// forward. The returned `Outcome` tells the game loop what to do next.
+    pub fn on_input(&mut self, input: Input) -> Outcome {
+        self.dirty = true;
+        self.message.clear();
+        if input == Input::Quit {
+            return Outcome::Quit { save: true };
+        }
+        // Other players play in the world even when the first player has a
+        // menu open, unless the game is paused.
+        if let Input::ForPlayer(index, input) = input {
+            let paused = |menu: &Menu| matches!(menu, Menu::Pause(_) | Menu::Confirm { .. });
+            if !self.menus.iter().any(paused) {
+                self.as_player(index as usize, |state| {
+                    state.on_input_no_menu(*input);
+                    state.pick_up_drops();
+                    state.explore();
+                });
+                self.tick();
+            }
+            return Outcome::Continue;
+        }
+        let outcome = match self.current_menu() {
+            Menu::None => {
+                self.on_input_no_menu(input);
+                Outcome::Continue
+            }
+            Menu::Inventory => {
+                self.on_input_inventory(input);
+                Outcome::Continue
+            }
+            Menu::Stats => {
+                self.on_input_stats(input);
+                Outcome::Continue
+            }
+            Menu::Pause(entry) => self.on_input_pause(input, entry),
+            Menu::Waypoints(index) => {
+                self.on_input_waypoints(input, index);
+                Outcome::Continue
+            }
+            Menu::Trade { pos, offer } => {
+                self.on_input_trade(input, pos, offer);
+                Outcome::Continue
+            }
+            Menu::Confirm { action, yes } => self.on_input_confirm(input, action, yes),
+        };
         self.tick();
-        Some(self)
+        self.remember_selection();
+        outcome
     }
 }
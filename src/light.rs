@@ -0,0 +1,87 @@
+//! Light levels. During the day everything is lit, but at night only the
+//! tiles around light sources (like torches) are.
+
+use std::collections::HashMap;
+
+use crate::Pos;
+
+/// The light level of a fully lit tile.
+pub const MAX_LIGHT: u8 = 6;
+
+/// The light level of tiles at night, away from any light source.
+pub const NIGHT_LIGHT: u8 = 1;
+
+/// How many ticks a full day (and night) lasts.
+pub const DAY_LENGTH: u64 = 200;
+
+/// Is it night at this tick? The second half of every day is night.
+pub fn is_night(tick: u64) -> bool {
+    tick % DAY_LENGTH >= DAY_LENGTH / 2
+}
+
+/// How much light a source gives to a tile this far away from it.
+pub fn falloff(emission: u8, (dx, dy): (i32, i32)) -> u8 {
+    let distance = ((dx * dx + dy * dy) as f64).sqrt().round() as u32;
+    (emission as u32).saturating_sub(distance) as u8
+}
+
+/// The most light levels a `LightCache` remembers, before it forgets them all
+/// and starts over.
+const MAX_CACHED: usize = 1 << 16;
+
+/// Light levels that were already worked out, so drawing the same tiles
+/// again doesn't look at every light source again. Has to be cleared when a
+/// light source is placed or broken.
+#[derive(Debug, Clone, Default)]
+pub struct LightCache {
+    /// The ambient light that the levels were worked out with. When it
+    /// changes, the levels are all wrong.
+    ambient: u8,
+    levels: HashMap<Pos, u8>,
+}
+
+impl LightCache {
+    pub fn get(&mut self, ambient: u8, pos: Pos) -> Option<u8> {
+        if ambient != self.ambient {
+            self.clear();
+            self.ambient = ambient;
+        }
+        self.levels.get(&pos).copied()
+    }
+
+    pub fn insert(&mut self, pos: Pos, level: u8) {
+        if self.levels.len() >= MAX_CACHED {
+            self.clear();
+        }
+        self.levels.insert(pos, level);
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_falls_off_with_the_distance() {
+        assert_eq!(falloff(MAX_LIGHT, (0, 0)), MAX_LIGHT);
+        assert_eq!(falloff(MAX_LIGHT, (0, -2)), MAX_LIGHT - 2);
+        // 1.4 tiles away rounds down to 1, and 5 tiles away diagonally is 5.
+        assert_eq!(falloff(MAX_LIGHT, (1, 1)), MAX_LIGHT - 1);
+        assert_eq!(falloff(MAX_LIGHT, (3, 4)), MAX_LIGHT - 5);
+        assert_eq!(falloff(MAX_LIGHT, (MAX_LIGHT as i32, 0)), 0);
+        assert_eq!(falloff(MAX_LIGHT, (100, 0)), 0);
+        assert_eq!(falloff(2, (1, 0)), 1);
+    }
+
+    #[test]
+    fn the_second_half_of_a_day_is_night() {
+        assert!(!is_night(0));
+        assert!(!is_night(DAY_LENGTH / 2 - 1));
+        assert!(is_night(DAY_LENGTH / 2));
+        assert!(!is_night(DAY_LENGTH));
+    }
+}
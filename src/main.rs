@@ -1,6 +1,152 @@
 use minecraft_2d::*;
 
+const USAGE: &str = "\
+Usage: minecraft-2d [flags] [serve <addr> | connect <addr> | validate]
+
+Flags:
+  --seed <seed>    Make the world from this seed, without asking (only when
+                   there is no save yet)
+  --slot <name>    Use another save, kept apart from the usual one
+  --creative       Turn creative mode on";
+
+/// What was given on the command line besides what to do.
+#[derive(Debug, Default)]
+struct Flags {
+    seed: Option<u32>,
+    slot: Option<String>,
+    creative: bool,
+}
+
 fn main() {
-    start_game(&mut TerminalPlatform)
-        .unwrap_or_else(|e| eprintln!("Error: {}", e));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((flags, args)) = parse_flags(&args) else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    let res = match args[..] {
+        ["serve", addr] => run_server(&flags, addr),
+        ["connect", addr] => NetworkPlatform::connect(addr).and_then(|mut p| start_game(&mut p)),
+        ["validate"] => validate(&flags),
+        [] => play(&flags),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = res {
+        eprintln!("The game stopped because of an error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Splits the flags from the other arguments. `None` if a flag is unknown or
+/// is missing its value.
+fn parse_flags(args: &[String]) -> Option<(Flags, Vec<&str>)> {
+    let mut flags = Flags::default();
+    let mut rest = vec![];
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--seed" => flags.seed = Some(seed_from_str(args.next()?)),
+            "--slot" => flags.slot = Some(args.next()?.to_string()),
+            "--creative" => flags.creative = true,
+            flag if flag.starts_with("--") => return None,
+            arg => rest.push(arg),
+        }
+    }
+    Some((flags, rest))
+}
+
+/// Where the saves are, for the slot in `flags`.
+fn store(flags: &Flags) -> Result<FileSaveStore, GameError> {
+    let store = FileSaveStore::in_data_dir()?;
+    Ok(match &flags.slot {
+        Some(slot) => FileSaveStore::new(store.dir().join("slots").join(slot)),
+        None => store,
+    })
+}
+
+/// Plays in the terminal. `start_game` restores the terminal before returning,
+/// so an error is printed to a normal screen.
+fn play(flags: &Flags) -> Result<(), GameError> {
+    let store = match store(flags) {
+        Ok(store) => store,
+        Err(e) => {
+            // Nothing was started yet, so there is nothing to restore.
+            eprintln!("The game could not start: {e}");
+            std::process::exit(1);
+        }
+    };
+    apply_flags(&store, flags)?;
+    start_game(&mut TerminalPlatform::new(store))
+}
+
+/// Makes the world with the seed in `flags`, or turns on creative mode in the
+/// saved one, before the game starts and loads it.
+fn apply_flags(store: &FileSaveStore, flags: &Flags) -> Result<(), GameError> {
+    let mut state = if store.exists() {
+        if flags.seed.is_some() {
+            println!("There is already a save, so the seed is ignored.");
+        }
+        if !flags.creative {
+            return Ok(());
+        }
+        match store.load()? {
+            LoadResult::Loaded(state) => *state,
+            // The game says what is wrong with it.
+            LoadResult::NoSave | LoadResult::Corrupt { .. } => return Ok(()),
+        }
+    } else {
+        match flags.seed {
+            Some(seed) => State::with_seed(seed),
+            None if flags.creative => State::new(),
+            None => return Ok(()),
+        }
+    };
+    if flags.creative {
+        state.set_creative(true);
+    }
+    Ok(store.save_state(&state)?)
+}
+
+/// Checks that the save loads, and says what in it was not as expected,
+/// without playing it.
+fn validate(flags: &Flags) -> Result<(), GameError> {
+    let store = store(flags)?;
+    let report = match store.validate() {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            println!("There is no save in {}.", store.dir().display());
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("The save is broken. {e}");
+            std::process::exit(1);
+        }
+    };
+    if !report.defaulted.is_empty() {
+        println!("Missing, so set to their defaults: {}", report.defaulted.join(", "));
+    }
+    if !report.unknown.is_empty() {
+        println!("Unknown, so lost on the next save: {}", report.unknown.join(", "));
+    }
+    println!("The save loads.");
+    Ok(())
+}
+
+/// Serves the saved world until everyone leaves, then saves it.
+fn run_server(flags: &Flags, addr: &str) -> Result<(), GameError> {
+    let store = store(flags)?;
+    apply_flags(&store, flags)?;
+    let state = match store.load()? {
+        LoadResult::Loaded(state) => *state,
+        LoadResult::NoSave => State::new(),
+        LoadResult::Corrupt { reason } => {
+            eprintln!("The save could not be loaded ({reason}).");
+            std::process::exit(1);
+        }
+    };
+    println!("Serving on {addr}. Connect with `connect {addr}`.");
+    let state = serve(addr, state)?;
+    Ok(store.save_state(&state)?)
 }
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Pos;
+
+/// The menu that is open over the world, if any.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Menu {
+    #[default]
+    None,
+    Inventory,
+    Stats,
+    /// The pause menu, with the highlighted entry.
+    Pause(PauseEntry),
+    /// The waypoints, with the index of the highlighted one. See
+    /// `State::waypoints`.
+    Waypoints(usize),
+    /// The offers of the trader at `pos`, with the index of the highlighted
+    /// one. See `State::trade_offers`.
+    Trade { pos: Pos, offer: usize },
+    /// Asks if the player is sure they want to do something that can't be
+    /// undone. `yes` is whether "Yes" is highlighted.
+    Confirm { action: ConfirmAction, yes: bool },
+}
+
+/// Something the player is asked to confirm before it is done. See
+/// `Menu::Confirm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConfirmAction {
+    QuitWithoutSaving,
+    /// See `Outcome::NewWorld`.
+    NewWorld,
+}
+
+impl ConfirmAction {
+    /// What the player is asked.
+    pub const fn prompt(self) -> &'static str {
+        match self {
+            ConfirmAction::QuitWithoutSaving => {
+                "Quit without saving? Everything since the last save is lost."
+            }
+            ConfirmAction::NewWorld => "Start a new world? This one will be gone for good.",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PauseEntry {
+    #[default]
+    Resume,
+    Save,
+    /// Throws this world away for a new one. See `Outcome::NewWorld`.
+    NewWorld,
+    SaveAndQuit,
+    QuitWithoutSaving,
+}
+
+impl PauseEntry {
+    /// All entries, in the order they are shown.
+    pub const ALL: [PauseEntry; 5] = [
+        PauseEntry::Resume,
+        PauseEntry::Save,
+        PauseEntry::NewWorld,
+        PauseEntry::SaveAndQuit,
+        PauseEntry::QuitWithoutSaving,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            PauseEntry::Resume => "Resume",
+            PauseEntry::Save => "Save",
+            PauseEntry::NewWorld => "New world",
+            PauseEntry::SaveAndQuit => "Save & Quit",
+            PauseEntry::QuitWithoutSaving => "Quit without saving",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&e| e == self).unwrap_or(0)
+    }
+
+    /// The entry below this one. Wraps.
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The entry above this one. Wraps.
+    pub fn prev(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
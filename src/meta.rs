@@ -0,0 +1,75 @@
+//! What is known about a world besides what is in it, kept in its save.
+//!
+//! How the world was generated (its seed, `WorldGen` and wall density) is
+//! kept on `State` itself, as the world can't be generated without it.
+
+use serde::{Deserialize, Serialize};
+
+/// The name of a world, how hard it is, and whether it was played fairly.
+/// Old saves have none of these, so they get the defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldMeta {
+    /// Empty if the world has no name.
+    pub name: String,
+    pub description: String,
+    pub difficulty: Difficulty,
+    /// Was creative mode (or anything else that isn't playing fairly) ever
+    /// turned on? Once on, it stays on.
+    pub cheats_used: bool,
+}
+
+/// How hard the world is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    Peaceful,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Peaceful, Difficulty::Normal, Difficulty::Hard];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "Peaceful",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// The difficulty with this name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name().eq_ignore_ascii_case(name))
+    }
+
+    /// How the game plays at this difficulty.
+    pub const fn rules(self) -> Rules {
+        match self {
+            Difficulty::Peaceful => Rules { wall_hits: 1, hunger: false, hunger_speed: 1 },
+            Difficulty::Normal => Rules { wall_hits: 1, hunger: true, hunger_speed: 1 },
+            Difficulty::Hard => Rules { wall_hits: 2, hunger: true, hunger_speed: 2 },
+        }
+    }
+}
+
+/// The parts of the game that change with the difficulty. Anything that
+/// plays differently at some difficulty takes these, instead of looking at
+/// the difficulty itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rules {
+    /// How many times every stage of a wall is dug at before it breaks. See
+    /// `Tile::hits_to_break`.
+    pub wall_hits: u8,
+    /// Does the player get hungry? See `State::hunger`.
+    pub hunger: bool,
+    /// How many times faster than usual the player gets hungry.
+    pub hunger_speed: u64,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Difficulty::default().rules()
+    }
+}
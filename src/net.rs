@@ -0,0 +1,494 @@
+//! Playing together over the network.
+//!
+//! A server (see `serve`) owns the world, and applies the inputs of all of its
+//! clients in the order they arrive. After every input (even one it ignores),
+//! and every step a player walks by themselves, it sends every client the
+//! whole world. Clients play with a `NetworkPlatform`, which draws the world
+//! the server sent instead of its own.
+//!
+//! Every message is a 4 byte big-endian length, and then that many bytes of
+//! MessagePack.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::game_loop::WALK_STEP_EVERY;
+use crate::{
+    Dir, FileSaveStore, GameError, Input, LoadResult, Menu, Platform, State, TerminalPlatform,
+};
+
+/// No message should be anywhere near this big. Bigger lengths mean the
+/// other side is not talking our protocol.
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ServerMessage {
+    /// The first message a client gets, telling it which player is theirs.
+    Welcome { player: u8 },
+    /// The world, and the menus that the client has open in it.
+    World { state: Box<State>, menus: Vec<Menu>, inputs: u64 },
+}
+
+fn encode(message: &impl Serialize) -> io::Result<Vec<u8>> {
+    let body = rmp_serde::to_vec_named(message).map_err(io::Error::other)?;
+    let len = u32::try_from(body.len()).map_err(io::Error::other)?;
+    let mut bytes = len.to_be_bytes().to_vec();
+    bytes.extend(body);
+    Ok(bytes)
+}
+
+fn write_message(stream: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+    stream.write_all(&encode(message)?)?;
+    stream.flush()
+}
+
+fn read_message<T: DeserializeOwned>(stream: &mut impl Read) -> io::Result<T> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long"));
+    }
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body)?;
+    rmp_serde::from_slice(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Something that happened with one of the clients, by id.
+enum ClientEvent {
+    Joined(usize, TcpStream),
+    Input(usize, Input),
+    Left(usize),
+}
+
+struct Client {
+    stream: TcpStream,
+    /// The index of their player. See `State::as_player`.
+    player: u8,
+    /// Every client has their own menus.
+    menus: Vec<Menu>,
+    /// And walks by themselves on their own. See `State::is_walking`.
+    auto_path: VecDeque<Dir>,
+    /// How many inputs they sent, so they know when a world they get has
+    /// the last one in it.
+    inputs: u64,
+}
+
+impl Client {
+    fn is_walking(&self) -> bool {
+        self.menus.is_empty() && !self.auto_path.is_empty()
+    }
+}
+
+/// Runs `f` as the player of `client`, with their own menus and walk.
+fn as_client(state: &mut State, client: &mut Client, f: impl FnOnce(&mut State)) {
+    state.as_player(client.player as usize, |state| {
+        std::mem::swap(&mut state.menus, &mut client.menus);
+        std::mem::swap(&mut state.auto_path, &mut client.auto_path);
+        f(state);
+        std::mem::swap(&mut state.menus, &mut client.menus);
+        std::mem::swap(&mut state.auto_path, &mut client.auto_path);
+    });
+}
+
+/// Runs a server for a world on `addr`. Returns the world when everyone who
+/// joined has left, so it can be saved.
+pub fn serve(addr: impl ToSocketAddrs, state: State) -> io::Result<State> {
+    Ok(serve_on(TcpListener::bind(addr)?, state))
+}
+
+fn serve_on(listener: TcpListener, mut state: State) -> State {
+    let (events, received) = mpsc::channel();
+    thread::spawn(move || accept_clients(listener, events));
+
+    let mut clients: HashMap<usize, Client> = HashMap::new();
+    let mut next_step = Instant::now();
+    loop {
+        // Like in `start_game`, players that walk by themselves take a step
+        // every so often, unless something else happens first.
+        let event = if clients.values().any(Client::is_walking) {
+            let timeout = next_step.saturating_duration_since(Instant::now());
+            match received.recv_timeout(timeout) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            next_step = Instant::now() + WALK_STEP_EVERY;
+            match received.recv() {
+                Ok(event) => Some(event),
+                Err(_) => break,
+            }
+        };
+        match event {
+            Some(ClientEvent::Joined(id, mut stream)) => {
+                let Some(player) = free_player(&mut state, &clients) else {
+                    // No room. The reading thread sees this and says they left.
+                    let _ = stream.shutdown(Shutdown::Both);
+                    continue;
+                };
+                if write_message(&mut stream, &ServerMessage::Welcome { player }).is_ok() {
+                    let (menus, auto_path) = (vec![], VecDeque::new());
+                    clients.insert(id, Client { stream, player, menus, auto_path, inputs: 0 });
+                }
+            }
+            Some(ClientEvent::Input(id, input)) => {
+                let Some(client) = clients.get_mut(&id) else {
+                    continue;
+                };
+                client.inputs += 1;
+                // Ignored inputs are still answered with the world, which
+                // the client waits for.
+                if is_shared_input(&input) {
+                    // Quitting and saving are up to the client and the
+                    // server, so the outcome does not matter here.
+                    as_client(&mut state, client, |state| {
+                        state.on_input(input);
+                    });
+                }
+            }
+            Some(ClientEvent::Left(id)) => {
+                if clients.remove(&id).is_some() && clients.is_empty() {
+                    break;
+                }
+            }
+            None => {
+                for client in clients.values_mut().filter(|client| client.is_walking()) {
+                    as_client(&mut state, client, State::walk_step);
+                }
+                next_step += WALK_STEP_EVERY;
+            }
+        }
+        // Feedback is up to the clients, which don't get events.
+        state.take_events();
+        send_world(&state, &mut clients);
+    }
+    state
+}
+
+fn accept_clients(listener: TcpListener, events: Sender<ClientEvent>) {
+    for (id, stream) in listener.incoming().enumerate() {
+        // A client that failed to connect does not stop the others.
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_nodelay(true);
+        let Ok(mut reader) = stream.try_clone() else {
+            continue;
+        };
+        if events.send(ClientEvent::Joined(id, stream)).is_err() {
+            return; // The server stopped.
+        }
+        let events = events.clone();
+        thread::spawn(move || {
+            while let Ok(input) = read_message(&mut reader) {
+                if events.send(ClientEvent::Input(id, input)).is_err() {
+                    return;
+                }
+            }
+            let _ = events.send(ClientEvent::Left(id));
+        });
+    }
+}
+
+/// A player that no client is playing, adding one if there is none.
+fn free_player(state: &mut State, clients: &HashMap<usize, Client>) -> Option<u8> {
+    let players = 1 + state.other_players().len();
+    let free = (0..players).find(|&i| clients.values().all(|c| c.player as usize != i));
+    let index = match free {
+        Some(index) => index,
+        None => state.add_player()?,
+    };
+    u8::try_from(index).ok()
+}
+
+/// Inputs that don't make sense when other people play in the same world
+/// are ignored. Adding players is done by connecting, and everyone uses their
+/// own keyboard.
+fn is_shared_input(input: &Input) -> bool {
+    match input {
+        Input::Command(command) => !matches!(command.trim(), "join" | "leave"),
+        Input::ForPlayer(..) => false,
+        _ => true,
+    }
+}
+
+/// Sends everyone the world. Clients that can't be sent to are left alone:
+/// their reading thread will notice that they left.
+fn send_world(state: &State, clients: &mut HashMap<usize, Client>) {
+    for client in clients.values_mut() {
+        let message = ServerMessage::World {
+            state: Box::new(state.clone()),
+            menus: client.menus.clone(),
+            inputs: client.inputs,
+        };
+        let _ = write_message(&mut client.stream, &message);
+    }
+}
+
+/// A world from the server, with the menus the client has open in it, and
+/// how many of the client's inputs it has in it.
+type World = (State, Vec<Menu>, u64);
+
+/// The client's side of talking to a server.
+struct Connection {
+    stream: TcpStream,
+    player: u8,
+    worlds: Receiver<World>,
+    /// How many inputs we sent.
+    sent: u64,
+}
+
+impl Connection {
+    fn open(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let ServerMessage::Welcome { player } = read_message(&mut stream)? else {
+            let e = io::Error::new(io::ErrorKind::InvalidData, "the server did not welcome us");
+            return Err(e);
+        };
+        let (sender, worlds) = mpsc::channel();
+        let mut reader = stream.try_clone()?;
+        thread::spawn(move || {
+            while let Ok(ServerMessage::World { state, menus, inputs }) = read_message(&mut reader)
+            {
+                if sender.send((*state, menus, inputs)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self { stream, player, worlds, sent: 0 })
+    }
+
+    fn send(&mut self, input: &Input) -> io::Result<()> {
+        write_message(&mut self.stream, input)?;
+        self.sent += 1;
+        Ok(())
+    }
+
+    /// Waits for a world that has every input we sent in it. Worlds sent for
+    /// the inputs of other players in the meantime are skipped.
+    fn receive(&mut self) -> io::Result<World> {
+        loop {
+            let Ok(world) = self.worlds.recv() else {
+                let e = io::Error::new(io::ErrorKind::ConnectionAborted, "the server went away");
+                return Err(e);
+            };
+            let world = self.worlds.try_iter().last().unwrap_or(world);
+            if world.2 >= self.sent {
+                return Ok(world);
+            }
+        }
+    }
+
+    /// The newest world the server sent since we last looked, if any.
+    fn newest(&mut self) -> Option<World> {
+        self.worlds.try_iter().last()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // The reading thread keeps a copy of the stream, so it isn't closed
+        // by dropping ours.
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Plays in a world that a server (see `serve`) runs. Input is read and the
+/// world is drawn like with a `TerminalPlatform`, but the world shown is the
+/// one from the server, not the one the game loop keeps.
+pub struct NetworkPlatform {
+    terminal: TerminalPlatform,
+    connection: Connection,
+    /// The last world we got from the server.
+    world: Option<World>,
+}
+
+/// How often a client looks for worlds from the server while waiting for a
+/// key, so it sees the other players play.
+const LOOK_FOR_WORLDS_EVERY: Duration = Duration::from_millis(50);
+
+impl NetworkPlatform {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, GameError> {
+        Ok(Self {
+            connection: Connection::open(addr)?,
+            terminal: TerminalPlatform::for_network(FileSaveStore::in_data_dir()?),
+            world: None,
+        })
+    }
+
+    /// Draws the last world we got, around our own player.
+    fn draw_world(&mut self) -> Result<(), GameError> {
+        let Some((world, menus, _)) = &self.world else {
+            return Ok(());
+        };
+        let mut world = world.clone();
+        world.menus = menus.clone();
+        let player = self.connection.player as usize;
+        if player > 0 && player <= world.other_players().len() {
+            world.swap_player(player - 1);
+        }
+        self.terminal.draw(&world)
+    }
+}
+
+impl Platform for NetworkPlatform {
+    type Error = GameError;
+
+    fn init(&mut self) -> Result<(), GameError> {
+        self.terminal.init()
+    }
+
+    fn cleanup(&mut self) -> Result<(), GameError> {
+        self.terminal.cleanup()
+    }
+
+    fn ask_for_input(&mut self) -> Result<Option<Input>, GameError> {
+        loop {
+            if let Some(input) = self.poll_input(LOOK_FOR_WORLDS_EVERY)? {
+                return Ok(Some(input));
+            }
+        }
+    }
+
+    /// Waits for a key like the terminal does, but also draws the worlds the
+    /// server sends in the meantime.
+    fn poll_input(&mut self, timeout: Duration) -> Result<Option<Input>, GameError> {
+        let input = self.terminal.poll_input(timeout)?;
+        match &input {
+            Some(Input::Quit) | None => {
+                if let Some(world) = self.connection.newest() {
+                    self.world = Some(world);
+                    self.draw_world()?;
+                }
+            }
+            Some(input) => {
+                self.connection.send(input)?;
+                self.world = Some(self.connection.receive()?);
+            }
+        }
+        Ok(input)
+    }
+
+    fn draw(&mut self, state: &State) -> Result<(), GameError> {
+        match self.world {
+            Some(_) => self.draw_world(),
+            None => self.terminal.draw(state),
+        }
+    }
+
+    /// The server saves the world, not us.
+    fn save(&mut self, _state: &State) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<LoadResult, GameError> {
+        let world = self.connection.receive()?;
+        let state = world.0.clone();
+        self.world = Some(world);
+        Ok(LoadResult::Loaded(Box::new(state)))
+    }
+
+    fn back_up_save(&mut self) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    fn confirm(&mut self, msg: &str) -> Result<bool, GameError> {
+        self.terminal.confirm(msg)
+    }
+
+    fn run_command(&mut self, state: &State, command: &str) -> Result<Option<String>, GameError> {
+        let world = self.world.as_ref().map_or(state, |(world, ..)| world);
+        self.terminal.run_command(world, command)
+    }
+
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, GameError> {
+        self.terminal.ask_for_text(prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IsShift, Item, StateBuilder, Tile};
+
+    /// Runs a server for `state` on a free port. Gives back where it is, and
+    /// the world when everyone left.
+    fn start_server(state: State) -> (std::net::SocketAddr, thread::JoinHandle<State>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        (addr, thread::spawn(move || serve_on(listener, state)))
+    }
+
+    /// Sends an input, and gets the world with it.
+    fn play(connection: &mut Connection, input: Input) -> State {
+        connection.send(&input).unwrap();
+        connection.receive().unwrap().0
+    }
+
+    /// Two players next to each other, each above a wall.
+    fn two_walls() -> State {
+        let mut builder = StateBuilder::new();
+        for x in -3..=3 {
+            for y in -3..=3 {
+                builder = builder.with_tile((x, y), Tile::Empty);
+            }
+        }
+        builder.with_tile((0, 1), Tile::WallFull).with_tile((1, 1), Tile::WallFull).build()
+    }
+
+    #[test]
+    fn two_clients_dig_next_to_each_other() {
+        let (addr, server) = start_server(two_walls());
+        let mut first = Connection::open(addr).unwrap();
+        let mut second = Connection::open(addr).unwrap();
+        assert_eq!((first.player, second.player), (0, 1));
+        let dig = Input::Dir(Dir::Down, IsShift::No);
+        for stage in [Tile::WallHalf, Tile::WallLow, Tile::Empty] {
+            assert_eq!(play(&mut first, dig.clone()).get_tile((0, 1)), stage);
+            let world = play(&mut second, dig.clone());
+            assert_eq!((world.get_tile((0, 1)), world.get_tile((1, 1))), (stage, stage));
+        }
+        drop((first, second));
+        let mut world = server.join().unwrap();
+        assert_eq!(world.inventory().count_of(&Item::Wall), 1);
+        world.swap_player(0);
+        assert_eq!(world.inventory().count_of(&Item::Wall), 1);
+    }
+
+    #[test]
+    fn ignored_inputs_are_answered() {
+        let (addr, server) = start_server(two_walls());
+        let mut client = Connection::open(addr).unwrap();
+        let dig = Input::Dir(Dir::Down, IsShift::No);
+        for input in [Input::Command("join".to_string()), Input::ForPlayer(1, Box::new(dig))] {
+            let world = play(&mut client, input);
+            assert!(world.other_players().is_empty());
+            assert_eq!(world.get_tile((0, 1)), Tile::WallFull);
+        }
+        drop(client);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn players_walk_on_the_server() {
+        let (addr, server) = start_server(two_walls());
+        let mut client = Connection::open(addr).unwrap();
+        let target = (3, 3);
+        play(&mut client, Input::MoveTo(target));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while client.receive().unwrap().0.player_pos() != target {
+            assert!(Instant::now() < deadline, "The player never got there");
+        }
+        drop(client);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+//! Finding the way from one place to another, for walking there by itself.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{Dir, Pos};
+
+/// The shortest way from `from` to `to`, as the directions to step in,
+/// stepping only on positions where `is_free`. `None` if there is no way
+/// that takes at most `max_cost` steps, so an unreachable target can't take
+/// forever.
+pub fn find_path(
+    from: Pos,
+    to: Pos,
+    max_cost: u32,
+    mut is_free: impl FnMut(Pos) -> bool,
+) -> Option<Vec<Dir>> {
+    let estimate = |(x, y): Pos| x.abs_diff(to.0) + y.abs_diff(to.1);
+    // The fewest steps to every position found so far, and the direction of
+    // the last of them.
+    let mut best: HashMap<Pos, (u32, Option<Dir>)> = HashMap::from([(from, (0, None))]);
+    let mut open = BinaryHeap::from([Reverse((estimate(from), 0, from))]);
+    while let Some(Reverse((_, cost, pos))) = open.pop() {
+        if pos == to {
+            return Some(rebuild(&best, to));
+        }
+        if best[&pos].0 < cost {
+            continue; // Found a shorter way here since
+        }
+        for dir in Dir::ALL {
+            let next = pos + dir;
+            let next_cost = cost + 1;
+            let shorter = best.get(&next).is_none_or(|&(known, _)| next_cost < known);
+            if next_cost + estimate(next) > max_cost || !shorter || !is_free(next) {
+                continue;
+            }
+            best.insert(next, (next_cost, Some(dir)));
+            open.push(Reverse((next_cost + estimate(next), next_cost, next)));
+        }
+    }
+    None
+}
+
+/// The directions that lead to `to`, from how every position was reached.
+fn rebuild(best: &HashMap<Pos, (u32, Option<Dir>)>, to: Pos) -> Vec<Dir> {
+    let mut path = vec![];
+    let mut pos = to;
+    while let Some(dir) = best[&pos].1 {
+        path.push(dir);
+        pos = pos + dir.opposite();
+    }
+    path.reverse();
+    path
+}
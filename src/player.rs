@@ -0,0 +1,42 @@
+//! Extra players, for playing together on one keyboard.
+//!
+//! The player whose input is being handled always lives in `State`'s own
+//! fields (`player_pos`, `inventory`, ...), so the rest of the game does not
+//! have to know which player it is. The other players wait here until it is
+//! their turn. See `State::as_player`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Dir, Inventory, Item, MAX_HUNGER, Pos};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub pos: Pos,
+    pub dir: Dir,
+    #[serde(default)]
+    pub inventory: Inventory,
+    #[serde(default)]
+    pub selected_item: Option<Item>,
+    #[serde(default)]
+    pub last_built: Option<Item>,
+    #[serde(default = "default_hunger")]
+    pub hunger: u8,
+}
+
+fn default_hunger() -> u8 {
+    MAX_HUNGER
+}
+
+impl Player {
+    /// A player who just joined, with nothing yet.
+    pub fn new(pos: Pos) -> Self {
+        Self {
+            pos,
+            dir: Dir::Down,
+            inventory: Inventory::default(),
+            selected_item: None,
+            last_built: None,
+            hunger: MAX_HUNGER,
+        }
+    }
+}
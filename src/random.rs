@@ -0,0 +1,114 @@
+//! Randomness that is the same every time.
+//!
+//! Everything random in the game comes from `hash_rng`, which is made from
+//! the seed of the world, a position, a tick and a salt. So the same world
+//! always plays out the same way: a replay of the same inputs, or a test,
+//! sees the same numbers on every run and every platform. Different salts
+//! keep different things (like the weather and saplings) from always
+//! happening together.
+
+use rand::RngCore;
+
+use crate::Pos;
+
+/// Salts for `hash_rng`, one for every kind of thing that is random.
+pub mod salt {
+    pub const TILE_TICK: u64 = 1;
+    pub const ROCK: u64 = 2;
+    pub const BONUS_ROCK: u64 = 3;
+    pub const TRADER: u64 = 4;
+    pub const TRADE_OFFERS: u64 = 5;
+    pub const WEATHER: u64 = 6;
+}
+
+/// A generator for the randomness of one thing, at one place and time.
+pub fn hash_rng(seed: u64, pos: Pos, tick: u64, salt: u64) -> HashRng {
+    let (x, y) = pos;
+    // Every input is mixed in on its own, so that swapping them (like x and
+    // y) gives different numbers.
+    let state = [x as u32 as u64, y as u32 as u64, tick, salt]
+        .into_iter()
+        .fold(seed, |state, input| splitmix64(state ^ splitmix64(input)));
+    HashRng(state)
+}
+
+/// SplitMix64. Small and fast, and written out here (unlike `rand`'s
+/// generators) so its numbers never change with a new version of `rand`.
+#[derive(Debug, Clone)]
+pub struct HashRng(u64);
+
+/// Scrambles a number. Also the output step of SplitMix64.
+fn splitmix64(x: u64) -> u64 {
+    let x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+impl RngCore for HashRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let x = splitmix64(self.0);
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        x
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(seed: u64, pos: Pos, tick: u64, salt: u64) -> Vec<u64> {
+        let mut rng = hash_rng(seed, pos, tick, salt);
+        (0..8).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn the_same_inputs_give_the_same_numbers() {
+        assert_eq!(numbers(7, (3, -4), 100, salt::ROCK), numbers(7, (3, -4), 100, salt::ROCK));
+        // Written out, so a change to the generator is seen, since it would
+        // change every world.
+        assert_eq!(hash_rng(0, (0, 0), 0, 0).next_u64(), 0x1db8_2bce_51b2_6fd3);
+    }
+
+    #[test]
+    fn different_salts_give_different_numbers() {
+        let salts = [
+            salt::TILE_TICK,
+            salt::ROCK,
+            salt::BONUS_ROCK,
+            salt::TRADER,
+            salt::TRADE_OFFERS,
+            salt::WEATHER,
+        ];
+        for (i, &a) in salts.iter().enumerate() {
+            for &b in &salts[i + 1..] {
+                assert_ne!(numbers(7, (3, -4), 100, a), numbers(7, (3, -4), 100, b));
+            }
+        }
+    }
+
+    #[test]
+    fn every_input_changes_the_numbers() {
+        let base = numbers(7, (3, -4), 100, salt::ROCK);
+        assert_ne!(numbers(8, (3, -4), 100, salt::ROCK), base);
+        assert_ne!(numbers(7, (-4, 3), 100, salt::ROCK), base);
+        assert_ne!(numbers(7, (3, -3), 100, salt::ROCK), base);
+        assert_ne!(numbers(7, (3, -4), 101, salt::ROCK), base);
+    }
+}
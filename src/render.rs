@@ -0,0 +1,373 @@
+//! How the world looks, for every platform.
+//!
+//! This decides which characters and colors every tile is drawn as, and
+//! which tiles are on screen. Actually putting them on a screen is up to the
+//! platform (see `frame` for laying out the rest of a screen of text).
+
+use crate::tiles::WOOD_MAX;
+use crate::{Dir, Item, MAX_LIGHT, Pos, State, Tile, Weather, WoodKind};
+
+/// The colors the game is drawn in. These are the colors that terminals
+/// have, so the terminal can draw them as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// Whatever the platform draws text in (or behind it) by default.
+    Reset,
+    Black,
+    DarkGrey,
+    Grey,
+    White,
+    Red,
+    Green,
+    Yellow,
+    DarkYellow,
+    Cyan,
+}
+
+impl Color {
+    /// The color that this is usually shown as. `Reset` is up to the
+    /// platform, so the caller chooses.
+    pub const fn rgb(self, reset: [u8; 3]) -> [u8; 3] {
+        match self {
+            Color::Reset => reset,
+            Color::Black => [0, 0, 0],
+            Color::DarkGrey => [128, 128, 128],
+            Color::Grey => [192, 192, 192],
+            Color::White => [255, 255, 255],
+            Color::Red => [255, 0, 0],
+            Color::Green => [0, 255, 0],
+            Color::Yellow => [255, 255, 0],
+            Color::DarkYellow => [128, 128, 0],
+            Color::Cyan => [0, 255, 255],
+        }
+    }
+}
+
+/// The chars to draw on the screen for some game thing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chars {
+    pub left: char,
+    pub right: char,
+    pub bg: Color,
+    pub fg: Color,
+}
+
+impl Chars {
+    pub const fn new(left: char, right: char) -> Self {
+        Chars {
+            left,
+            right,
+            bg: Color::Reset,
+            fg: Color::Reset,
+        }
+    }
+
+    pub const fn single(c: char) -> Self {
+        Self::new(c, c)
+    }
+
+    pub const fn with_fg(mut self, fg: Color) -> Self {
+        self.fg = fg;
+        self
+    }
+
+    pub const fn with_bg(mut self, bg: Color) -> Self {
+        self.bg = bg;
+        self
+    }
+
+    /// The one character that looks most like these two.
+    pub fn single_char(self) -> char {
+        if self.left == ' ' { self.right } else { self.left }
+    }
+
+    /// The colors that the left and right characters look like from afar.
+    pub fn rgb(self) -> [[u8; 3]; 2] {
+        let fg = self.fg.rgb([255, 255, 255]);
+        let bg = self.bg.rgb([0, 0, 0]);
+        [self.left, self.right].map(|c| {
+            let t = coverage(c);
+            std::array::from_fn(|i| (bg[i] as f32 * (1.0 - t) + fg[i] as f32 * t) as u8)
+        })
+    }
+}
+
+impl From<char> for Chars {
+    fn from(char: char) -> Self {
+        Self::single(char)
+    }
+}
+
+impl From<[char; 2]> for Chars {
+    fn from(chars: [char; 2]) -> Self {
+        Self::new(chars[0], chars[1])
+    }
+}
+
+/// How much of a character's cell is covered by it, from 0 to 1.
+fn coverage(c: char) -> f32 {
+    match c {
+        ' ' => 0.0,
+        '░' => 0.25,
+        '▒' | '▀' | '▄' => 0.5,
+        '▓' => 0.75,
+        '█' => 1.0,
+        _ => 0.5,
+    }
+}
+
+/// One shade for every stage of wood.
+const SHADES: [char; WOOD_MAX as usize + 1] = ['░', '▒', '▓', '█'];
+
+fn wood_color(kind: WoodKind) -> Color {
+    match kind {
+        WoodKind::Oak => Color::DarkYellow,
+        WoodKind::Birch => Color::Grey,
+    }
+}
+
+/// A tile get's drawn to two characters because most fonts are taller than
+/// they are wide.
+pub fn draw_tile(tile: Tile) -> Chars {
+    match tile {
+        Tile::WallFull => ['█', '█'].into(),
+        Tile::WallHalf => ['▓', '▓'].into(),
+        Tile::WallLow => ['▒', '▒'].into(),
+        Tile::Empty => [' ', ' '].into(),
+        Tile::Wood(kind, n) => Chars::single(SHADES[n.min(WOOD_MAX) as usize]).with_fg(wood_color(kind)),
+        Tile::Bed => Chars::single('▄').with_fg(Color::Red),
+        Tile::Torch => Chars::new('¡', ' ').with_fg(Color::Yellow),
+        Tile::Sand => Chars::single('░').with_fg(Color::Yellow),
+        Tile::Sapling(_) => Chars::new('♣', ' ').with_fg(Color::Green),
+        Tile::Ice => Chars::single('░').with_fg(Color::Cyan),
+        Tile::Waystone => Chars::new('◊', ' ').with_fg(Color::Cyan),
+        Tile::Barrier => Chars::single('╳').with_fg(Color::DarkGrey),
+        Tile::Trader => Chars::new('☻', ' ').with_fg(Color::Green),
+        Tile::Bush(true) => Chars::new('※', ' ').with_fg(Color::Red),
+        Tile::Bush(false) => Chars::new('※', ' ').with_fg(Color::Green),
+    }
+}
+
+/// How an item lying on the ground looks. These are small, so they can be
+/// told apart from tiles.
+pub fn draw_item(item: &Item) -> Chars {
+    match item {
+        Item::Wall => Chars::new('•', ' ').with_fg(Color::Grey),
+        Item::Wood(kind) => Chars::new('=', ' ').with_fg(wood_color(*kind)),
+        Item::Bed => Chars::new('b', ' ').with_fg(Color::Red),
+        Item::Torch => Chars::new('i', ' ').with_fg(Color::Yellow),
+        Item::Sand => Chars::new('∙', ' ').with_fg(Color::Yellow),
+        Item::Sapling(_) => Chars::new('♠', ' ').with_fg(Color::Green),
+        Item::Rock => Chars::new('o', ' ').with_fg(Color::DarkGrey),
+        Item::Waystone => Chars::new('◇', ' ').with_fg(Color::Cyan),
+        Item::Compass => Chars::new('⊕', ' ').with_fg(Color::Red),
+        Item::Berry => Chars::new('∘', ' ').with_fg(Color::Red),
+    }
+}
+
+/// How the items lying on a tile look. When there are a few, they take turns
+/// every tick, with a `+` to show that there are more.
+fn draw_drops(items: &[Item], tick: u64) -> Option<Chars> {
+    let item = items.get((tick % items.len().max(1) as u64) as usize)?;
+    let chars = draw_item(item);
+    Some(if items.len() > 1 { Chars { right: '+', ..chars } } else { chars })
+}
+
+/// Is a raindrop drawn on an empty tile at a tick? Only on some tiles, and
+/// they move down every tick, so it looks like it is falling.
+fn is_raindrop((x, y): Pos, tick: u64) -> bool {
+    let y = y - (tick % 11) as i32;
+    (x * 7 + y * 3).rem_euclid(11) == 0
+}
+
+const RAINDROP: Chars = Chars::new('/', ' ').with_fg(Color::Grey);
+
+/// A tile that is being dug at, with a crack that grows with every hit. See
+/// `State::breaking`.
+fn cracked(chars: Chars, hits: u8, hits_to_break: u8) -> Chars {
+    let shade = hits as usize * SHADES.len() / hits_to_break.max(1) as usize;
+    Chars {
+        right: SHADES[shade.min(SHADES.len() - 1)],
+        ..chars
+    }
+}
+
+const UNEXPLORED: Chars = Chars::new('?', ' ').with_fg(Color::DarkGrey);
+
+/// The background of where the selected item would be built. See
+/// `State::build_target`.
+const BUILD_TARGET_BG: Color = Color::DarkGrey;
+
+/// How a tile looks in some amount of light.
+fn in_light(chars: Chars, light: u8) -> Chars {
+    match light {
+        0 => Chars::single(' '),
+        _ if light <= MAX_LIGHT / 2 => chars.with_fg(Color::DarkGrey),
+        _ => chars,
+    }
+}
+
+/// Player character
+pub fn player(dir: Dir) -> Chars {
+    Chars::from(match dir {
+        Dir::Up => ['▀', '▀'],
+        Dir::Down => ['▄', '▄'],
+        Dir::Left => ['█', ' '],
+        Dir::Right => [' ', '█'],
+    })
+    .with_fg(Color::White)
+    .with_bg(Color::DarkGrey)
+}
+
+/// How many tiles wide and tall every cell is, at every zoom level. See
+/// `State::zoom`.
+pub const ZOOM_LEVELS: [i32; 5] = [1, 2, 4, 8, 16];
+
+/// How many tiles wide and tall a cell is sampled at, when zoomed out. Keeps
+/// zooming out from looking at more tiles than the screen has room for.
+const OVERVIEW_SAMPLES: i32 = 4;
+
+/// The tiles shown in a view of the given size (in cells), as the top-left
+/// and bottom-right corners. Centered between all the players, but the first
+/// player is always in view.
+///
+/// When zoomed out, every cell is a block of tiles, and the blocks are lined
+/// up with the zoom so they don't change as the player moves.
+pub fn viewport(state: &State, cells_in_a_row: i32, rows: i32) -> (Pos, Pos) {
+    let zoom = state.zoom();
+    let (cells_in_a_row, rows) = (cells_in_a_row * zoom, rows * zoom);
+    let players = 1 + state.other_players().len() as i32;
+    let (sum_x, sum_y) = state
+        .other_players()
+        .iter()
+        .fold(state.player_pos, |(x, y), p| (x + p.pos.0, y + p.pos.1));
+    let center = (sum_x.div_euclid(players), sum_y.div_euclid(players));
+    // Where the top-left corner can be while the first player is still in
+    // view, with a cell to spare on each side.
+    let keep_in_view = |center: i32, size: i32, player: i32| {
+        let start = center - size / 2;
+        let start = start.min(player - zoom).max(player + 1 + zoom - size);
+        start.div_euclid(zoom) * zoom
+    };
+    let top_left = (
+        keep_in_view(center.0, cells_in_a_row, state.player_pos.0),
+        keep_in_view(center.1, rows, state.player_pos.1),
+    );
+    let bottom_right = (top_left.0 + cells_in_a_row - 1, top_left.1 + rows - 1);
+    (top_left, bottom_right)
+}
+
+/// How every tile in a viewport (see `viewport`) looks, with the players on
+/// top, row by row. When zoomed out, see `overview_cells`.
+pub fn world_cells(state: &State, (top_left, bottom_right): (Pos, Pos)) -> Vec<Vec<Chars>> {
+    if state.zoom() > 1 {
+        return overview_cells(state, (top_left, bottom_right), state.zoom());
+    }
+    let cells_in_a_row = (bottom_right.0 - top_left.0 + 1).max(0) as u32;
+    let rows = (bottom_right.1 - top_left.1 + 1).max(0) as u32;
+    let tiles = state.tiles_in_rect(top_left, cells_in_a_row, rows);
+    let light = state.light_in_rect(top_left, cells_in_a_row, rows);
+    let build_target = state.build_target();
+    (0..rows)
+        .map(|row| {
+            (0..cells_in_a_row)
+                .map(|col| {
+                    let pos = (top_left.0 + col as i32, top_left.1 + row as i32);
+                    let other_player = state.other_players().iter().find(|p| p.pos == pos);
+                    if pos == state.player_pos {
+                        player(state.player_dir)
+                    } else if let Some(other_player) = other_player {
+                        player(other_player.dir).with_fg(Color::Cyan)
+                    } else if !state.is_explored(pos) {
+                        UNEXPLORED
+                    } else {
+                        let i = (row * cells_in_a_row + col) as usize;
+                        let tick = state.stats().ticks_survived;
+                        let raindrop = state.weather() == Weather::Rain
+                            && tiles[i] == Tile::Empty
+                            && is_raindrop(pos, tick);
+                        let chars = match draw_drops(state.drops_at(pos), tick) {
+                            Some(chars) => chars,
+                            None if raindrop => RAINDROP,
+                            None => draw_tile(tiles[i]),
+                        };
+                        let chars = match state.breaking() {
+                            Some((breaking, hits)) if breaking == pos => {
+                                let tool = state.selected_item.as_ref();
+                                cracked(chars, hits, tiles[i].hits_to_break(tool, &state.rules()))
+                            }
+                            _ => chars,
+                        };
+                        // The newest effect is on top.
+                        let effect = state.effects().iter().rev().find(|e| e.pos == pos);
+                        let chars = match effect {
+                            Some(effect) => Chars::new(effect.glyph, ' ').with_fg(effect.color),
+                            None => chars,
+                        };
+                        let chars = in_light(chars, light[i]);
+                        // Even in the dark, to see where building goes.
+                        if build_target == Some(pos) {
+                            chars.with_bg(BUILD_TARGET_BG)
+                        } else {
+                            chars
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Like `world_cells`, but every cell is a `zoom` by `zoom` block of tiles,
+/// drawn as the tile that most of the block is. Only a few tiles of each block
+/// are looked at (see `OVERVIEW_SAMPLES`), and everything is drawn lit, to see
+/// as much as possible.
+fn overview_cells(
+    state: &State,
+    (top_left, bottom_right): (Pos, Pos),
+    zoom: i32,
+) -> Vec<Vec<Chars>> {
+    let cells_in_a_row = (bottom_right.0 - top_left.0 + 1).max(0) / zoom;
+    let rows = (bottom_right.1 - top_left.1 + 1).max(0) / zoom;
+    let cell_of = |(x, y): Pos| {
+        ((x - top_left.0).div_euclid(zoom), (y - top_left.1).div_euclid(zoom))
+    };
+    let step = (zoom / OVERVIEW_SAMPLES).max(1);
+    let offsets: Vec<i32> = (0..zoom).step_by(step as usize).collect();
+    (0..rows)
+        .map(|row| {
+            (0..cells_in_a_row)
+                .map(|col| {
+                    let other_player =
+                        state.other_players().iter().find(|p| cell_of(p.pos) == (col, row));
+                    if cell_of(state.player_pos) == (col, row) {
+                        return player(state.player_dir);
+                    } else if let Some(other_player) = other_player {
+                        return player(other_player.dir).with_fg(Color::Cyan);
+                    }
+                    let (left, top) = (top_left.0 + col * zoom, top_left.1 + row * zoom);
+                    let samples: Vec<Pos> = offsets
+                        .iter()
+                        .flat_map(|&dy| offsets.iter().map(move |&dx| (left + dx, top + dy)))
+                        .filter(|&pos| state.is_explored(pos))
+                        .collect();
+                    majority(state.tiles_at(samples)).map_or(UNEXPLORED, draw_tile)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The tile that comes up the most. On a tie, the one that came up first,
+/// so the overview doesn't flicker.
+fn majority(tiles: Vec<Tile>) -> Option<Tile> {
+    let mut counts: Vec<(Tile, usize)> = vec![];
+    for tile in tiles {
+        match counts.iter_mut().find(|(t, _)| *t == tile) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((tile, 1)),
+        }
+    }
+    // `max_by_key` gives the last of the biggest, so look from the end.
+    counts.into_iter().rev().max_by_key(|&(_, count)| count).map(|(tile, _)| tile)
+}
@@ -0,0 +1,339 @@
+//! Saving the game to files, for the platforms that have a file system.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::IgnoredAny;
+
+use crate::{LoadResult, State};
+
+/// How a game is written in a save file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveFormat {
+    /// Text that can be read and fixed by hand.
+    #[default]
+    Toml,
+    /// Smaller and faster than TOML, for big worlds.
+    MessagePack,
+}
+
+impl SaveFormat {
+    /// The name of the save file in this format. Different formats have
+    /// different files, so switching does not read a save as the wrong one.
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            SaveFormat::Toml => "save",
+            SaveFormat::MessagePack => "save.msgpack",
+        }
+    }
+
+    fn serialize(self, state: &State) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        Ok(match self {
+            // TODO: the toml crate's pretty printer actually kind of sucks. I
+            // should implement my own and PR it.
+            SaveFormat::Toml => toml::to_string_pretty(state)?.into_bytes(),
+            SaveFormat::MessagePack => rmp_serde::to_vec_named(state)?,
+        })
+    }
+
+    /// The names of the fields at the top of a save, without reading what is
+    /// in them.
+    fn field_names(self, bytes: &[u8]) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let fields: BTreeMap<String, IgnoredAny> = match self {
+            SaveFormat::Toml => toml::from_str(std::str::from_utf8(bytes)?)?,
+            SaveFormat::MessagePack => rmp_serde::from_slice(bytes)?,
+        };
+        Ok(fields.into_keys().collect())
+    }
+
+    fn deserialize(self, bytes: &[u8]) -> Result<State, Box<dyn Error + Send + Sync>> {
+        Ok(match self {
+            SaveFormat::Toml => {
+                let text = std::str::from_utf8(bytes)?;
+                // Just the message, without the part of the file it is in,
+                // which is too long to show the player.
+                toml::from_str(text).map_err(|e| e.message().to_string())?
+            }
+            SaveFormat::MessagePack => rmp_serde::from_slice(bytes)?,
+        })
+    }
+}
+
+/// Why saving or loading failed.
+#[derive(Debug)]
+pub enum SaveError {
+    /// Reading or writing a file failed.
+    Io(PathBuf, io::Error),
+    /// There is no directory to keep saves in.
+    NoDataDir,
+    /// The game could not be written in the save format.
+    Serialize(PathBuf, Box<dyn Error + Send + Sync>),
+    /// There is a save, but it is not a game in the save format.
+    Deserialize(PathBuf, Box<dyn Error + Send + Sync>),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            SaveError::NoDataDir => {
+                write!(f, "Could not find a directory to save the game in (is HOME set?)")
+            }
+            SaveError::Serialize(path, e) => {
+                write!(f, "Failed to save the game to {}: {}", path.display(), e)
+            }
+            SaveError::Deserialize(path, e) => {
+                write!(f, "Failed to load the game from {}: {}", path.display(), e)
+            }
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io(_, e) => Some(e),
+            SaveError::NoDataDir => None,
+            SaveError::Serialize(_, e) | SaveError::Deserialize(_, e) => Some(&**e),
+        }
+    }
+}
+
+/// What `FileSaveStore::validate` found in a save that loads.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct SaveReport {
+    /// Fields that the save doesn't have, which were given their defaults.
+    /// Normal for saves from older versions of the game.
+    pub defaulted: Vec<String>,
+    /// Fields that the game doesn't know, which are lost the next time it
+    /// saves. Likely from a newer version of the game, or a typo.
+    pub unknown: Vec<String>,
+}
+
+/// Saves and loads the game as files in a directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileSaveStore {
+    dir: PathBuf,
+    format: SaveFormat,
+}
+
+impl FileSaveStore {
+    /// Keeps the saves in `dir`, which is made when first saving if it is
+    /// not there.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            format: SaveFormat::default(),
+        }
+    }
+
+    /// Keeps the saves in the usual place for the data of programs.
+    pub fn in_data_dir() -> Result<Self, SaveError> {
+        let dir = dirs::data_dir().ok_or(SaveError::NoDataDir)?;
+        Ok(Self::new(dir.join("j-minecraft-2d")))
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn save_path(&self) -> PathBuf {
+        self.dir.join(self.format.file_name())
+    }
+
+    /// Is there a saved game?
+    pub fn exists(&self) -> bool {
+        self.save_path().exists()
+    }
+
+    /// The saved game, or `None` if there is none.
+    pub fn load_state(&self) -> Result<Option<State>, SaveError> {
+        let path = self.save_path();
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(SaveError::Io(path, e)),
+        };
+        match self.format.deserialize(&bytes) {
+            Ok(state) => Ok(Some(state)),
+            Err(e) => Err(SaveError::Deserialize(path, e)),
+        }
+    }
+
+    /// Loads the save without playing it, to check it. Returns `None` if
+    /// there is no save, and an error if it can't be loaded.
+    pub fn validate(&self) -> Result<Option<SaveReport>, SaveError> {
+        let path = self.save_path();
+        let Some(state) = self.load_state()? else {
+            return Ok(None);
+        };
+        let bytes = std::fs::read(&path).map_err(|e| SaveError::Io(path.clone(), e))?;
+        let in_file = self
+            .format
+            .field_names(&bytes)
+            .map_err(|e| SaveError::Deserialize(path.clone(), e))?;
+        // The fields a save has when the game writes it now.
+        let known = self
+            .format
+            .serialize(&state)
+            .and_then(|bytes| self.format.field_names(&bytes))
+            .map_err(|e| SaveError::Serialize(path, e))?;
+        Ok(Some(SaveReport {
+            defaulted: known.iter().filter(|&f| !in_file.contains(f)).cloned().collect(),
+            unknown: in_file.iter().filter(|&f| !known.contains(f)).cloned().collect(),
+        }))
+    }
+
+    /// Like `load_state`, but a save that can't be read is a
+    /// `LoadResult::Corrupt` instead of an error. See `Platform::load`.
+    pub fn load(&self) -> Result<LoadResult, SaveError> {
+        Ok(match self.load_state() {
+            Ok(Some(state)) => LoadResult::Loaded(Box::new(state)),
+            Ok(None) => LoadResult::NoSave,
+            Err(SaveError::Deserialize(_, e)) => LoadResult::Corrupt {
+                reason: e.to_string(),
+            },
+            Err(e) => return Err(e),
+        })
+    }
+
+    /// Saves the game. The last save is kept next to it, in case this one
+    /// turns out to be broken.
+    pub fn save_state(&self, state: &State) -> Result<(), SaveError> {
+        let path = self.save_path();
+        let bytes = self
+            .format
+            .serialize(state)
+            .map_err(|e| SaveError::Serialize(path.clone(), e))?;
+        // Written to another file first, so failing half way through doesn't
+        // ruin the last save.
+        let new = with_suffix(&path, ".new");
+        self.write(&new, &bytes)?;
+        if path.exists() {
+            let backup = with_suffix(&path, ".bak");
+            std::fs::rename(&path, &backup).map_err(|e| SaveError::Io(backup, e))?;
+        }
+        std::fs::rename(&new, &path).map_err(|e| SaveError::Io(path, e))
+    }
+
+    /// Moves the save aside. See `Platform::back_up_save`.
+    pub fn back_up(&self) -> Result<(), SaveError> {
+        let path = self.save_path();
+        let corrupt = with_suffix(&path, ".corrupt");
+        std::fs::rename(&path, &corrupt).map_err(|e| SaveError::Io(corrupt, e))
+    }
+
+    /// Writes some other file to the directory of the saves.
+    pub fn write_file(&self, file_name: &Path, bytes: &[u8]) -> Result<(), SaveError> {
+        self.write(&self.dir.join(file_name), bytes)
+    }
+
+    /// Writes a file, making its directory first if needed.
+    fn write(&self, path: &Path, bytes: &[u8]) -> Result<(), SaveError> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| SaveError::Io(self.dir.clone(), e))?;
+        std::fs::write(path, bytes).map_err(|e| SaveError::Io(path.to_path_buf(), e))
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dir, Item, Menu, StateBuilder, Tile};
+
+    /// A store in a directory of its own, which isn't made yet.
+    fn store_in_new_dir(name: &str) -> FileSaveStore {
+        let dir = std::env::temp_dir()
+            .join(format!("minecraft-2d-store-{name}-{}", std::process::id()))
+            .join("saves");
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+        FileSaveStore::new(dir)
+    }
+
+    fn some_game() -> State {
+        StateBuilder::new()
+            .seed(7)
+            .player_pos((3, -2))
+            .facing(Dir::Left)
+            .with_tile((4, -2), Tile::Sand)
+            .with_item(Item::Wall, 12)
+            .build()
+    }
+
+    #[test]
+    fn a_saved_game_loads_the_same() {
+        for format in [SaveFormat::Toml, SaveFormat::MessagePack] {
+            let store = store_in_new_dir(&format!("{format:?}")).with_format(format);
+            assert!(store.load_state().unwrap().is_none());
+            store.save_state(&some_game()).unwrap();
+            let loaded = store.load_state().unwrap().expect("The game was saved");
+            assert_eq!(loaded.player_pos(), (3, -2));
+            assert_eq!(loaded.player_dir, Dir::Left);
+            assert_eq!(loaded.get_tile((4, -2)), Tile::Sand);
+            assert_eq!(loaded.inventory().count_of(&Item::Wall), 12);
+            std::fs::remove_dir_all(store.dir().parent().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_loaded_game_keeps_its_selected_item_but_not_its_menus() {
+        let mut game = some_game();
+        game.selected_item = Some(Item::Wall);
+        game.push_menu(Menu::Stats);
+        game.push_menu(Menu::Inventory);
+        assert_eq!(game.current_menu(), Menu::Inventory);
+        for format in [SaveFormat::Toml, SaveFormat::MessagePack] {
+            let loaded = format.deserialize(&format.serialize(&game).unwrap()).unwrap();
+            assert!(loaded.menus.is_empty(), "{format:?}");
+            assert_eq!(loaded.current_menu(), Menu::None);
+            assert_eq!(loaded.selected_item, Some(Item::Wall));
+        }
+    }
+
+    #[test]
+    fn saving_makes_the_directory() {
+        let store = store_in_new_dir("missing");
+        assert!(!store.dir().exists());
+        assert!(!store.exists());
+        store.save_state(&some_game()).unwrap();
+        assert!(store.exists());
+        store.write_file(Path::new("map.txt"), b"#").unwrap();
+        assert_eq!(std::fs::read(store.dir().join("map.txt")).unwrap(), b"#");
+        std::fs::remove_dir_all(store.dir().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn the_last_save_is_kept_as_a_backup() {
+        let store = store_in_new_dir("backup");
+        store.save_state(&some_game()).unwrap();
+        store.save_state(&StateBuilder::new().player_pos((9, 9)).build()).unwrap();
+        let backup = with_suffix(&store.save_path(), ".bak");
+        let backup = SaveFormat::Toml.deserialize(&std::fs::read(backup).unwrap()).unwrap();
+        assert_eq!(backup.player_pos(), (3, -2));
+        assert_eq!(store.load_state().unwrap().unwrap().player_pos(), (9, 9));
+        std::fs::remove_dir_all(store.dir().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn a_broken_save_is_corrupt_and_can_be_backed_up() {
+        let store = store_in_new_dir("corrupt");
+        store.write_file(Path::new("save"), b"not = [a game").unwrap();
+        assert!(matches!(store.load().unwrap(), LoadResult::Corrupt { .. }));
+        store.back_up().unwrap();
+        assert!(!store.exists());
+        assert!(matches!(store.load().unwrap(), LoadResult::NoSave));
+        std::fs::remove_dir_all(store.dir().parent().unwrap()).unwrap();
+    }
+}
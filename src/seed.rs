@@ -0,0 +1,25 @@
+//! World seeds, and turning what the player typed into one.
+//!
+//! A seed is all it takes to recreate the generated parts of a world, so it
+//! doubles as a world code that players can share with each other.
+
+/// The seed of every world created before seeds were stored in saves.
+pub const DEFAULT_SEED: u32 = 12412;
+
+/// Turns text the player typed into a seed. Numbers are used as-is, so a
+/// shared world code always brings you back to the same world. Anything else
+/// is hashed, so "my cool world" is always the same world too.
+pub fn seed_from_str(text: &str) -> u32 {
+    let text = text.trim();
+    text.parse().unwrap_or_else(|_| fnv1a(text.as_bytes()))
+}
+
+/// The 32-bit FNV-1a hash. It's tiny, and unlike `std`'s hasher it is stable
+/// across versions and platforms, which is all we need here.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    const OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |hash, &b| (hash ^ b as u32).wrapping_mul(PRIME))
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Counts of things the player did. These only ever go up.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// Tiles broken all the way, into an item.
+    pub tiles_broken: u64,
+    pub tiles_placed: u64,
+    pub steps_taken: u64,
+    /// Items gotten from traders. There is no crafting yet, and trading is
+    /// the only way to make one item out of others.
+    pub items_crafted: u64,
+    pub ticks_survived: u64,
+    /// How long the world was played, in milliseconds. See `play_time`.
+    ///
+    /// Kept in time rather than in ticks, since the game ticks once for
+    /// every input and not at a steady rate, so ticks can't be turned into
+    /// time. That is also why `format_duration` takes a `Duration`.
+    pub play_time_ms: u64,
+}
+
+impl Stats {
+    /// The name of each stat with its value, for showing to the player.
+    pub fn lines(&self) -> [(&'static str, u64); 5] {
+        [
+            ("Tiles broken", self.tiles_broken),
+            ("Tiles placed", self.tiles_placed),
+            ("Steps taken", self.steps_taken),
+            ("Items traded for", self.items_crafted),
+            ("Ticks survived", self.ticks_survived),
+        ]
+    }
+
+    pub fn play_time(&self) -> Duration {
+        Duration::from_millis(self.play_time_ms)
+    }
+
+    pub fn add_play_time(&mut self, time: Duration) {
+        self.play_time_ms = self.play_time_ms.saturating_add(time.as_millis() as u64);
+    }
+}
+
+/// A duration the way the player reads it, like "2h 13m" or "59s". Only the
+/// two biggest units are shown.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_show_their_two_biggest_units() {
+        let secs = |secs| format_duration(Duration::from_secs(secs));
+        assert_eq!(secs(0), "0s");
+        assert_eq!(secs(59), "59s");
+        assert_eq!(secs(60), "1m 0s");
+        assert_eq!(secs(3599), "59m 59s");
+        assert_eq!(secs(3600), "1h 0m");
+        assert_eq!(secs(2 * 3600 + 13 * 60 + 59), "2h 13m");
+        assert_eq!(format_duration(Duration::from_millis(999)), "0s");
+    }
+}
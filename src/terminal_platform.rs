@@ -1,11 +1,23 @@
-use crate::{Dir, Input, IsShift, Menu, Platform, State, Tile};
+use crate::{Dir, GameError, GameEvent, Input, IsShift, LoadResult, Menu, Platform, Pos, State};
+use crate::frame::{
+    self, Frame, ITEM_LIST_TOP, MIN_SIZE, MIN_WIDTH_FOR_DETAILS, RenderOptions, menu_panel,
+    screen_to_world, viewport,
+};
+use crate::render::{self, draw_tile};
+use crate::save_store::{FileSaveStore, SaveError};
 use crossterm::cursor;
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
-use crossterm::style::{self, Attribute, Color, Colors, Print};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use crossterm::style::{self, Color, Colors, Print};
 use crossterm::terminal;
 use crossterm::{execute, queue};
 use std::io::{self, Write, stdout};
-use std::path::{Path, PathBuf};
+use std::path::Path;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 /*
 fn line_ending() -> &'static str {
@@ -16,79 +28,118 @@ fn line_ending() -> &'static str {
 }
 */
 
-mod border {
-    use std::io::{self, Write};
-
-    pub const TL: char = '┏';
-    pub const T: char = '━';
-    pub const TR: char = '┓';
-    pub const L: char = '┃';
-    pub const R: char = '┃';
-    pub const BL: char = '┗';
-    pub const B: char = '━';
-    pub const BR: char = '┛';
-
-    pub fn bottom_row(output: &mut impl Write, inner_width: u16) -> io::Result<()> {
-        write!(output, "{}", BL)?;
-        for _ in 0..inner_width {
-            write!(output, "{}", B)?;
-        }
-        write!(output, "{}", BR)
+fn on_letter_pressed(char: char) -> Option<Input> {
+    match char {
+        'w' | 'k' => Some(Input::Dir(Dir::Up, IsShift::No)),
+        's' | 'j' => Some(Input::Dir(Dir::Down, IsShift::No)),
+        'a' | 'h' => Some(Input::Dir(Dir::Left, IsShift::No)),
+        'd' | 'l' => Some(Input::Dir(Dir::Right, IsShift::No)),
+        'W' | 'K' => Some(Input::Dir(Dir::Up, IsShift::Yes)),
+        'S' | 'J' => Some(Input::Dir(Dir::Down, IsShift::Yes)),
+        'A' | 'H' => Some(Input::Dir(Dir::Left, IsShift::Yes)),
+        'D' | 'L' => Some(Input::Dir(Dir::Right, IsShift::Yes)),
+        'b' | 'B' => Some(Input::Build),
+        'e' | 'E' => Some(Input::Interact),
+        'q' => Some(Input::Quit),
+        // The command itself is typed in a prompt. See `ask_for_input`.
+        '/' => Some(Input::Command(String::new())),
+        'i' | 'I' => Some(Input::OpenInventory),
+        'n' | 'N' => Some(Input::OpenWaypoints),
+        'z' | 'Z' => Some(Input::CycleZoom),
+        'p' | 'P' => Some(Input::ToggleAutoPlace),
+        'm' | 'M' => Some(Input::ToggleLog),
+        'v' | 'V' => Some(Input::CycleVariant),
+        'f' | 'F' => Some(Input::Throw),
+        'r' | 'R' => Some(Input::Eat),
+        // Run by the platform. See `copy_screen`.
+        'y' | 'Y' => Some(Input::Command("copy".to_string())),
+        _ => None,
     }
+}
 
-    pub fn top_row(output: &mut impl Write, inner_width: u16) -> io::Result<()> {
-        write!(output, "{}", TL)?;
-        for _ in 0..inner_width {
-            write!(output, "{}", T)?;
-        }
-        write!(output, "{}", TR)
-    }
+/// The keys of the second player, when there is one. These take over keys
+/// that the first player has too (like i and h/j/k/l).
+fn on_second_player_letter_pressed(char: char) -> Option<Input> {
+    let input = match char {
+        'i' => Input::Dir(Dir::Up, IsShift::No),
+        'k' => Input::Dir(Dir::Down, IsShift::No),
+        'j' => Input::Dir(Dir::Left, IsShift::No),
+        'l' => Input::Dir(Dir::Right, IsShift::No),
+        'I' => Input::Dir(Dir::Up, IsShift::Yes),
+        'K' => Input::Dir(Dir::Down, IsShift::Yes),
+        'J' => Input::Dir(Dir::Left, IsShift::Yes),
+        'L' => Input::Dir(Dir::Right, IsShift::Yes),
+        ';' => Input::Build,
+        '\'' => Input::Interact,
+        _ => return None,
+    };
+    Some(Input::ForPlayer(1, Box::new(input)))
 }
 
-fn data_dir() -> io::Result<PathBuf> {
-    // TODO: Maybe return a result?
-    let dir = dirs::data_dir()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find data directory"))?;
-    let out = dir.join("j-minecraft-2d");
-    if !out.exists() {
-        std::fs::create_dir_all(&out)?;
+fn on_ctrl_letter_pressed(char: char) -> Option<Input> {
+    match char {
+        // Raw mode turns Ctrl-C into a key press instead of a signal, so we
+        // quit (and save) like any other quit.
+        'c' | 'C' => Some(Input::Quit),
+        'w' | 'W' => Some(Input::Run(Dir::Up)),
+        's' | 'S' => Some(Input::Run(Dir::Down)),
+        'a' | 'A' => Some(Input::Run(Dir::Left)),
+        'd' | 'D' => Some(Input::Run(Dir::Right)),
+        _ => None,
     }
-    Ok(out)
 }
 
-fn on_letter_pressed(char: char) -> Option<Input> {
+/// Alt and a direction looks that way. See `Input::Look`.
+fn on_alt_letter_pressed(char: char) -> Option<Input> {
     match char {
-        'w' => Some(Input::Dir(Dir::Up, IsShift::No)),
-        's' => Some(Input::Dir(Dir::Down, IsShift::No)),
-        'a' => Some(Input::Dir(Dir::Left, IsShift::No)),
-        'd' => Some(Input::Dir(Dir::Right, IsShift::No)),
-        'W' => Some(Input::Dir(Dir::Up, IsShift::Yes)),
-        'S' => Some(Input::Dir(Dir::Down, IsShift::Yes)),
-        'A' => Some(Input::Dir(Dir::Left, IsShift::Yes)),
-        'D' => Some(Input::Dir(Dir::Right, IsShift::Yes)),
-        'b' | 'B' => Some(Input::Build),
-        'q' => Some(Input::Quit),
-        'i' | 'I' => Some(Input::OpenInventory),
+        'w' | 'W' | 'k' | 'K' => Some(Input::Look(Dir::Up)),
+        's' | 'S' | 'j' | 'J' => Some(Input::Look(Dir::Down)),
+        'a' | 'A' | 'h' | 'H' => Some(Input::Look(Dir::Left)),
+        'd' | 'D' | 'l' | 'L' => Some(Input::Look(Dir::Right)),
         _ => None,
     }
 }
 
-fn on_key_event(key_event: KeyEvent) -> Option<Input> {
+fn on_key_event(key_event: KeyEvent, coop: bool) -> Option<Input> {
     // We want to skip release events because they are not the pressing of a button.
     if key_event.kind == event::KeyEventKind::Release {
         return None;
     }
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key_event.code {
+            KeyCode::Char(ch) => on_ctrl_letter_pressed(ch),
+            _ => None,
+        };
+    }
+    if key_event.modifiers.contains(KeyModifiers::ALT) {
+        return match key_event.code {
+            KeyCode::Char(ch) => on_alt_letter_pressed(ch),
+            _ => None,
+        };
+    }
     match key_event.code {
+        KeyCode::Char(ch) if coop => {
+            on_second_player_letter_pressed(ch).or_else(|| on_letter_pressed(ch))
+        }
         KeyCode::Char(ch) => on_letter_pressed(ch),
         KeyCode::Esc => Some(Input::CloseMenu),
+        KeyCode::Enter => Some(Input::Confirm),
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+            let dir = match key_event.code {
+                KeyCode::Up => Dir::Up,
+                KeyCode::Down => Dir::Down,
+                KeyCode::Left => Dir::Left,
+                _ => Dir::Right,
+            };
+            if key_event.modifiers.contains(KeyModifiers::SHIFT) {
+                Some(Input::BuildDir(dir))
+            } else {
+                Some(Input::DigDir(dir))
+            }
+        }
         _ => None,
         /* Other types of key-event codes:
          * `KeyCode::Backspace`
-         * `KeyCode::Enter`
-         * `KeyCode::Left`
-         * `KeyCode::Right`
-         * `KeyCode::Up`
-         * `KeyCode::Down`
          * `KeyCode::Home`
          * `KeyCode::End`
          * `KeyCode::PageUp`
@@ -112,359 +163,740 @@ fn on_key_event(key_event: KeyEvent) -> Option<Input> {
     }
 }
 
-fn get_input() -> Option<Input> {
+/// How often a direction that is held down moves the player again. See
+/// `TerminalPlatform::get_held_input`.
+const REPEAT_EVERY: Duration = Duration::from_millis(120);
+
+/// Where the items of the inventory were last drawn, one per row, to know
+/// which one the player clicks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ItemRows {
+    /// The first column of the rows, and the one after the last.
+    columns: (u16, u16),
+    top: u16,
+    len: u16,
+}
+
+impl ItemRows {
+    /// The rows of an inventory panel drawn by `draw_inventory`.
+    fn of_panel(state: &State, (left, top): (u16, u16), (width, height): (u16, u16)) -> Self {
+        let has_details = state.selected_item.is_some() && width - 2 >= MIN_WIDTH_FOR_DETAILS;
+        let right = if has_details { left + width / 2 } else { left + width - 1 };
+        // Items that don't fit above the bottom of the panel are not drawn.
+        let room = height.saturating_sub(ITEM_LIST_TOP + 1);
+        ItemRows {
+            columns: (left + 1, right),
+            top: top + ITEM_LIST_TOP,
+            len: (state.inventory.iter().count() as u16).min(room),
+        }
+    }
+
+    /// The place in `Inventory::iter` of the item drawn at a cell, if any.
+    fn item_at(self, column: u16, row: u16) -> Option<usize> {
+        let in_columns = (self.columns.0..self.columns.1).contains(&column);
+        let in_rows = (self.top..self.top + self.len).contains(&row);
+        (in_columns && in_rows).then_some((row - self.top) as usize)
+    }
+}
+
+/// Reads the next input. Clicks are turned into positions in the world using
+/// the viewport the world was last drawn in, if it was, or into items when
+/// they are on the inventory. When `coop` is on, some keys go to the second
+/// player.
+fn get_input(
+    viewport: Option<(Pos, Pos)>,
+    item_rows: Option<ItemRows>,
+    coop: bool,
+) -> io::Result<Option<Input>> {
     // TODO: Currently, this buffers input. So if you spam a key, it will keep
     // being registered as pressed even after you let go of the button (if there
     // is some lag). To avoid this, we want another thread reading input and
     // blocking, and sending them individually, but to a 1-length buffer.
-    let event = crossterm::event::read().expect("Failed to read input");
+    let event = crossterm::event::read()?;
+    Ok(event_to_input(event, viewport, item_rows, coop))
+}
+
+/// The input that an event is, if any. See `get_input`.
+fn event_to_input(
+    event: Event,
+    viewport: Option<(Pos, Pos)>,
+    item_rows: Option<ItemRows>,
+    coop: bool,
+) -> Option<Input> {
     match event {
-        Event::Key(key_event) => on_key_event(key_event),
+        Event::Key(key_event) => on_key_event(key_event, coop),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            ..
+        }) => match item_rows {
+            // The inventory is drawn over the world.
+            Some(item_rows) => item_rows.item_at(column, row).map(Input::ClickItem),
+            None => viewport
+                .and_then(|viewport| screen_to_world(viewport, column, row))
+                .map(Input::ClickTile),
+        },
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Right),
+            column,
+            row,
+            ..
+        }) if item_rows.is_none() => viewport
+            .and_then(|viewport| screen_to_world(viewport, column, row))
+            .map(Input::BuildAt),
         _ => None,
         /* Other types of events:
          *
          * `Event::FocusGained`
          * `Event::FocusLost`
-         * `Event::Mouse(mouse_event)`
          * `Event::Paste(_)`
          * `Event::Resize(_, _)`
          */
     }
 }
 
-/// The chars to draw on the screen for some game thing.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Chars {
-    left: char,
-    right: char,
-    bg: Color,
-    fg: Color,
+/// The same key with or without shift, so letting go of a key is noticed
+/// even if shift was let go of first.
+fn unshifted(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(ch) => KeyCode::Char(ch.to_ascii_lowercase()),
+        code => code,
+    }
 }
 
-impl Chars {
-    pub const fn new(left: char, right: char) -> Self {
-        Chars {
-            left,
-            right,
-            bg: Color::Reset,
-            fg: Color::Reset,
-        }
+/// The terminal color to draw a color of the game in.
+fn terminal_color(color: render::Color) -> Color {
+    match color {
+        render::Color::Reset => Color::Reset,
+        render::Color::Black => Color::Black,
+        render::Color::DarkGrey => Color::DarkGrey,
+        render::Color::Grey => Color::Grey,
+        render::Color::White => Color::White,
+        render::Color::Red => Color::Red,
+        render::Color::Green => Color::Green,
+        render::Color::Yellow => Color::Yellow,
+        render::Color::DarkYellow => Color::DarkYellow,
+        render::Color::Cyan => Color::Cyan,
     }
+}
 
-    pub const fn single(c: char) -> Self {
-        Self::new(c, c)
+/// Writes a whole frame to the terminal, from its top left corner. Any
+/// other output works too, like a `Vec<u8>` to see what would be written.
+pub fn write_frame(frame: &Frame, output: &mut impl io::Write) -> io::Result<()> {
+    let mut colors = None;
+    for (row, cells) in frame.rows().enumerate() {
+        queue!(output, cursor::MoveTo(0, row as u16))?;
+        for cell in cells {
+            // Only when they change, as most of the screen is in a few colors.
+            if colors != Some((cell.fg, cell.bg)) {
+                colors = Some((cell.fg, cell.bg));
+                let colors = Colors::new(terminal_color(cell.fg), terminal_color(cell.bg));
+                queue!(output, style::SetColors(colors))?;
+            }
+            queue!(output, Print(cell.ch))?;
+        }
     }
+    queue!(output, style::ResetColor)
+}
 
-    pub const fn with_fg(mut self, fg: Color) -> Self {
-        self.fg = fg;
-        self
+/// Asks for the command of the key that opens the command prompt (which
+/// gives an empty one). Other inputs, and commands of their own (like `y`'s),
+/// are already whole.
+fn finish_command<E>(
+    input: Option<Input>,
+    ask_for_text: impl FnOnce(&str) -> Result<Option<String>, E>,
+) -> Result<Option<Input>, E> {
+    match input {
+        Some(Input::Command(command)) if command.is_empty() => {
+            Ok(ask_for_text("Command:")?.map(Input::Command))
+        }
+        input => Ok(input),
     }
+}
 
-    pub const fn with_bg(mut self, bg: Color) -> Self {
-        self.bg = bg;
-        self
+/// Copies the text of the screen to the clipboard, the way it is drawn now
+/// (without the colors). Returns a message for the player, which says why if
+/// the screen or the clipboard can't be gotten to.
+fn copy_screen(state: &State) -> String {
+    let copied = terminal::size().map_err(|e| e.to_string()).and_then(|(w, h)| {
+        let text = screen_text(state, w, h);
+        let clipboard = arboard::Clipboard::new().and_then(|mut c| c.set_text(text));
+        clipboard.map_err(|e| e.to_string())
+    });
+    match copied {
+        Ok(()) => "Copied the screen to the clipboard.".to_string(),
+        Err(e) => format!("Could not copy the screen: {e}"),
     }
+}
 
-    pub fn write(self, output: &mut impl io::Write) -> io::Result<()> {
-        queue!(
-            output,
-            style::SetColors(Colors::new(self.fg, self.bg)),
-            Print(self.left),
-            Print(self.right),
-        )
-    }
+/// The text of the screen, `width` by `height`, as `copy_screen` copies it.
+fn screen_text(state: &State, width: u16, height: u16) -> String {
+    frame::render_frame(state, width, height, &RenderOptions { help: HELP }).text()
 }
 
-impl From<char> for Chars {
-    fn from(char: char) -> Self {
-        Self::single(char)
-    }
+/// Puts the terminal back the way it was, for when the game is stopped
+/// without a chance to call `cleanup` (or save). Errors are ignored, as
+/// there is nothing left to do about them.
+fn restore_terminal_now() {
+    let _ = terminal::disable_raw_mode();
+    #[cfg(unix)]
+    let _ = KeyboardEnhancement::TERMINAL.pop(&mut stdout());
+    let _ = execute!(stdout(), event::DisableMouseCapture, terminal::LeaveAlternateScreen);
 }
 
-impl From<[char; 2]> for Chars {
-    fn from(chars: [char; 2]) -> Self {
-        Self::new(chars[0], chars[1])
-    }
+/// What we ask terminals that support it to tell us about keys: when they
+/// are let go of (see `get_held_input`), and Esc apart from the start of
+/// other keys, so it isn't held back to see what comes after it.
+#[cfg(unix)]
+const KEYBOARD_FLAGS: event::KeyboardEnhancementFlags =
+    event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+        .union(event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES);
+
+/// Were `KEYBOARD_FLAGS` pushed to the terminal? See
+/// `KeyboardEnhancement::TERMINAL`.
+#[cfg(unix)]
+static KEYBOARD_FLAGS_PUSHED: AtomicBool = AtomicBool::new(false);
+
+/// Pushes `KEYBOARD_FLAGS` to a terminal that supports them, and pops them
+/// only if they were pushed, as popping flags that weren't leaves escape
+/// codes in some terminals after the game.
+#[cfg(unix)]
+struct KeyboardEnhancement<'a> {
+    pushed: &'a AtomicBool,
 }
 
-const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+#[cfg(unix)]
+impl KeyboardEnhancement<'static> {
+    /// For the terminal the game is in. Shared with `restore_terminal_now`,
+    /// which can't get to the platform.
+    const TERMINAL: Self = Self {
+        pushed: &KEYBOARD_FLAGS_PUSHED,
+    };
+}
 
-/// A tile get's drawn to two characters because most fonts are taller than
-/// they are wide.
-fn draw_tile(tile: Tile) -> Chars {
-    match tile {
-        Tile::WallFull => ['█', '█'].into(),
-        Tile::WallHalf => ['▓', '▓'].into(),
-        Tile::WallLow => ['▒', '▒'].into(),
-        Tile::Empty => [' ', ' '].into(),
-        Tile::Wood(n) => Chars::single(SHADES[n.min(3) as usize]).with_fg(Color::DarkYellow),
+#[cfg(unix)]
+impl KeyboardEnhancement<'_> {
+    /// Pushes the flags to `out` if the terminal is `supported`, and they
+    /// weren't already. Returns whether they are pushed.
+    fn push(&self, supported: bool, out: &mut impl io::Write) -> io::Result<bool> {
+        if supported && !self.pushed.load(Ordering::SeqCst) {
+            execute!(out, event::PushKeyboardEnhancementFlags(KEYBOARD_FLAGS))?;
+            self.pushed.store(true, Ordering::SeqCst);
+        }
+        Ok(self.pushed.load(Ordering::SeqCst))
+    }
+
+    /// Pops the flags from `out`, if they were pushed.
+    fn pop(&self, out: &mut impl io::Write) -> io::Result<()> {
+        if self.pushed.swap(false, Ordering::SeqCst) {
+            execute!(out, event::PopKeyboardEnhancementFlags)?;
+        }
+        Ok(())
     }
 }
 
-/// Player character
-fn player(dir: Dir) -> Chars {
-    Chars::from(match dir {
-        Dir::Up => ['▀', '▀'],
-        Dir::Down => ['▄', '▄'],
-        Dir::Left => ['█', ' '],
-        Dir::Right => [' ', '█'],
-    })
-    .with_fg(Color::White)
-    .with_bg(Color::DarkGrey)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalPlatform {
+    /// Where the world was last drawn, to know where the player clicks.
+    viewport: Option<(Pos, Pos)>,
+    /// Where the items were last drawn, if the inventory is open.
+    item_rows: Option<ItemRows>,
+    /// Is there a second player, who needs their own keys?
+    coop: bool,
+    /// When playing over the network, the other players have their own
+    /// keyboards, so `coop` is never on.
+    networked: bool,
+    /// Where the game is saved, and where exported maps go.
+    store: FileSaveStore,
+    /// Does the terminal tell us when keys are let go of? If so, held
+    /// directions are repeated by us. See `get_held_input`.
+    key_releases: bool,
+    /// The direction key being held down, and the input it is.
+    held: Option<(KeyCode, Input)>,
 }
 
-fn draw(state: &State, output: &mut impl io::Write, width: u16, height: u16) -> io::Result<()> {
-    let outer_width = width & !1 /* Ensure even */;
-    // let outer_height = height - 2 /* For living space for text below */;
-    let outer_height = height;
-    let inner_width = outer_width - 2 /* For the frame */;
-    let inner_height = outer_height - 2 /* For the frame */;
-    let rows = inner_height;
-    let cells_in_a_row = inner_width / 2;
-
-    queue!(output, style::ResetColor)?;
-
-    queue!(output, cursor::MoveTo(0, 0))?;
-    border::top_row(output, inner_width)?;
-
-    for row in 0..rows {
-        queue!(output, cursor::MoveTo(0, row + 1))?;
-        write!(output, "{}", border::L)?;
-        for col in 0..cells_in_a_row {
-            let pos = (
-                state.player_pos.0 + col as i32 - cells_in_a_row as i32 / 2,
-                state.player_pos.1 + row as i32 - rows as i32 / 2,
-            );
-            // TODO: this should just check against row and col, not the pos.
-            let chars = if pos == state.player_pos {
-                queue!(output, cursor::SavePosition,)?;
-                player(state.player_dir)
-            } else {
-                let tile = state.get_tile(pos);
-                draw_tile(tile)
-            };
-            chars.write(output)?;
+impl TerminalPlatform {
+    pub fn new(store: FileSaveStore) -> Self {
+        TerminalPlatform {
+            viewport: None,
+            item_rows: None,
+            coop: false,
+            networked: false,
+            store,
+            key_releases: false,
+            held: None,
         }
-        write!(output, "{}", border::R)?;
     }
 
-    queue!(output, cursor::MoveTo(0, rows + 1))?;
-    border::bottom_row(output, inner_width)?;
-
-    queue!(output, cursor::MoveTo(0, rows + 1))?;
-    write!(output, "XY: {} {}", state.player_pos.0, state.player_pos.1,)?;
-
-    queue!(
-        output,
-        style::ResetColor,
-        cursor::RestorePosition,
-        cursor::MoveDown(2),
-        cursor::MoveLeft((state.message.len() / 2) as u16),
-        Print(&state.message),
-    )?;
+    /// A terminal for drawing worlds that a server sends. See `net`.
+    pub(crate) fn for_network(store: FileSaveStore) -> Self {
+        TerminalPlatform {
+            networked: true,
+            ..Self::new(store)
+        }
+    }
 
-    match state.menu {
-        Menu::None => (),
-        Menu::Inventory => draw_inventory(
-            state,
-            output,
-            (width / 4, height / 4),
-            (width / 2, height / 2),
-        )?,
+    /// Like `get_input`, but a direction that is held down comes again every
+    /// `REPEAT_EVERY`, instead of as often as the terminal repeats keys, which
+    /// is different in every terminal. Only works when the terminal tells us
+    /// when keys are let go of.
+    fn get_held_input(&mut self) -> io::Result<Option<Input>> {
+        if let Some((_, input)) = &self.held
+            && !event::poll(REPEAT_EVERY)?
+        {
+            return Ok(Some(input.clone()));
+        }
+        let event = event::read()?;
+        if let Event::Key(key_event) = event {
+            let code = unshifted(key_event.code);
+            let is_held = self.held.as_ref().is_some_and(|(held, _)| *held == code);
+            match key_event.kind {
+                KeyEventKind::Release if is_held => self.held = None,
+                // We repeat it ourselves.
+                KeyEventKind::Repeat if is_held => return Ok(None),
+                KeyEventKind::Press | KeyEventKind::Repeat | KeyEventKind::Release => (),
+            }
+        }
+        let input = event_to_input(event.clone(), self.viewport, self.item_rows, self.coop);
+        if let Event::Key(KeyEvent { kind: KeyEventKind::Press, code, .. }) = event
+            && let Some(input @ Input::Dir(..)) = &input
+        {
+            self.held = Some((unshifted(code), input.clone()));
+        }
+        Ok(input)
     }
 
-    Ok(())
+    /// Exports a region of the map to a file in the data directory. Returns
+    /// the message to show the player.
+    fn run_export(&mut self, state: &State, args: &[&str]) -> Result<String, GameError> {
+        let Some((file_name, region)) = args.split_first() else {
+            return Ok(EXPORT_USAGE.to_string());
+        };
+        let path = self.store.dir().join(file_name);
+        let extension = path.extension().and_then(|e| e.to_str());
+        let region = if let Some(region) = parse_region(region) {
+            region
+        } else if !region.is_empty() {
+            return Ok(EXPORT_USAGE.to_string());
+        } else if extension == Some("txt") {
+            // Text is for pasting in chat, so just what's on screen.
+            let (w, h) = terminal::size()?;
+            viewport(state, w, h)
+        } else {
+            state.explored_area()
+        };
+        let ((left, top), (right, bottom)) = region;
+        // The corners are typed by the player, so they can be as far apart
+        // as an `i32` allows, which doesn't fit in one.
+        if right.abs_diff(left) >= MAX_EXPORT_SIDE || bottom.abs_diff(top) >= MAX_EXPORT_SIDE {
+            return Ok(format!("Cannot export more than {MAX_EXPORT_SIDE} tiles on each side."));
+        }
+        let bytes = match extension {
+            Some("png") => {
+                let mut png = io::Cursor::new(vec![]);
+                match map_image(state, region).write_to(&mut png, image::ImageFormat::Png) {
+                    Ok(()) => png.into_inner(),
+                    Err(e) => return Ok(format!("Could not export the map: {e}")),
+                }
+            }
+            Some("txt") => map_text(state, region).into_bytes(),
+            _ => return Ok("Can only export to .png or .txt files.".to_string()),
+        };
+        let result = match self.store.write_file(Path::new(file_name), &bytes) {
+            Ok(()) => Ok(()),
+            Err(SaveError::Io(_, e)) => Err(e),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(match result {
+            Ok(()) => format!("Exported the map to {}", path.display()),
+            Err(e) => format!("Could not export the map: {e}"),
+        })
+    }
 }
 
-fn draw_inventory(
-    state: &State,
-    output: &mut impl io::Write,
-    (left, top): (u16, u16),
-    (width, height): (u16, u16),
-) -> io::Result<()> {
-    let bottom = top + height - 1;
-    let inner_width = width - 2;
-
-    queue!(output, cursor::MoveTo(left, top))?;
-    border::top_row(output, inner_width)?;
-
-    // Clear the inside
-    for row in top + 1..bottom {
-        queue!(
-            output,
-            cursor::MoveTo(left, row),
-            Print(border::L),
-            Print(" ".repeat(inner_width as usize)),
-            Print(border::R),
-        )?;
-    }
-
-    let draw_player_at = (left + 3, top + 2);
-    queue!(output, cursor::MoveTo(draw_player_at.0, draw_player_at.1))?;
-    player(state.player_dir).write(output)?;
-    queue!(output, style::ResetColor)?;
-
-    queue!(output, cursor::MoveTo(left + 1, top + 4))?;
-    write!(output, "{}", "-".repeat(inner_width as usize))?;
-
-    for (i, (item, count)) in state.inventory.iter().enumerate() {
-        queue!(output, cursor::MoveTo(left + 6, top + 6 + i as u16))?;
-        let name = item.name();
-        let is_selected = Some(&item) == state.selected_item.as_ref();
-        let selected: Colors = Colors::new(Color::Black, Color::White);
-        if is_selected {
-            queue!(
-                output,
-                style::SetColors(selected),
-                // style::SetAttribute(Attribute::Underlined),
-            )?;
+impl Platform for TerminalPlatform {
+    type Error = GameError;
+
+    fn init(&mut self) -> Result<(), GameError> {
+        terminal::enable_raw_mode()?;
+        // Windows always tells us when keys are let go of. Other terminals
+        // only do if they support it and we ask.
+        self.key_releases = cfg!(windows);
+        #[cfg(unix)]
+        {
+            // Not knowing is the same as not supporting them: we just don't
+            // ask for more.
+            let supported = terminal::supports_keyboard_enhancement().unwrap_or(false);
+            self.key_releases = KeyboardEnhancement::TERMINAL.push(supported, &mut stdout())?;
+        }
+        execute!(stdout(), terminal::EnterAlternateScreen, event::EnableMouseCapture)?;
+        // In raw mode Ctrl-C is a key press (see `on_ctrl_letter_pressed`),
+        // but the game can still be stopped from the outside.
+        match ctrlc::set_handler(|| {
+            restore_terminal_now();
+            std::process::exit(130);
+        }) {
+            // Set by an earlier game, which does the same thing.
+            Ok(()) | Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+            Err(e) => Err(GameError::CtrlC(e)),
         }
-        let prefix = if is_selected { '>' } else { ' ' };
-        if count == 1 {
-            write!(output, "{prefix} {name}")?;
+    }
+
+    fn cleanup(&mut self) -> Result<(), GameError> {
+        // `init` might have stopped half way, so every step is tried even if
+        // one fails, to give back as much of the terminal as we can.
+        // `and` takes a result that was already made, so each step is done.
+        let res = terminal::disable_raw_mode();
+        #[cfg(unix)]
+        let res = res.and(KeyboardEnhancement::TERMINAL.pop(&mut stdout()));
+        let res = res.and(execute!(
+            stdout(),
+            event::DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        ));
+        Ok(res?)
+    }
+
+    fn ask_for_input(&mut self) -> Result<Option<Input>, GameError> {
+        let input = if self.key_releases {
+            self.get_held_input()?
         } else {
-            write!(output, "{prefix} {name} ✗ {count}")?;
+            get_input(self.viewport, self.item_rows, self.coop)?
+        };
+        finish_command(input, |prompt| self.ask_for_text(prompt))
+    }
+
+    fn poll_input(&mut self, timeout: Duration) -> Result<Option<Input>, GameError> {
+        if !event::poll(timeout)? {
+            return Ok(None);
         }
-        if is_selected {
-            queue!(
-                output,
-                style::ResetColor,
-                style::SetAttribute(Attribute::Reset)
-            )?;
+        self.ask_for_input()
+    }
+
+    fn draw(&mut self, state: &State) -> Result<(), GameError> {
+        let (w, h) = terminal::size()?;
+        let frame = frame::render_frame(state, w, h, &RenderOptions { help: HELP });
+        let mut out = vec![];
+        write_frame(&frame, &mut out)?;
+        stdout().write_all(&out)?;
+        stdout().flush()?;
+        if w < MIN_SIZE.0 || h < MIN_SIZE.1 {
+            self.viewport = None;
+            self.item_rows = None;
+            return Ok(());
+        }
+        // Clicking only works on tiles, which can't be told apart when
+        // zoomed out.
+        self.viewport = (state.zoom() == 1).then(|| viewport(state, w, h));
+        self.item_rows = (state.current_menu() == Menu::Inventory).then(|| {
+            let (corner, size) = menu_panel((w, h), state.menus().len() - 1);
+            ItemRows::of_panel(state, corner, size)
+        });
+        self.coop = !self.networked && !state.other_players().is_empty();
+        Ok(())
+    }
+
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, GameError> {
+        // Letting go of the key would be read here, not in `get_held_input`.
+        self.held = None;
+        let mut text = String::new();
+        loop {
+            let mut out = vec![];
+            let frame = frame::render_text_prompt(prompt, &text, terminal::size()?);
+            write_frame(&frame, &mut out)?;
+            stdout().write_all(&out)?;
+            stdout().flush()?;
+            let Event::Key(key_event) = event::read()? else {
+                continue;
+            };
+            if key_event.kind == event::KeyEventKind::Release {
+                continue;
+            }
+            match key_event.code {
+                KeyCode::Enter if text.trim().is_empty() => return Ok(None),
+                KeyCode::Enter => return Ok(Some(text)),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => _ = text.pop(),
+                KeyCode::Char(ch) => text.push(ch),
+                _ => (),
+            }
         }
     }
 
-    queue!(output, cursor::MoveTo(left, bottom))?;
-    border::bottom_row(output, inner_width)?;
+    fn run_command(&mut self, state: &State, command: &str) -> Result<Option<String>, GameError> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words.split_first() {
+            Some((&"export", args)) => Ok(Some(self.run_export(state, args)?)),
+            Some((&"copy", [])) => Ok(Some(copy_screen(state))),
+            _ => Ok(None),
+        }
+    }
+
+    fn save(&mut self, state: &State) -> Result<(), GameError> {
+        Ok(self.store.save_state(state)?)
+    }
 
-    Ok(())
+    fn load(&mut self) -> Result<LoadResult, GameError> {
+        Ok(self.store.load()?)
+    }
+
+    fn back_up_save(&mut self) -> Result<(), GameError> {
+        Ok(self.store.back_up()?)
+    }
+
+    fn notify(&mut self, event: GameEvent) -> Result<(), GameError> {
+        match event {
+            GameEvent::Damage | GameEvent::Pickup | GameEvent::Break => {
+                execute!(stdout(), Print(BELL))?;
+            }
+            // Building happens on every step while placing behind the
+            // player, and a bell every step would be too much.
+            GameEvent::Build => (),
+        }
+        Ok(())
+    }
+
+    fn confirm(&mut self, msg: &str) -> Result<bool, GameError> {
+        // Letting go of the key would be read here, not in `get_held_input`.
+        self.held = None;
+        let mut out = vec![];
+        write_frame(&frame::render_text_prompt(msg, "y/n", terminal::size()?), &mut out)?;
+        stdout().write_all(&out)?;
+        stdout().flush()?;
+        loop {
+            let Event::Key(key_event) = event::read()? else {
+                continue;
+            };
+            if key_event.kind == event::KeyEventKind::Release {
+                continue;
+            }
+            match key_event.code {
+                KeyCode::Char('y' | 'Y') => return Ok(true),
+                KeyCode::Char('n' | 'N') | KeyCode::Esc => return Ok(false),
+                _ => (),
+            }
+        }
+    }
 }
 
-/// TODO: Rename
-#[derive(Debug)]
-enum Error {
-    Ser(PathBuf, toml::ser::Error),
-    De(PathBuf, toml::de::Error),
+/// The most tiles on each side of an exported map.
+const MAX_EXPORT_SIDE: u32 = 1000;
+/// How many pixels wide and tall each tile is in an exported image.
+const PIXELS_PER_TILE: u32 = 4;
+const EXPORT_USAGE: &str = "Usage: export <file.png|file.txt> [left top right bottom]";
+
+/// Draws the tiles in a region of the map, including its corners, to an image.
+fn map_image(state: &State, ((left, top), (right, bottom)): (Pos, Pos)) -> image::RgbImage {
+    let width = right.abs_diff(left) + 1;
+    let height = bottom.abs_diff(top) + 1;
+    let half = PIXELS_PER_TILE / 2;
+    let tiles = state.tiles_in_rect((left, top), width, height);
+    image::RgbImage::from_fn(width * PIXELS_PER_TILE, height * PIXELS_PER_TILE, |x, y| {
+        let index = (y / PIXELS_PER_TILE * width + x / PIXELS_PER_TILE) as usize;
+        let side = (x % PIXELS_PER_TILE / half) as usize;
+        image::Rgb(draw_tile(tiles[index]).rgb()[side])
+    })
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::Ser(path, e) => write!(f, "Failed to serialize to {}: {}", path.display(), e),
-            Error::De(path, e) => write!(f, "Failed to deserialize from {}: {}", path.display(), e),
+/// Writes the tiles in a region of the map, including its corners, as text
+/// with one character per tile. The player is an `@`.
+fn map_text(state: &State, ((left, top), (right, bottom)): (Pos, Pos)) -> String {
+    let width = right.abs_diff(left) + 1;
+    let height = bottom.abs_diff(top) + 1;
+    let mut text = String::new();
+    for (pos, tile) in state.region((left, top), width, height) {
+        text.push(if pos == state.player_pos {
+            '@'
+        } else {
+            draw_tile(tile).single_char()
+        });
+        if pos.0 == right {
+            text.push('\n');
         }
     }
+    text
 }
 
-impl std::error::Error for Error {}
+fn parse_region(args: &[&str]) -> Option<(Pos, Pos)> {
+    let [left, top, right, bottom] = args else {
+        return None;
+    };
+    let [left, top, right, bottom] = [left, top, right, bottom].map(|arg| arg.parse::<i32>());
+    let (left, top, right, bottom) = (left.ok()?, top.ok()?, right.ok()?, bottom.ok()?);
+    Some(((left.min(right), top.min(bottom)), (left.max(right), top.max(bottom))))
+}
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct TerminalPlatform;
+/// Makes the terminal beep, or flash, depending on the terminal.
+const BELL: char = '\x07';
 
-impl TerminalPlatform {
-    pub const fn new() -> Self {
-        TerminalPlatform
+const HELP: &[&str] = &[
+    "Controls:",
+    "w/a/s/d or h/j/k/l - move",
+    "W/A/S/D or H/J/K/L - move without turning",
+    "Ctrl+w/a/s/d - run",
+    "Alt+w/a/s/d - look without moving or digging",
+    "arrows - dig that way, without turning (Shift - build)",
+    "b/B - build (or choose, in the inventory)",
+    "e/E - use",
+    "f/F - throw a rock",
+    "r/R - eat",
+    "y/Y - copy the screen, to share it",
+    "click - dig next to you, or walk there",
+    "right click - build next to you",
+    "p/P - place behind you while moving",
+    "m/M - show/hide the last messages",
+    "v/V - choose how broken to build walls and wood",
+    "i/I - open/close inventory",
+    "n/N - go to a waypoint (use a waystone to make one)",
+    "z/Z - zoom out, to look around",
+    "/ - type a command (like stats, fog, dig, lang es, difficulty hard or export map.txt)",
+    "Esc - close menu / pause",
+    "q or Ctrl+c - save and quit",
+    "Second player (/join): i/j/k/l, ; and '",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in for the prompt, for inputs that shouldn't ask for anything.
+    fn no_prompt(prompt: &str) -> io::Result<Option<String>> {
+        panic!("Asked for {prompt:?}")
     }
 
-    fn read<T: serde::de::DeserializeOwned>(
-        &mut self,
-        file_path: &Path,
-    ) -> Result<Option<T>, io::Error> {
-        let path = data_dir()?.join(file_path);
-        if !path.exists() {
-            return Ok(None); // File does not exist
-        }
-        let text = std::fs::read_to_string(&path)?;
-        toml::from_str(&text)
-            .map_err(|e| Error::De(path.clone(), e))
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    #[test]
+    fn y_copies_without_asking_for_a_command() {
+        let input = finish_command(on_letter_pressed('y'), no_prompt).unwrap();
+        assert_eq!(input, Some(Input::Command("copy".to_string())));
     }
 
-    fn write<T: serde::Serialize>(&mut self, file_path: &Path, value: T) -> Result<(), io::Error> {
-        let path = data_dir()?.join(file_path);
-        // TODO: the toml crate's pretty printer actually kind of sucks. I
-        // should implement my own and PR it.
-        let text = toml::to_string_pretty(&value)
-            .map_err(|e| Error::Ser(path.clone(), e))
-            .map_err(io::Error::other)?;
-        std::fs::write(&path, text)
+    #[test]
+    fn the_copied_screen_is_the_frame_without_colors() {
+        let mut state = State::new();
+        state.message = "Hello".to_string();
+        let text = screen_text(&state, 40, 12);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert!(lines.iter().all(|line| line.chars().count() == 40), "{text}");
+        assert!(text.contains("Hello"));
+        assert!(!text.contains('\x1b'));
     }
-}
 
-impl Platform for TerminalPlatform {
-    type Error = io::Error;
+    fn click(button: MouseButton, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(button),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
 
-    fn init(&mut self) -> io::Result<()> {
-        terminal::enable_raw_mode()?;
-        #[cfg(unix)]
-        queue!(
-            stdout(),
-            event::PushKeyboardEnhancementFlags(event::KeyboardEnhancementFlags::empty()),
-        );
-        execute!(stdout(), terminal::EnterAlternateScreen,)?;
-        Ok(())
+    #[test]
+    fn clicks_on_the_world_are_on_its_tiles() {
+        let viewport = Some(((10, 20), (30, 40)));
+        let input = |event| event_to_input(event, viewport, None, false);
+        assert_eq!(input(click(MouseButton::Left, 1, 1)), Some(Input::ClickTile((10, 20))));
+        assert_eq!(input(click(MouseButton::Left, 4, 2)), Some(Input::ClickTile((11, 21))));
+        assert_eq!(input(click(MouseButton::Right, 4, 2)), Some(Input::BuildAt((11, 21))));
+        // On the frame.
+        assert_eq!(input(click(MouseButton::Left, 0, 2)), None);
+        assert_eq!(input(click(MouseButton::Right, 4, 0)), None);
+        assert_eq!(input(click(MouseButton::Middle, 4, 2)), None);
     }
 
-    fn cleanup(&mut self) -> io::Result<()> {
-        terminal::disable_raw_mode()?;
-        #[cfg(unix)]
-        queue!(stdout(), event::PopKeyboardEnhancementFlags,);
-        execute!(stdout(), terminal::LeaveAlternateScreen,)?;
-        Ok(())
+    /// A platform that saves to a directory of its own, which isn't made yet.
+    fn platform_in_new_dir(name: &str) -> TerminalPlatform {
+        let dir = std::env::temp_dir().join(format!("minecraft-2d-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        TerminalPlatform::new(FileSaveStore::new(dir))
     }
 
-    fn ask_for_input(&mut self) -> io::Result<Option<Input>> {
-        Ok(get_input())
+    #[test]
+    fn exporting_too_much_is_refused() {
+        let mut platform = platform_in_new_dir("too-much");
+        let (min, max) = (i32::MIN.to_string(), i32::MAX.to_string());
+        let regions = [[min.as_str(), "0", max.as_str(), "0"], ["0", &min, "0", &max]];
+        for region in regions {
+            let args = [&["map.png"], &region[..]].concat();
+            let message = platform.run_export(&State::new(), &args).unwrap();
+            assert!(message.starts_with("Cannot export more"), "{message}");
+        }
+        assert!(!platform.store.dir().exists());
     }
 
-    fn draw(&mut self, state: &State) -> io::Result<()> {
-        queue!(
-            stdout(),
-            // terminal::Clear(terminal::ClearType::All),
-            cursor::MoveTo(0, 0),
-        )?;
+    #[test]
+    fn exporting_makes_the_directory() {
+        for file in ["map.png", "map.txt"] {
+            let mut platform = platform_in_new_dir(file);
+            let message = platform.run_export(&State::new(), &[file, "-2", "-2", "2", "2"]);
+            assert!(message.unwrap().starts_with("Exported"));
+            assert!(platform.store.dir().join(file).exists());
+            std::fs::remove_dir_all(platform.store.dir()).unwrap();
+        }
+    }
+
+    /// A terminal that is gone, and can't be written to.
+    #[cfg(unix)]
+    struct Gone;
+
+    #[cfg(unix)]
+    impl io::Write for Gone {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keyboard_flags_are_popped_once_after_a_push() {
+        let pushed = AtomicBool::new(false);
+        let keyboard = KeyboardEnhancement { pushed: &pushed };
         let mut out = vec![];
-        let (w, h) = terminal::size()?;
-        let (w, h) = (w as _, h as _);
-        draw(state, &mut out, w, h)?;
-        io::stdout().write_all(&out)?;
-        execute!(
-            stdout(),
-            style::ResetColor,
-            cursor::MoveTo(1, 1),
-            Print(HELP[0]),
-            cursor::MoveTo(1, 2),
-            Print(HELP[1]),
-            cursor::MoveTo(1, 3),
-            Print(HELP[2]),
-            cursor::MoveTo(1, 4),
-            Print(HELP[3]),
-            cursor::MoveTo(1, 5),
-            Print(HELP[4]),
-            cursor::MoveTo(1, 6),
-            Print(HELP[5]),
-            cursor::MoveTo(1, 7),
-            Print(HELP[6]),
-        )?;
-        Ok(())
+        assert!(keyboard.push(true, &mut out).unwrap());
+        assert!(!out.is_empty());
+        out.clear();
+        assert!(keyboard.push(true, &mut out).unwrap());
+        assert!(out.is_empty());
+        keyboard.pop(&mut out).unwrap();
+        assert!(!out.is_empty());
+        out.clear();
+        keyboard.pop(&mut out).unwrap();
+        assert!(out.is_empty());
     }
 
-    fn save(&mut self, state: &State) -> io::Result<()> {
-        // TODO: Make a backup.
-        self.write(Path::new("save"), state)
+    #[cfg(unix)]
+    #[test]
+    fn keyboard_flags_are_not_popped_if_not_supported() {
+        let pushed = AtomicBool::new(false);
+        let keyboard = KeyboardEnhancement { pushed: &pushed };
+        let mut out = vec![];
+        assert!(!keyboard.push(false, &mut out).unwrap());
+        keyboard.pop(&mut out).unwrap();
+        assert!(out.is_empty());
     }
 
-    fn load(&mut self) -> io::Result<Option<State>> {
-        self.read(Path::new("save"))
+    #[cfg(unix)]
+    #[test]
+    fn keyboard_flags_are_not_popped_after_a_failed_push() {
+        let pushed = AtomicBool::new(false);
+        let keyboard = KeyboardEnhancement { pushed: &pushed };
+        assert!(keyboard.push(true, &mut Gone).is_err());
+        let mut out = vec![];
+        keyboard.pop(&mut out).unwrap();
+        assert!(out.is_empty());
     }
-}
 
-const HELP: &[&str] = &[
-    "Controls:",
-    "w/a/s/d - move",
-    "W/A/S/D - move without turning",
-    "b/B - build",
-    "i/I - open inventory",
-    "Esc - close menu",
-    "q - quit",
-];
+    #[test]
+    fn slash_asks_for_a_command() {
+        let input = finish_command(on_letter_pressed('/'), |prompt| {
+            assert_eq!(prompt, "Command:");
+            io::Result::Ok(Some("stats".to_string()))
+        });
+        assert_eq!(input.unwrap(), Some(Input::Command("stats".to_string())));
+    }
+}
@@ -0,0 +1,318 @@
+/// Building, and placing items behind the player.
+mod building;
+/// Playing together on one keyboard.
+mod coop;
+/// Whole frames, drawn the same way every time.
+mod drawing;
+/// Effects drawn where something just happened.
+mod effects;
+/// Sand, and other tiles that fall.
+mod falling;
+/// Fog of war, and what the player explored.
+mod fog;
+/// Tests of `start_game`.
+mod game_loop;
+/// How worlds are generated.
+mod generation;
+/// Getting hungry, and eating.
+mod hunger;
+/// Torches and light levels.
+mod light;
+/// What every input does in every menu.
+mod menus;
+/// What the player is told about what is ahead of them.
+mod looking;
+/// Running, sliding and other ways of getting around.
+mod moving;
+/// Things that used to panic, and now only tell the player what went wrong.
+mod recovering;
+/// A platform for driving `start_game` in tests.
+mod scripted;
+/// Which item is selected, as items come and go.
+mod selecting;
+/// Throwing rocks.
+mod throwing;
+/// Walking somewhere by itself.
+mod walking;
+/// Waystones, and going back to them.
+mod waypoints;
+/// What is known about a world besides what is in it, like how hard it is.
+mod world_meta;
+
+use crate::inventory::DEFAULT_CAPACITY;
+use crate::items::WoodKind;
+use crate::tiles::WOOD_MAX;
+use crate::{Achievement, Dir, Input, IsShift, Item, Menu, Pos, State, StateBuilder, Tile, Trade};
+
+/// A scene with nothing around the player (at (0, 0), facing down), so the
+/// generated world doesn't get in the way.
+fn open_scene() -> StateBuilder {
+    let mut builder = StateBuilder::new();
+    for y in -3..=3 {
+        for x in -3..=3 {
+            builder = builder.with_tile((x, y), Tile::Empty);
+        }
+    }
+    builder
+}
+
+/// A corridor going right from the player (at (0, 0)), `length` tiles long,
+/// with walls above and below it and at its end.
+fn corridor(length: i32) -> StateBuilder {
+    let mut builder = StateBuilder::new().facing(Dir::Right);
+    for x in -1..=length + 1 {
+        builder = builder
+            .with_tile((x, -1), Tile::WallFull)
+            .with_tile((x, 0), Tile::Empty)
+            .with_tile((x, 1), Tile::WallFull);
+    }
+    builder.with_tile((length + 1, 0), Tile::WallFull)
+}
+
+fn press(state: &mut State, dir: Dir) {
+    state.on_input(Input::Dir(dir, IsShift::No));
+}
+
+#[test]
+fn moving_into_empty_moves_the_player() {
+    let mut state = open_scene().facing(Dir::Right).build();
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (1, 0));
+}
+
+#[test]
+fn a_new_direction_only_turns() {
+    let mut state = open_scene().build();
+    press(&mut state, Dir::Left);
+    assert_eq!(state.player_pos(), (0, 0));
+    assert_eq!(state.player_dir, Dir::Left);
+    press(&mut state, Dir::Left);
+    assert_eq!(state.player_pos(), (-1, 0));
+}
+
+#[test]
+fn digging_a_wall_goes_through_every_stage() {
+    let wall: Pos = (1, 0);
+    let mut state = open_scene().facing(Dir::Right).with_tile(wall, Tile::WallFull).build();
+    for stage in [Tile::WallHalf, Tile::WallLow, Tile::Empty] {
+        assert_eq!(state.inventory().count_of(&Item::Wall), 0);
+        press(&mut state, Dir::Right);
+        assert_eq!(state.get_tile(wall), stage);
+    }
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+    assert_eq!(state.player_pos(), (0, 0));
+}
+
+#[test]
+fn only_digging_a_wall_all_the_way_counts_as_breaking_it() {
+    let wall: Pos = (1, 0);
+    let mut state = open_scene().facing(Dir::Right).with_tile(wall, Tile::WallFull).build();
+    for broken in [0, 0, 1] {
+        press(&mut state, Dir::Right);
+        assert_eq!(state.stats().tiles_broken, broken);
+    }
+    press(&mut state, Dir::Right);
+    assert_eq!(state.stats().steps_taken, 1);
+    assert_eq!(state.stats().ticks_survived, 4);
+}
+
+#[test]
+fn digging_with_a_full_inventory_drops_what_was_dug() {
+    let wall: Pos = (1, 0);
+    let mut state = open_scene()
+        .facing(Dir::Right)
+        .with_tile(wall, Tile::WallLow)
+        .with_item(Item::Compass, DEFAULT_CAPACITY)
+        .build();
+    // So the message is not about the achievement.
+    state.achievements.insert(Achievement::FirstBreak);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.get_tile(wall), Tile::Empty);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 0);
+    assert!(state.drops_at(wall).contains(&Item::Wall));
+    assert_eq!(state.message, "Your inventory is full, so the wall fell on the ground.");
+    // It can be picked up once there is room.
+    state.inventory.remove(&Item::Compass).unwrap();
+    press(&mut state, Dir::Up);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+}
+
+#[test]
+fn hits_on_a_tile_add_up_until_it_breaks() {
+    let waystone: Pos = (0, 1);
+    let mut state = open_scene().with_tile(waystone, Tile::Waystone).build();
+    assert_eq!(Tile::Waystone.hits_to_break(None, &state.rules()), 3);
+    for hits in [1, 2] {
+        press(&mut state, Dir::Down);
+        assert_eq!(state.breaking(), Some((waystone, hits)));
+        assert_eq!(state.get_tile(waystone), Tile::Waystone);
+    }
+    press(&mut state, Dir::Down);
+    assert_eq!(state.breaking(), None);
+    assert_eq!(state.get_tile(waystone), Tile::Empty);
+    assert_eq!(state.inventory().count_of(&Item::Waystone), 1);
+}
+
+#[test]
+fn hitting_another_tile_starts_over() {
+    let mut state = open_scene()
+        .with_tile((0, 1), Tile::Waystone)
+        .with_tile((1, 0), Tile::Waystone)
+        .build();
+    press(&mut state, Dir::Down);
+    press(&mut state, Dir::Down);
+    state.player_dir = Dir::Right;
+    press(&mut state, Dir::Right);
+    assert_eq!(state.breaking(), Some(((1, 0), 1)));
+    state.player_dir = Dir::Down;
+    press(&mut state, Dir::Down);
+    assert_eq!(state.breaking(), Some(((0, 1), 1)));
+}
+
+#[test]
+fn walking_away_starts_over() {
+    let mut state = open_scene().with_tile((0, 1), Tile::Waystone).build();
+    press(&mut state, Dir::Down);
+    state.on_input(Input::Dir(Dir::Left, IsShift::Yes));
+    assert_eq!(state.player_pos(), (-1, 0));
+    assert_eq!(state.breaking(), None);
+}
+
+#[test]
+fn no_item_breaks_tiles_faster_yet() {
+    let rules = State::new().rules();
+    for tile in Tile::ALL {
+        let by_hand = tile.hits_to_break(None, &rules);
+        for item in Item::ALL {
+            assert_eq!(tile.hits_to_break(Some(item), &rules), by_hand, "{tile:?} with {item:?}");
+        }
+    }
+}
+
+#[test]
+fn building_uses_up_the_selected_item() {
+    let mut state = open_scene().with_item(Item::Wall, 2).build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::Build);
+    assert_eq!(state.get_tile((0, 1)), Tile::WallFull);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+    assert_eq!(state.stats().tiles_placed, 1);
+}
+
+#[test]
+fn building_on_a_tile_says_it_cannot() {
+    let mut state = open_scene().with_tile((0, 1), Tile::WallLow).with_item(Item::Wall, 1).build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::Build);
+    assert_eq!(state.message, "You cannot build on existing tiles.");
+    assert_eq!(state.get_tile((0, 1)), Tile::WallLow);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+}
+
+#[test]
+fn using_every_tile_does_what_it_says() {
+    for &tile in Tile::ALL {
+        let mut state = open_scene().build();
+        // Away from where the player starts, so setting the spawn is seen.
+        state.player_pos = (1, 0);
+        state.set_tile((1, 1), tile);
+        state.on_input(Input::Interact);
+        let name = tile.name(state.language);
+        match tile {
+            Tile::Bed => {
+                assert_eq!(state.spawn_point, (1, 0));
+                assert_eq!(state.message, "You will now respawn next to this bed.");
+            }
+            Tile::Waystone => assert_eq!(state.waypoints.get("Waypoint 1"), Some(&(1, 1))),
+            Tile::Trader => {
+                assert_eq!(state.current_menu(), Menu::Trade { pos: (1, 1), offer: 0 })
+            }
+            Tile::Empty => assert_eq!(state.message, "There is nothing to use there."),
+            _ => assert_eq!(state.message, format!("You cannot use a {name}."), "{tile:?}"),
+        }
+        if tile != Tile::Bed {
+            assert_eq!(state.spawn_point, (0, 0), "{tile:?}");
+        }
+        if tile != Tile::Waystone {
+            assert!(state.waypoints.is_empty(), "{tile:?}");
+        }
+        if tile != Tile::Trader {
+            assert_eq!(state.current_menu(), Menu::None, "{tile:?}");
+        }
+    }
+}
+
+#[test]
+fn regen_only_takes_four_numbers() {
+    let mut state = open_scene().with_tile((1, 1), Tile::Torch).build();
+    state.set_creative(true);
+    let generated = state.generate_tile((1, 1));
+    assert_ne!(generated, Tile::Torch);
+    for args in ["0 0 x 1 1", "0 0 1", "0 0 1 1 1", ""] {
+        state.on_input(Input::Command(format!("regen {args}")));
+        assert_eq!(state.message, "Usage: regen <x1> <y1> <x2> <y2>", "{args:?}");
+        assert_eq!(state.get_tile((1, 1)), Tile::Torch, "{args:?}");
+    }
+    state.on_input(Input::Command("regen 0 0 1 1".to_string()));
+    assert_eq!(state.message, "Regenerated 4 tiles.");
+    assert_eq!(state.get_tile((1, 1)), generated);
+}
+
+/// A scene with a trader below the player, and the player's inventory
+/// holding `have` of what its first offer costs.
+fn trader_scene(have: usize) -> (State, Trade) {
+    let trader: Pos = (0, 1);
+    let mut state = open_scene().with_tile(trader, Tile::Trader).build();
+    let trade = state.trade_offers(trader)[0].clone();
+    for _ in 0..have {
+        state.inventory.insert(trade.price.0.clone()).unwrap();
+    }
+    state.on_input(Input::Interact);
+    assert_eq!(state.current_menu(), Menu::Trade { pos: trader, offer: 0 });
+    (state, trade)
+}
+
+#[test]
+fn trading_takes_the_price_and_gives_the_goods() {
+    let (mut state, trade) = trader_scene(40);
+    let ((price, cost), (goods, count)) = (&trade.price, &trade.goods);
+    state.on_input(Input::Confirm);
+    assert_eq!(state.inventory().count_of(price), 40 - cost);
+    assert_eq!(state.inventory().count_of(goods), *count);
+    assert_eq!(state.stats().items_crafted, *count as u64);
+    assert!(state.has_achievement(Achievement::FirstCraft));
+}
+
+#[test]
+fn trading_without_enough_takes_nothing() {
+    let (mut state, trade) = trader_scene(1);
+    let (price, cost) = &trade.price;
+    state.on_input(Input::Confirm);
+    assert!(state.message.starts_with(&format!("You need {cost}")));
+    assert_eq!(state.inventory().count_of(price), 1);
+    assert_eq!(state.inventory().count_of(&trade.goods.0), 0);
+    assert_eq!(state.stats().items_crafted, 0);
+    assert!(!state.has_achievement(Achievement::FirstCraft));
+}
+
+#[test]
+fn only_tiles_that_change_are_ticked() {
+    let mut state = open_scene().with_tile((1, 1), Tile::Sapling(WoodKind::Oak)).build();
+    assert!(state.ticking.contains(&(1, 1)));
+    state.set_tile((2, 2), Tile::Bush(false));
+    assert_eq!(state.ticking.len(), 2);
+    // Berries grew back, so the bush is done changing.
+    state.set_tile((2, 2), Tile::Bush(true));
+    state.set_tile((1, 1), Tile::Empty);
+    assert!(state.ticking.is_empty());
+}
+
+#[test]
+fn saplings_grow_into_logs() {
+    let mut state = open_scene().with_tile((1, 1), Tile::Sapling(WoodKind::Oak)).build();
+    for _ in 0..2000 {
+        state.on_input(Input::Look(Dir::Down));
+    }
+    assert_eq!(state.get_tile((1, 1)), Tile::Wood(WoodKind::Oak, WOOD_MAX));
+    assert!(state.ticking.is_empty());
+}
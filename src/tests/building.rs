@@ -0,0 +1,131 @@
+use super::{open_scene, press};
+use crate::{Achievement, Dir, Input, Item, State, StateBuilder, Tile};
+
+/// Builds the selected item below the player. The achievement for building is
+/// unlocked already, so the message is about what was built.
+fn build_below(state: &mut State) {
+    state.achievements.insert(Achievement::FirstBuild);
+    state.on_input(Input::BuildDir(Dir::Down));
+}
+
+#[test]
+fn a_bridge_of_five_walls_takes_five_steps() {
+    let mut builder = StateBuilder::new().facing(Dir::Right).with_item(Item::Wall, 5);
+    for x in 0..=6 {
+        builder = builder.with_tile((x, 0), Tile::Empty);
+    }
+    let mut state = builder.build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::ToggleAutoPlace);
+    for _ in 0..5 {
+        press(&mut state, Dir::Right);
+    }
+    assert_eq!(state.player_pos(), (5, 0));
+    for x in 0..5 {
+        assert_eq!(state.get_tile((x, 0)), Tile::WallFull, "At x = {x}");
+    }
+    assert_eq!(state.inventory().count_of(&Item::Wall), 0);
+    assert_eq!(state.message, "You ran out of wall, so you stopped placing it.");
+    // Nothing is left to place.
+    press(&mut state, Dir::Right);
+    assert_eq!(state.get_tile((5, 0)), Tile::Empty);
+}
+
+#[test]
+fn building_says_how_many_are_left() {
+    let mut state = open_scene().with_item(Item::Wall, 3).build();
+    state.selected_item = Some(Item::Wall);
+    build_below(&mut state);
+    assert_eq!(state.message, "Placed wall (2 left)");
+}
+
+#[test]
+fn building_the_last_one_says_it_ran_out_and_selects_another() {
+    let mut state = open_scene().with_item(Item::Wall, 1).with_item(Item::Torch, 1).build();
+    state.selected_item = Some(Item::Wall);
+    build_below(&mut state);
+    assert_eq!(state.message, "Placed wall (0 left) You are out of wall!");
+    assert_eq!(state.selected_item, Some(Item::Torch));
+}
+
+#[test]
+fn building_with_none_left_says_it_ran_out() {
+    let mut state = open_scene().build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::BuildDir(Dir::Down));
+    assert_eq!(state.message, "You are out of wall!");
+    assert_eq!(state.get_tile((0, 1)), Tile::Empty);
+}
+
+#[test]
+fn only_passable_tiles_are_built_on() {
+    for &tile in Tile::ALL {
+        let mut state = open_scene().with_tile((0, 1), tile).with_item(Item::Torch, 1).build();
+        state.selected_item = Some(Item::Torch);
+        build_below(&mut state);
+        let built = state.inventory().count_of(&Item::Torch) == 0;
+        assert_eq!(built, tile.is_passable(), "{tile:?}");
+    }
+}
+
+#[test]
+fn digging_behind_does_not_turn() {
+    let mut state = open_scene().facing(Dir::Right).with_tile((-1, 0), Tile::WallLow).build();
+    state.on_input(Input::DigDir(Dir::Left));
+    assert_eq!(state.get_tile((-1, 0)), Tile::Empty);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+    assert_eq!(state.player_dir, Dir::Right);
+    assert_eq!(state.player_pos(), (0, 0));
+}
+
+#[test]
+fn building_to_the_side_does_not_turn() {
+    let mut state = open_scene().facing(Dir::Right).with_item(Item::Wall, 2).build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::BuildDir(Dir::Up));
+    assert_eq!(state.get_tile((0, -1)), Tile::WallFull);
+    assert_eq!(state.player_dir, Dir::Right);
+    // Building where the player faces still works too.
+    state.on_input(Input::Build);
+    assert_eq!(state.get_tile((1, 0)), Tile::WallFull);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 0);
+}
+
+#[test]
+fn cycling_goes_through_every_variant_and_back() {
+    let mut state = open_scene().with_item(Item::Wall, 1).with_item(Item::Torch, 1).build();
+    state.selected_item = Some(Item::Wall);
+    for tile in [Tile::WallHalf, Tile::WallLow, Tile::WallFull] {
+        state.on_input(Input::CycleVariant);
+        assert_eq!(state.tile_to_build(&Item::Wall), Some(tile));
+    }
+    state.selected_item = Some(Item::Torch);
+    state.on_input(Input::CycleVariant);
+    assert_eq!(state.message, "A torch can only be built one way.");
+    assert_eq!(state.tile_to_build(&Item::Torch), Some(Tile::Torch));
+    // Past the last variant is the last one.
+    assert_eq!(Item::Wall.to_tile_with_variant(100), Some(Tile::WallLow));
+}
+
+#[test]
+fn every_variant_breaks_back_into_one_item() {
+    for item in Item::ALL.iter().filter(|item| item.variants() > 1) {
+        for variant in 0..item.variants() {
+            let mut state = open_scene().with_item(item.clone(), 1).build();
+            state.selected_item = Some(item.clone());
+            for _ in 0..variant {
+                state.on_input(Input::CycleVariant);
+            }
+            build_below(&mut state);
+            assert_eq!(state.get_tile((0, 1)), item.to_tile_with_variant(variant).unwrap());
+            assert_eq!(state.inventory().count_of(item), 0);
+            for _ in 0..20 {
+                if state.get_tile((0, 1)) != Tile::Empty {
+                    press(&mut state, Dir::Down);
+                }
+            }
+            assert_eq!(state.get_tile((0, 1)), Tile::Empty, "{item:?} {variant}");
+            assert_eq!(state.inventory().count_of(item), 1, "{item:?} {variant}");
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use super::open_scene;
+use crate::{Dir, Input, IsShift, Item, Player, State, Tile};
+
+/// Gives an input to the second player.
+fn as_second(state: &mut State, input: Input) {
+    state.on_input(Input::ForPlayer(1, Box::new(input)));
+}
+
+#[test]
+fn each_player_has_their_own_inventory() {
+    let mut state = open_scene().with_tile((3, 0), Tile::WallLow).with_item(Item::Wall, 2).build();
+    state.other_players.push(Player::new((2, 0)));
+    as_second(&mut state, Input::DigDir(Dir::Right));
+    assert_eq!(state.other_players()[0].inventory.count_of(&Item::Wall), 1);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 2);
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::BuildDir(Dir::Down));
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+    assert_eq!(state.other_players()[0].inventory.count_of(&Item::Wall), 1);
+}
+
+#[test]
+fn players_block_each_other() {
+    let mut state = open_scene().facing(Dir::Right).build();
+    state.other_players.push(Player::new((1, 0)));
+    state.on_input(Input::Dir(Dir::Right, IsShift::Yes));
+    assert_eq!(state.player_pos(), (0, 0));
+    as_second(&mut state, Input::Dir(Dir::Left, IsShift::Yes));
+    assert_eq!(state.other_players()[0].pos, (1, 0));
+    // But they can still walk away from each other.
+    as_second(&mut state, Input::Dir(Dir::Down, IsShift::Yes));
+    assert_eq!(state.other_players()[0].pos, (1, 1));
+    state.on_input(Input::Dir(Dir::Right, IsShift::Yes));
+    assert_eq!(state.player_pos(), (1, 0));
+}
+
+#[test]
+fn the_second_player_is_saved() {
+    let mut state = open_scene().build();
+    state.other_players.push(Player::new((2, 0)));
+    state.other_players[0].inventory.insert(Item::Torch).unwrap();
+    let text = toml::to_string(&state).unwrap();
+    let loaded: State = toml::from_str(&text).unwrap();
+    assert_eq!(loaded.other_players()[0].pos, (2, 0));
+    assert_eq!(loaded.other_players()[0].inventory.count_of(&Item::Torch), 1);
+}
@@ -0,0 +1,110 @@
+use super::open_scene;
+use crate::{Input, Item, RenderOptions, State, Tile, render_frame};
+
+/// A few things around the player, and a message.
+fn scene() -> State {
+    let mut state = open_scene()
+        .with_tile((1, 0), Tile::WallFull)
+        .with_tile((-2, 1), Tile::Torch)
+        .with_tile((0, 2), Tile::Trader)
+        .with_item(Item::Wall, 3)
+        .with_item(Item::Torch, 1)
+        .build();
+    state.tick();
+    state.message = "Hello!".to_string();
+    state
+}
+
+/// Draws `state`, and checks that it looks like `expected`, row by row.
+fn check(state: &State, (width, height): (u16, u16), expected: &[&str]) {
+    let text = render_frame(state, width, height, &RenderOptions::default()).text();
+    assert_eq!(text.lines().collect::<Vec<_>>(), expected, "\n{text}");
+}
+
+#[test]
+fn a_small_screen() {
+    let expected = [
+        "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░┃",
+        "┃▒▒▒▒░░░░              ░░░░░░┃",
+        "┃██████▒▒              ░░░░░░┃",
+        "┃████████              ░░░░  ┃",
+        "┃████████      ▄▄██    ░░    ┃",
+        "┃▒▒██████  ¡                 ┃",
+        "┃░░██████   Hello!     ※     ┃",
+        "┃░░░░▓▓██                    ┃",
+        "┃░░░░░░░░░░░░░░░░            ┃",
+        "XY: 0 0 Day Food: ●●●●●●●●●●━┛",
+    ];
+    check(&scene(), (30, 12), &expected);
+}
+
+#[test]
+fn a_bigger_screen() {
+    let expected = [
+        "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░    ┃",
+        "┃░░░░░░▒▒▒▒░░░░              ░░░░░░░░      ┃",
+        "┃░░░░████████▒▒              ░░░░░░        ┃",
+        "┃░░░░▓▓████████              ░░░░          ┃",
+        "┃░░░░░░████████      ▄▄██    ░░            ┃",
+        "┃░░░░░░▒▒██████  ¡                         ┃",
+        "┃░░░░░░░░██████   Hello!     ※             ┃",
+        "┃░░░░░░░░░░▓▓██                          ██┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░                ████┃",
+        "┃░░░░░░░░░░░░░░░░░░░░                  ████┃",
+        "┃░░░░░░░░░░░░░░░░░░    ※     ※         ████┃",
+        "XY: 0 0 Day Food: ●●●●●●●●●●━━━━━━━━━━━━━━━┛",
+    ];
+    check(&scene(), (44, 16), &expected);
+}
+
+#[test]
+fn the_inventory() {
+    let mut state = scene();
+    state.on_input(Input::OpenInventory);
+    state.selected_item = Some(Item::Torch);
+    let expected = [
+        "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
+        "┃████░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░┃",
+        "┃████░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ┃",
+        "┃██░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░▒▒    ┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░      ┃",
+        "┃░░░░░░░░░░░░░░┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓░░░░░░        ┃",
+        "┃░░░░░░░░░░░░░░┃                            ┃░░░░          ┃",
+        "┃░░░░░░░░░░░░░░┃  ▄▄            2/20 slots  ┃░░            ┃",
+        "┃░░░░░░░░░░░░░░┃                            ┃              ┃",
+        "┃░░░░░░░░░░░░██┃----------------------------┃              ┃",
+        "┃░░░░░░░░░░░░▓▓┃                            ┃              ┃",
+        "┃░░░░░░░░░░░░░░┃       wall ✗ 3             ┃              ┃",
+        "┃░░░░░░░░░░░░░░┃     > torch                ┃              ┃",
+        "┃░░░░░░░░░░░░░░┃                            ┃            ▓▓┃",
+        "┃░░░░░░░░░░░░░░┃----------------------------┃    ██      ██┃",
+        "┃░░░░░░░░░░░░░░┃ Lights up the area around  ┃  ████    ████┃",
+        "┃░░░░░░░░░░░░░░┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛  ████    ████┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░░░    ※     ※         ████    ████┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░░░                        ██    ████┃",
+        "┃░░░░░░░░░░░░░░░░░░░░░░                                ██▓▓┃",
+        "┃░░░░░░░░░░░░░░░░░░░░                                      ┃",
+        "┃░░░░░░░░░░░░░░░░░░            ☻                           ┃",
+        "XY: 0 0 Day Food: ●●●●●●●●●●━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛",
+    ];
+    check(&state, (60, 24), &expected);
+}
+
+#[test]
+fn a_short_inventory_is_cut_at_its_bottom() {
+    let mut state = scene();
+    state.on_input(Input::OpenInventory);
+    let frame = render_frame(&state, 60, 16, &RenderOptions::default());
+    let rows: Vec<String> = frame.text().lines().map(String::from).collect();
+    // The panel ends at row 11, and the second item would have been there.
+    assert!(rows[11].contains("┗━━━"));
+    assert!(rows[10].contains("wall"));
+    assert!(!frame.text().contains("torch"));
+}
@@ -0,0 +1,46 @@
+use super::{open_scene, press};
+use crate::render::Color;
+use crate::{Dir, RenderOptions, State, Tile, render_frame};
+
+/// The player facing a full wall on their right.
+fn by_a_wall() -> State {
+    open_scene().facing(Dir::Right).with_tile((1, 0), Tile::WallFull).build()
+}
+
+fn drawn(state: &State) -> String {
+    render_frame(state, 30, 12, &RenderOptions::default()).text()
+}
+
+#[test]
+fn a_hit_is_shown_for_a_few_ticks() {
+    let mut state = by_a_wall();
+    press(&mut state, Dir::Right);
+    // The tick of the input already counts.
+    let effects: Vec<_> = state.effects().iter().map(|e| (e.pos, e.glyph, e.ttl)).collect();
+    assert_eq!(effects, [((1, 0), '*', 2)]);
+    state.tick();
+    assert_eq!(state.effects()[0].ttl, 1);
+    state.tick();
+    assert!(state.effects().is_empty());
+}
+
+#[test]
+fn an_effect_is_drawn_until_it_is_gone() {
+    let mut state = by_a_wall();
+    assert!(!drawn(&state).contains('*'));
+    press(&mut state, Dir::Right);
+    assert!(drawn(&state).contains('*'));
+    state.tick();
+    state.tick();
+    assert!(!drawn(&state).contains('*'));
+}
+
+#[test]
+fn the_oldest_effects_go_when_there_are_too_many() {
+    let mut state = by_a_wall();
+    for x in 0..20 {
+        state.add_effect((x, 5), '*', Color::Yellow);
+    }
+    let xs: Vec<i32> = state.effects().iter().map(|e| e.pos.0).collect();
+    assert_eq!(xs, (4..20).collect::<Vec<_>>());
+}
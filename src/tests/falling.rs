@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+use super::{open_scene, press};
+use crate::{Dir, Pos, State, Tile};
+
+/// The tiles from `top` down to `bottom`, at `x`.
+fn column(state: &State, x: i32, top: i32, bottom: i32) -> Vec<Tile> {
+    (top..=bottom).map(|y| state.get_tile((x, y))).collect()
+}
+
+#[test]
+fn a_column_of_sand_falls_together() {
+    use Tile::{Empty, Sand, WallFull};
+    let mut state = open_scene()
+        .with_tile((2, -3), Sand)
+        .with_tile((2, -2), Sand)
+        .with_tile((2, -1), Sand)
+        .with_tile((2, 2), WallFull)
+        .build();
+    state.tick();
+    // The whole column fell a tile, not just the bottom of it.
+    assert_eq!(column(&state, 2, -3, 2), [Empty, Sand, Sand, Sand, Empty, WallFull]);
+    state.tick();
+    assert_eq!(column(&state, 2, -3, 2), [Empty, Empty, Sand, Sand, Sand, WallFull]);
+    // And stays on the wall.
+    state.tick();
+    assert_eq!(column(&state, 2, -3, 2), [Empty, Empty, Sand, Sand, Sand, WallFull]);
+}
+
+#[test]
+fn sand_on_the_ground_stays() {
+    let mut state = open_scene()
+        .with_tile((2, 0), Tile::Sand)
+        .with_tile((2, 1), Tile::WallFull)
+        .build();
+    state.tick();
+    assert_eq!(state.get_tile((2, 0)), Tile::Sand);
+    // Nothing is left to look at on the next tick.
+    assert!(state.unstable.is_empty());
+}
+
+#[test]
+fn digging_under_sand_makes_it_fall() {
+    let mut state = open_scene()
+        .facing(Dir::Up)
+        .with_tile((0, -2), Tile::Sand)
+        .with_tile((0, -1), Tile::WallLow)
+        .build();
+    state.tick();
+    assert!(state.unstable.is_empty());
+    // The sand falls in the same tick that the wall under it is dug.
+    while state.get_tile((0, -2)) == Tile::Sand {
+        press(&mut state, Dir::Up);
+    }
+    assert_eq!(column(&state, 0, -2, -1), [Tile::Empty, Tile::Sand]);
+    // Where it fell from and to, and whatever was above it, might fall next.
+    let unstable: HashSet<Pos> = HashSet::from([(0, -3), (0, -2), (0, -1)]);
+    assert_eq!(state.unstable, unstable);
+    // Falling on the player pushes them to the side.
+    state.tick();
+    assert_eq!(state.get_tile((0, 0)), Tile::Sand);
+    assert_ne!(state.player_pos(), (0, 0));
+    assert_eq!(state.player_pos().1, 0);
+}
@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+
+use crate::{Dir, Input, State, StateBuilder, VIEW_RADIUS};
+
+/// A state where the player looked around once at `pos`.
+fn looked_at(pos: (i32, i32)) -> State {
+    let mut state = StateBuilder::new().player_pos(pos).build();
+    state.on_input(Input::Look(Dir::Down));
+    state
+}
+
+#[test]
+fn the_chunks_in_view_are_explored() {
+    let state = looked_at((0, 0));
+    let expected = HashSet::from([(-1, -1), (-1, 0), (0, -1), (0, 0)]);
+    assert_eq!(state.explored, expected);
+    // The corners of those chunks, and just past them.
+    assert!(state.is_explored((-16, -16)));
+    assert!(state.is_explored((15, 15)));
+    assert!(!state.is_explored((-17, 0)));
+    assert!(!state.is_explored((0, 16)));
+}
+
+#[test]
+fn what_is_in_view_is_seen_even_in_a_new_chunk() {
+    let state = StateBuilder::new().build();
+    assert!(state.explored.is_empty());
+    assert!(state.is_explored((VIEW_RADIUS, -VIEW_RADIUS)));
+    assert!(!state.is_explored((VIEW_RADIUS + 1, 0)));
+}
+
+#[test]
+fn explored_chunks_stay_explored() {
+    let mut state = looked_at((0, 0));
+    state.player_pos = (100, 0);
+    state.on_input(Input::Look(Dir::Up));
+    assert!(state.is_explored((0, 0)));
+    assert!(state.is_explored((100, 0)));
+    assert!(!state.is_explored((50, 0)));
+}
+
+#[test]
+fn without_fog_everything_is_seen() {
+    let mut state = looked_at((0, 0));
+    state.on_input(Input::Command("fog".to_string()));
+    assert!(state.is_explored((1000, -1000)));
+}
+
+#[test]
+fn explored_chunks_are_saved() {
+    let state = looked_at((40, -40));
+    let text = toml::to_string(&state).unwrap();
+    let loaded: State = toml::from_str(&text).unwrap();
+    assert_eq!(loaded.explored, state.explored);
+    assert_eq!(loaded.fog_of_war, state.fog_of_war);
+}
@@ -0,0 +1,326 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use super::open_scene;
+use super::scripted::{FakeClock, ScriptedPlatform, Step};
+use crate::{
+    DEFAULT_SEED, Dir, Input, IsShift, Item, LoadResult, State, StateBuilder, Tile, TileChange,
+    seed_from_str, start_game, start_game_with_clock,
+};
+
+fn corrupt_save() -> ScriptedPlatform<'static> {
+    ScriptedPlatform {
+        save: Some(LoadResult::Corrupt { reason: "a bad byte".to_string() }),
+        ..ScriptedPlatform::default()
+    }
+}
+
+#[test]
+fn without_a_save_a_new_world_is_made() {
+    let mut p = ScriptedPlatform::pressing([]);
+    p.texts = [Some("Home".to_string()), Some("my cool world".to_string())].into();
+    start_game(&mut p).unwrap();
+    assert_eq!(p.last_save().meta().name, "Home");
+    assert_eq!(p.last_save().seed(), seed_from_str("my cool world"));
+    assert!(p.cleaned_up);
+}
+
+#[test]
+fn a_loaded_game_is_played_on() {
+    let state = StateBuilder::new().player_pos((5, 5)).build();
+    let mut p = ScriptedPlatform::pressing([]).loading(state);
+    start_game(&mut p).unwrap();
+    assert!(p.asked.is_empty());
+    assert_eq!(p.last_save().player_pos(), (5, 5));
+}
+
+#[test]
+fn a_loaded_game_is_saved_compacted() {
+    let mut state = StateBuilder::new().build();
+    // Stored, but the same as what is generated there.
+    let stale = state.get_tile((10, 10));
+    state.tiles.borrow_mut().insert((10, 10), stale);
+    let edit = if state.get_tile((1, 1)) == Tile::WallHalf { Tile::Empty } else { Tile::WallHalf };
+    state.set_tile((1, 1), edit);
+    let mut p = ScriptedPlatform::pressing([]).loading(state);
+    start_game(&mut p).unwrap();
+    let tiles = p.last_save().tiles.borrow().clone();
+    assert!(!tiles.contains_key(&(10, 10)));
+    assert_eq!(tiles.get(&(1, 1)), Some(&edit));
+}
+
+#[test]
+fn a_corrupt_save_is_left_alone_if_the_player_does_not_start_over() {
+    let mut p = corrupt_save();
+    start_game(&mut p).unwrap();
+    assert!(p.asked[0].contains("a bad byte"));
+    assert!(!p.backed_up);
+    assert!(p.saved.is_empty());
+    assert!(p.cleaned_up);
+}
+
+#[test]
+fn a_corrupt_save_is_backed_up_before_starting_over() {
+    let mut p = corrupt_save();
+    p.confirms = [true].into();
+    start_game(&mut p).unwrap();
+    assert!(p.backed_up);
+    assert_eq!(p.saved.len(), 1);
+    assert_eq!(p.last_save().seed(), DEFAULT_SEED);
+}
+
+/// Opens the pause menu, goes down to an entry and chooses it.
+fn choose_in_pause(entries_down: usize) -> Vec<Input> {
+    let mut inputs = vec![Input::CloseMenu];
+    inputs.extend(std::iter::repeat_n(down(), entries_down));
+    inputs.push(Input::Confirm);
+    inputs
+}
+
+fn down() -> Input {
+    Input::Dir(Dir::Down, IsShift::No)
+}
+
+/// Plays `inputs` in the open scene.
+fn play(inputs: Vec<Input>) -> ScriptedPlatform<'static> {
+    let mut p = ScriptedPlatform::pressing(inputs).loading(open_scene().build());
+    start_game(&mut p).unwrap();
+    p
+}
+
+#[test]
+fn resuming_goes_back_to_the_game() {
+    let mut inputs = choose_in_pause(0);
+    inputs.push(down());
+    let p = play(inputs);
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn saving_from_the_pause_menu_saves_and_goes_on() {
+    let mut inputs = choose_in_pause(1);
+    inputs.push(down());
+    let p = play(inputs);
+    assert_eq!(p.saved.len(), 2);
+    assert_eq!(p.saved[0].player_pos(), (0, 0));
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn a_new_world_from_the_pause_menu_replaces_this_one() {
+    let mut inputs = choose_in_pause(2);
+    // "Yes", to the question if the player is sure.
+    inputs.extend([Input::Dir(Dir::Left, IsShift::No), Input::Confirm]);
+    let mut p = ScriptedPlatform::pressing(inputs).loading(open_scene().build());
+    p.texts = [Some("Another".to_string()), Some("another seed".to_string())].into();
+    start_game(&mut p).unwrap();
+    assert_eq!(p.saved.len(), 2);
+    assert_eq!(p.saved[0].meta().name, "Another");
+    assert_eq!(p.last_save().seed(), seed_from_str("another seed"));
+}
+
+#[test]
+fn saying_no_to_a_new_world_keeps_this_one() {
+    let mut inputs = choose_in_pause(2);
+    // "No" is highlighted, and then the pause menu is closed.
+    inputs.extend([Input::Confirm, Input::CloseMenu, down()]);
+    let p = play(inputs);
+    assert!(p.asked.is_empty());
+    assert!(p.saved.iter().all(|state| state.seed() == open_scene().build().seed()));
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn escaping_a_question_is_saying_no() {
+    let mut inputs = choose_in_pause(4);
+    // Back to the pause menu, and out of it.
+    inputs.extend([Input::Dir(Dir::Left, IsShift::No), Input::CloseMenu, Input::CloseMenu, down()]);
+    let p = play(inputs);
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn save_and_quit_saves_and_stops() {
+    let mut inputs = choose_in_pause(3);
+    inputs.push(down());
+    let p = play(inputs);
+    assert_eq!(p.saved.len(), 1);
+    assert_eq!(p.last_save().player_pos(), (0, 0));
+    // The game stopped before the last input.
+    assert_eq!(p.steps.len(), 1);
+    assert!(p.cleaned_up);
+}
+
+#[test]
+fn quitting_without_saving_does_not_save() {
+    let mut inputs = vec![down()];
+    inputs.extend(choose_in_pause(4));
+    inputs.extend([Input::Dir(Dir::Left, IsShift::No), Input::Confirm]);
+    let p = play(inputs);
+    assert!(p.saved.is_empty());
+    assert!(p.cleaned_up);
+}
+
+#[test]
+fn saying_no_to_quitting_without_saving_goes_on() {
+    let mut inputs = choose_in_pause(4);
+    // "No" is highlighted, and then the pause menu is closed.
+    inputs.extend([Input::Confirm, Input::CloseMenu, down()]);
+    let p = play(inputs);
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn a_panic_while_drawing_still_saves_and_cleans_up() {
+    let mut p = ScriptedPlatform::pressing([down()]).loading(open_scene().build());
+    p.panic_after_draws = Some(1);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| start_game(&mut p)));
+    assert!(result.is_err());
+    assert!(p.cleaned_up);
+    // What was done before the panic is not lost.
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+}
+
+#[test]
+fn an_error_still_saves_what_was_not_saved() {
+    let inputs = [down(), Input::Look(Dir::Left)];
+    let mut p = ScriptedPlatform::pressing(inputs).loading(open_scene().build());
+    p.fail_at_end = true;
+    assert_eq!(start_game(&mut p), Err("Out of inputs".to_string()));
+    // Too few inputs for a save on the way, so this is the one at the end.
+    assert_eq!(p.saved.len(), 1);
+    assert_eq!(p.last_save().player_pos(), (0, 1));
+    assert_eq!(p.last_save().player_dir, Dir::Left);
+    assert!(p.cleaned_up);
+}
+
+#[test]
+fn an_input_makes_a_game_dirty() {
+    let mut state = open_scene().build();
+    assert!(!state.is_dirty());
+    state.on_input(down());
+    assert!(state.is_dirty());
+}
+
+#[test]
+fn a_game_that_did_not_change_is_not_saved_at_the_end() {
+    let mut p = ScriptedPlatform::pressing([]).loading(open_scene().build());
+    p.fail_at_end = true;
+    start_game(&mut p).unwrap_err();
+    assert!(p.saved.is_empty());
+}
+
+/// Plays `steps` in `state` with a fake clock, and gives the state at the end.
+fn play_in_time(state: State, steps: impl IntoIterator<Item = Step>) -> State {
+    let clock = FakeClock::new();
+    let mut p = ScriptedPlatform::new(steps).loading(state).with_clock(&clock);
+    start_game_with_clock(&mut p, &clock).unwrap();
+    p.last_save().clone()
+}
+
+/// A long corridor going down from the player, who faces down it.
+fn long_way_down() -> State {
+    let mut builder = StateBuilder::new();
+    for y in 0..=20 {
+        builder = builder.with_tile((0, y), Tile::Empty);
+    }
+    builder.build()
+}
+
+#[test]
+fn steps_too_close_together_are_ignored() {
+    let state = long_way_down();
+    let cooldown = state.move_cooldown();
+    let steps = [
+        Step::Press(down()),
+        Step::Press(down()),
+        Step::Wait(cooldown / 2),
+        Step::Press(down()),
+        Step::Wait(cooldown / 2),
+        Step::Press(down()),
+        Step::Wait(cooldown * 3),
+        Step::Press(down()),
+    ];
+    let state = play_in_time(state, steps);
+    assert_eq!(state.player_pos(), (0, 3));
+    assert_eq!(state.stats().steps_taken, 3);
+}
+
+#[test]
+fn a_held_key_walks_at_the_same_speed_however_fast_it_repeats() {
+    let mut state = long_way_down();
+    state.set_move_cooldown(Duration::from_millis(100));
+    // A second of a key repeating every 10 or every 50 milliseconds.
+    for repeat in [10, 50] {
+        let held = (0..1000 / repeat)
+            .flat_map(|_| [Step::Press(down()), Step::Wait(Duration::from_millis(repeat))]);
+        assert_eq!(play_in_time(state.clone(), held).player_pos(), (0, 10), "{repeat}");
+    }
+}
+
+#[test]
+fn turning_is_a_step_too() {
+    let state = long_way_down();
+    let steps = [Step::Press(Input::Dir(Dir::Left, IsShift::No)), Step::Press(down())];
+    let state = play_in_time(state, steps);
+    // Turned left, and the turn back came too soon.
+    assert_eq!(state.player_dir, Dir::Left);
+    assert_eq!(state.player_pos(), (0, 0));
+}
+
+#[test]
+fn the_time_between_inputs_is_played_but_only_up_to_a_minute() {
+    let look = |dir| Step::Press(Input::Look(dir));
+    let steps = [
+        Step::Wait(Duration::from_secs(10)),
+        look(Dir::Left),
+        // The player walked away for a while.
+        Step::Wait(Duration::from_secs(300)),
+        look(Dir::Right),
+        Step::Wait(Duration::from_secs(20)),
+        look(Dir::Up),
+    ];
+    let state = play_in_time(open_scene().build(), steps);
+    assert_eq!(state.stats().play_time(), Duration::from_secs(10 + 60 + 20));
+    assert_eq!(state.last_played(), FakeClock::UNIX_START + 330);
+}
+
+#[test]
+fn a_new_world_is_dated_when_it_is_made() {
+    let clock = FakeClock::new();
+    clock.wait(Duration::from_secs(7));
+    let mut p = ScriptedPlatform::pressing([]).with_clock(&clock);
+    start_game_with_clock(&mut p, &clock).unwrap();
+    assert_eq!(p.last_save().created_at(), FakeClock::UNIX_START + 7);
+}
+
+#[test]
+fn old_saves_were_never_played() {
+    let mut state = open_scene().build();
+    state.stats.add_play_time(Duration::from_secs(5));
+    (state.created_at, state.last_played) = (FakeClock::UNIX_START, FakeClock::UNIX_START);
+    let text = toml::to_string(&state).unwrap();
+    let new_fields = ["created_at", "last_played", "play_time_ms"];
+    let old_text: String = text
+        .lines()
+        .filter(|line| !new_fields.iter().any(|field| line.starts_with(field)))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let old: State = toml::from_str(&old_text).unwrap();
+    assert_eq!(old.stats().play_time(), Duration::ZERO);
+    assert_eq!((old.created_at(), old.last_played()), (0, 0));
+}
+
+#[test]
+fn digging_changes_one_tile_and_one_item() {
+    let mut p = ScriptedPlatform::pressing([down()])
+        .loading(open_scene().with_tile((0, 1), Tile::WallLow).build());
+    start_game(&mut p).unwrap();
+    let dig = &p.changes[0];
+    let broken = TileChange { pos: (0, 1), old: Tile::WallLow, new: Tile::Empty };
+    assert_eq!(dig.tiles, [broken]);
+    assert_eq!(dig.inventory, [(Item::Wall, 1)]);
+    assert_eq!(dig.player_moved, None);
+    // Quitting changes nothing.
+    assert!(p.changes[1].tiles.is_empty() && p.changes[1].inventory.is_empty());
+}
@@ -0,0 +1,140 @@
+use noise::{NoiseFn, Perlin};
+use rand::Rng;
+
+use crate::random::hash_rng;
+use crate::render::draw_tile;
+use crate::{DEFAULT_SEED, GEN_VERSION, State, StateBuilder, Tile};
+
+/// Like in an exported map, but with dots for empty tiles, which are easier
+/// to count than spaces.
+fn tile_char(tile: Tile) -> char {
+    match draw_tile(tile).single_char() {
+        ' ' => '.',
+        c => c,
+    }
+}
+
+/// The generated tiles of a region, a character for each, row by row.
+fn dump(state: &State, (left, top): (i32, i32), width: u32, height: u32) -> String {
+    let tiles = state.tiles_in_rect((left, top), width, height);
+    tiles
+        .chunks(width as usize)
+        .map(|row| row.iter().map(|&tile| tile_char(tile)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn generation_has_not_changed() {
+    let state = StateBuilder::new().seed(DEFAULT_SEED).build();
+    let expected = [
+        "░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░▒.........██",
+        "░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░..........██",
+        "░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░..........※██",
+        "░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░.............█",
+        "░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░..............█",
+        "░░░░░░░░░░░░░░░░░▒▒░░░░░░░░░░░░░...............▓",
+        "░░░░░░░░░░░░░░░░████▒░░░░░░░░░░................▒",
+        "░░░░░░░░░░░░░░░░▓████▒░░░░░░░░.................▓",
+        "░░░░░░░░░░░░░░░░░█████░░░░░░░...................",
+        "░░░░░░░░░░░░░░░░░▒████▓░░░░░..................██",
+        "░░░░░░░▒░░░░░░░░░░█████░░░░.※.........▓......███",
+        "░░░░█░██░░░░░░░░░░░▓██░░░░........█...██....████",
+        "░░░█░░██░░░░░░░░░░░░░░░░░........██..█████▓█████",
+        "░░░█░███░░░░░░░░░░░░░░░░.........██..███████████",
+        "░░░░░███░░░░░░░░░░░░░░░..※..※....██..█████▓█████",
+        "░░░░████░░░░░░░░░░░░░░............█..███....████",
+    ]
+    .join("\n");
+    assert_eq!(dump(&state, (-24, -8), 48, 16), expected);
+}
+
+#[test]
+fn old_worlds_generate_the_way_they_did() {
+    let mut state = StateBuilder::new().build();
+    state.gen_version = 0;
+    // How the world was generated before there were versions.
+    let noise = Perlin::new(DEFAULT_SEED);
+    for (pos, tile) in state.region((-50, -50), 100, 100) {
+        let f = (noise.get([pos.0 as f64 * 0.1, pos.1 as f64 * 0.1]) + 1.0) / 2.0;
+        assert_eq!(tile, if f < 0.3 { Tile::WallFull } else { Tile::Empty }, "At {pos:?}");
+    }
+}
+
+#[test]
+fn saves_without_a_version_are_old_worlds() {
+    let state = State::new();
+    assert_eq!(state.gen_version, GEN_VERSION);
+    let text = toml::to_string(&state).unwrap();
+    assert!(text.contains("gen_version"));
+    let old_text: String = text
+        .lines()
+        .filter(|line| !line.starts_with("gen_version"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let loaded: State = toml::from_str(&old_text).unwrap();
+    assert_eq!(loaded.gen_version, 0);
+}
+
+#[test]
+fn walls_are_weathered_at_their_edges() {
+    let mut state = StateBuilder::new().seed(DEFAULT_SEED).build();
+    let weathered = |state: &State| {
+        let tiles = state.tiles_in_rect((-100, -100), 200, 200);
+        tiles.iter().filter(|&&tile| matches!(tile, Tile::WallHalf | Tile::WallLow)).count()
+    };
+    assert!(weathered(&state) > 0);
+    state.gen_version = 0;
+    assert_eq!(weathered(&state), 0);
+}
+
+/// A world with a stored tile that is the same as its generated one, like an
+/// old save can have, at (10, 10), and a real edit at (11, 11).
+fn stale_world() -> State {
+    let mut state = StateBuilder::new().seed(DEFAULT_SEED).build();
+    let stale = state.get_tile((10, 10));
+    state.tiles.borrow_mut().insert((10, 10), stale);
+    let edit = if state.get_tile((11, 11)) == Tile::Empty { Tile::WallFull } else { Tile::Empty };
+    state.set_tile((11, 11), edit);
+    state
+}
+
+#[test]
+fn compacting_drops_generated_tiles_and_keeps_edits() {
+    let mut state = stale_world();
+    let before = state.tiles_in_rect((0, 0), 20, 20);
+    assert_eq!(state.tiles.borrow().len(), 2);
+    state.compact_tiles();
+    assert_eq!(state.tiles.borrow().keys().collect::<Vec<_>>(), [&(11, 11)]);
+    assert_eq!(state.tiles_in_rect((0, 0), 20, 20), before);
+}
+
+#[test]
+fn a_compacted_world_saves_and_loads() {
+    let mut state = stale_world();
+    state.compact_tiles();
+    let text = toml::to_string(&state).unwrap();
+    let loaded: State = toml::from_str(&text).unwrap();
+    assert_eq!(*loaded.tiles.borrow(), *state.tiles.borrow());
+    assert_eq!(loaded.tiles_in_rect((0, 0), 20, 20), state.tiles_in_rect((0, 0), 20, 20));
+}
+
+#[test]
+fn a_rect_of_tiles_is_the_same_as_each_tile() {
+    let state = StateBuilder::new()
+        .with_tile((-3, -4), Tile::Sand)
+        .with_tile((5, 2), Tile::Torch)
+        .bounds((-40, -40), (40, 40))
+        .build();
+    let mut rng = hash_rng(0, (0, 0), 0, 0);
+    for _ in 0..20 {
+        let top_left = (rng.gen_range(-50..20), rng.gen_range(-50..20));
+        let (width, height) = (rng.gen_range(0..40), rng.gen_range(0..40));
+        let positions: Vec<_> = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (top_left.0 + x, top_left.1 + y)))
+            .collect();
+        let expected: Vec<_> = positions.iter().map(|&pos| state.get_tile(pos)).collect();
+        assert_eq!(state.tiles_in_rect(top_left, width, height), expected);
+        assert_eq!(state.tiles_at(positions), expected);
+    }
+}
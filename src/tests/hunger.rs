@@ -0,0 +1,88 @@
+use super::{corridor, open_scene, press};
+use crate::{Dir, FAINTED_HUNGER, HUNGER_STEPS, Input, Item, MAX_HUNGER, State, Tile};
+
+/// The open scene, with the player `hunger` full and holding `berries`
+/// berries, selected.
+fn hungry_with_berries(hunger: u8, berries: usize) -> State {
+    let mut state = open_scene().build();
+    state.hunger = hunger;
+    for _ in 0..berries {
+        state.inventory.insert(Item::Berry).unwrap();
+    }
+    state.selected_item = Some(Item::Berry);
+    state
+}
+
+#[test]
+fn walking_makes_the_player_hungry_every_so_many_steps() {
+    let mut state = corridor(3).build();
+    state.stats.steps_taken = HUNGER_STEPS - 2;
+    press(&mut state, Dir::Right);
+    assert_eq!(state.hunger(), MAX_HUNGER);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.hunger(), MAX_HUNGER - 1);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.hunger(), MAX_HUNGER - 1);
+}
+
+#[test]
+fn starving_faints_the_player_back_to_their_spawn() {
+    let mut state = corridor(3).build();
+    state.hunger = 0;
+    state.stats.steps_taken = HUNGER_STEPS - 2;
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (1, 0));
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (0, 0));
+    assert_eq!(state.hunger(), FAINTED_HUNGER);
+    assert_eq!(state.message, "You fainted from hunger, and woke up at your spawn point.");
+}
+
+#[test]
+fn eating_takes_one_and_fills_the_player_up() {
+    let mut state = hungry_with_berries(10, 2);
+    state.on_input(Input::Eat);
+    assert_eq!(state.hunger(), 10 + Item::Berry.nutrition());
+    assert_eq!(state.inventory().count_of(&Item::Berry), 1);
+    assert_eq!(state.message, "You ate a berry.");
+    // Never past full.
+    state.hunger = MAX_HUNGER - 1;
+    state.on_input(Input::Eat);
+    assert_eq!(state.hunger(), MAX_HUNGER);
+    assert_eq!(state.inventory().count_of(&Item::Berry), 0);
+}
+
+#[test]
+fn a_full_player_does_not_eat() {
+    let mut state = hungry_with_berries(MAX_HUNGER, 1);
+    state.on_input(Input::Eat);
+    assert_eq!(state.message, "You are not hungry.");
+    assert_eq!(state.inventory().count_of(&Item::Berry), 1);
+}
+
+#[test]
+fn what_is_not_food_is_not_eaten() {
+    let mut state = hungry_with_berries(10, 0);
+    state.inventory.insert(Item::Wall).unwrap();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::Eat);
+    assert_eq!(state.message, "You can't eat wall.");
+    assert_eq!(state.hunger(), 10);
+    assert_eq!(state.inventory().count_of(&Item::Wall), 1);
+}
+
+#[test]
+fn picked_bushes_grow_their_berries_back() {
+    let mut state = open_scene().with_tile((0, 1), Tile::Bush(true)).build();
+    press(&mut state, Dir::Down);
+    assert_eq!(state.get_tile((0, 1)), Tile::Bush(false));
+    assert_eq!(state.inventory().count_of(&Item::Berry), 1);
+    let ticks = (1..1000).find(|_| {
+        state.tick();
+        state.get_tile((0, 1)) == Tile::Bush(true)
+    });
+    assert!(ticks.is_some_and(|ticks| ticks > 1), "{ticks:?}");
+    // And can be picked again.
+    press(&mut state, Dir::Down);
+    assert_eq!(state.inventory().count_of(&Item::Berry), 2);
+}
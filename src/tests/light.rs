@@ -0,0 +1,63 @@
+use crate::light::{DAY_LENGTH, NIGHT_LIGHT};
+use crate::{MAX_LIGHT, State, StateBuilder, Tile};
+
+/// A night with torches at `torches`, and nothing else giving light.
+fn night_with(torches: &[(i32, i32)]) -> State {
+    let mut builder = StateBuilder::new();
+    for &pos in torches {
+        builder = builder.with_tile(pos, Tile::Torch);
+    }
+    let mut state = builder.build();
+    state.stats.ticks_survived = DAY_LENGTH / 2;
+    assert!(state.is_night());
+    state
+}
+
+#[test]
+fn a_torch_is_dimmer_further_away() {
+    let state = night_with(&[(0, 0)]);
+    assert_eq!(state.light_at((0, 0)), MAX_LIGHT);
+    assert_eq!(state.light_at((2, 0)), MAX_LIGHT - 2);
+    assert_eq!(state.light_at((0, -3)), MAX_LIGHT - 3);
+    assert_eq!(state.light_at((20, 0)), NIGHT_LIGHT);
+}
+
+#[test]
+fn the_day_is_lit_without_torches() {
+    let state = StateBuilder::new().build();
+    assert!(!state.is_night());
+    assert_eq!(state.light_at((20, 0)), MAX_LIGHT);
+}
+
+#[test]
+fn two_torches_give_the_light_of_the_closer_one() {
+    let state = night_with(&[(-3, 0), (3, 0)]);
+    // Between them the light doesn't add up.
+    assert_eq!(state.light_at((0, 0)), MAX_LIGHT - 3);
+    assert_eq!(state.light_at((-3, 0)), MAX_LIGHT);
+    assert_eq!(state.light_at((4, 0)), MAX_LIGHT - 1);
+    assert_eq!(state.light_at((-4, 0)), MAX_LIGHT - 1);
+}
+
+#[test]
+fn the_light_in_a_rect_is_the_light_at_each_tile() {
+    let state = night_with(&[(0, 0), (4, 3)]);
+    let rect = state.light_in_rect((-2, -1), 8, 5);
+    // Another state, so nothing is remembered from working out the rect.
+    let other = night_with(&[(0, 0), (4, 3)]);
+    for (i, &level) in rect.iter().enumerate() {
+        let pos = (-2 + i as i32 % 8, -1 + i as i32 / 8);
+        assert_eq!(level, other.light_at(pos), "At {pos:?}");
+    }
+}
+
+#[test]
+fn placing_and_breaking_a_torch_changes_the_light() {
+    let mut state = night_with(&[]);
+    // Remembered from here on.
+    assert_eq!(state.light_at((2, 0)), NIGHT_LIGHT);
+    state.set_tile((0, 0), Tile::Torch);
+    assert_eq!(state.light_at((2, 0)), MAX_LIGHT - 2);
+    state.set_tile((0, 0), Tile::Empty);
+    assert_eq!(state.light_at((2, 0)), NIGHT_LIGHT);
+}
@@ -0,0 +1,44 @@
+use super::corridor;
+use crate::{Dir, Input, State, Tile};
+
+fn look(state: &mut State, dir: Dir) {
+    state.on_input(Input::Look(dir));
+}
+
+#[test]
+fn nothing_is_ahead_in_a_long_corridor() {
+    let mut state = corridor(20).build();
+    assert_eq!(state.first_obstacle((0, 0), Dir::Right, 8), None);
+    look(&mut state, Dir::Right);
+    assert_eq!(state.message, "");
+}
+
+#[test]
+fn a_wall_next_to_the_player_is_faced() {
+    let mut state = corridor(0).build();
+    assert_eq!(state.first_obstacle((0, 0), Dir::Right, 8), Some(((1, 0), Tile::WallFull)));
+    look(&mut state, Dir::Right);
+    assert_eq!(state.message, "You are facing a wall");
+}
+
+#[test]
+fn a_wall_further_away_says_how_far() {
+    let mut state = corridor(4).build();
+    assert_eq!(state.first_obstacle((0, 0), Dir::Right, 8), Some(((5, 0), Tile::WallFull)));
+    // Too far.
+    assert_eq!(state.first_obstacle((0, 0), Dir::Right, 4), None);
+    look(&mut state, Dir::Right);
+    assert_eq!(state.message, "A wall, 5 tiles ahead");
+}
+
+#[test]
+fn the_same_thing_is_not_said_twice_in_a_row() {
+    let mut state = corridor(4).build();
+    look(&mut state, Dir::Right);
+    look(&mut state, Dir::Right);
+    assert_eq!(state.message, "");
+    look(&mut state, Dir::Up);
+    assert_eq!(state.message, "You are facing a wall");
+    look(&mut state, Dir::Right);
+    assert_eq!(state.message, "A wall, 5 tiles ahead");
+}
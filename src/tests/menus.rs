@@ -0,0 +1,271 @@
+use super::open_scene;
+use crate::{ConfirmAction, Dir, Input, IsShift, Item, Menu, Outcome, PauseEntry, Pos, State, Tile};
+
+/// One input of every kind.
+fn every_input() -> Vec<Input> {
+    vec![
+        Input::Dir(Dir::Down, IsShift::No),
+        Input::Run(Dir::Down),
+        Input::Look(Dir::Down),
+        Input::Build,
+        Input::Interact,
+        Input::Quit,
+        Input::OpenInventory,
+        Input::OpenWaypoints,
+        Input::CycleZoom,
+        Input::CloseMenu,
+        Input::Confirm,
+        Input::CycleVariant,
+        Input::ToggleAutoPlace,
+        Input::ToggleLog,
+        Input::Command("dig".to_string()),
+        Input::ClickTile((0, 2)),
+        Input::DigDir(Dir::Down),
+        Input::BuildDir(Dir::Down),
+        Input::BuildAt((0, 1)),
+        Input::ClickItem(0),
+        Input::MoveTo((2, 0)),
+        Input::Throw,
+        Input::Eat,
+        Input::ForPlayer(1, Box::new(Input::Dir(Dir::Down, IsShift::No))),
+    ]
+}
+
+/// A scene with a trader at (0, 1), waystones at (2, 2) and (-2, -2), and
+/// some items, with `menu` open.
+fn scene_in(menu: Menu) -> State {
+    let mut state = open_scene()
+        .with_tile((0, 1), Tile::Trader)
+        .with_tile((2, 2), Tile::Waystone)
+        .with_tile((-2, -2), Tile::Waystone)
+        .with_item(Item::Torch, 2)
+        .with_item(Item::Rock, 2)
+        .build();
+    state.waypoints.insert("A".to_string(), (2, 2));
+    state.waypoints.insert("B".to_string(), (-2, -2));
+    // Sees the trader it faces already, so that is not news to any input.
+    state.tick();
+    state.message.clear();
+    state.menus = vec![menu];
+    state
+}
+
+/// Everything an input could change that the tests look at.
+fn look(state: &State) -> impl PartialEq + std::fmt::Debug + use<> {
+    (
+        (state.player_pos(), state.player_dir, state.tiles_in_rect((-3, -3), 7, 7)),
+        (state.inventory().iter().collect::<Vec<_>>(), state.selected_item.clone()),
+        (state.message.clone(), state.zoom_level, state.show_log, state.auto_place),
+        (state.dig_on_turn, state.variants.clone(), state.is_walking()),
+    )
+}
+
+/// What an input did in a menu.
+#[derive(Debug, PartialEq)]
+enum Effect {
+    Nothing,
+    /// Closed the menu, going back to the one under it (or the world).
+    Closes,
+    /// Opened another menu over it.
+    Opens,
+    /// Highlighted another entry of the menu.
+    Moves,
+    /// Did something else, like choosing an item or trading.
+    Acts,
+    Quits,
+}
+
+fn effect_in(menu: Menu, input: Input) -> Effect {
+    let mut state = scene_in(menu);
+    let before = look(&state);
+    let outcome = state.on_input(input);
+    let menus = state.menus();
+    if let Outcome::Quit { .. } = outcome {
+        Effect::Quits
+    } else if menus.is_empty() {
+        Effect::Closes
+    } else if menus.len() > 1 {
+        Effect::Opens
+    } else if menus[0] != menu {
+        Effect::Moves
+    } else if look(&state) != before {
+        Effect::Acts
+    } else {
+        Effect::Nothing
+    }
+}
+
+/// Checks what every input does in `menu`. `expected` gives the effect of
+/// inputs that do something, and all others must do nothing.
+fn check_menu(menu: Menu, expected: impl Fn(&Input) -> Option<Effect>) {
+    for input in every_input() {
+        let expected = match input {
+            Input::Quit => Effect::Quits,
+            _ => expected(&input).unwrap_or(Effect::Nothing),
+        };
+        assert_eq!(effect_in(menu, input.clone()), expected, "{input:?} in {menu:?}");
+    }
+}
+
+fn is_dir(input: &Input) -> bool {
+    matches!(
+        input,
+        Input::Dir(..) | Input::Run(_) | Input::Look(_) | Input::DigDir(_) | Input::BuildDir(_)
+    )
+}
+
+#[test]
+fn inputs_in_the_inventory() {
+    check_menu(Menu::Inventory, |input| match input {
+        // Nothing is selected, so moving selects the first item.
+        _ if is_dir(input) => Some(Effect::Acts),
+        Input::ClickItem(_) | Input::Eat | Input::Command(_) => Some(Effect::Acts),
+        Input::Build | Input::Confirm => Some(Effect::Closes),
+        Input::OpenInventory | Input::CloseMenu => Some(Effect::Closes),
+        _ => None,
+    });
+}
+
+#[test]
+fn inputs_in_the_stats() {
+    check_menu(Menu::Stats, |input| match input {
+        Input::CloseMenu => Some(Effect::Closes),
+        Input::OpenInventory => Some(Effect::Opens),
+        Input::Command(_) => Some(Effect::Acts),
+        _ => None,
+    });
+}
+
+#[test]
+fn inputs_in_the_pause_menu() {
+    check_menu(Menu::Pause(PauseEntry::Resume), |input| match input {
+        _ if is_dir(input) => Some(Effect::Moves),
+        // Resumes.
+        Input::Build | Input::Confirm | Input::CloseMenu => Some(Effect::Closes),
+        Input::Command(_) => Some(Effect::Acts),
+        _ => None,
+    });
+}
+
+#[test]
+fn inputs_in_the_waypoints() {
+    check_menu(Menu::Waypoints(0), |input| match input {
+        _ if is_dir(input) => Some(Effect::Moves),
+        // Goes to the waypoint.
+        Input::Build | Input::Confirm | Input::Interact => Some(Effect::Closes),
+        Input::OpenWaypoints | Input::CloseMenu => Some(Effect::Closes),
+        Input::Command(_) => Some(Effect::Acts),
+        _ => None,
+    });
+}
+
+#[test]
+fn inputs_in_a_trade() {
+    let trader: Pos = (0, 1);
+    check_menu(Menu::Trade { pos: trader, offer: 0 }, |input| match input {
+        _ if is_dir(input) => Some(Effect::Moves),
+        // Trying to trade says what is missing.
+        Input::Build | Input::Confirm | Input::Interact => Some(Effect::Acts),
+        Input::CloseMenu => Some(Effect::Closes),
+        Input::Command(_) => Some(Effect::Acts),
+        _ => None,
+    });
+}
+
+#[test]
+fn inputs_in_a_confirm_dialog() {
+    let menu = Menu::Confirm { action: ConfirmAction::NewWorld, yes: false };
+    check_menu(menu, |input| match input {
+        _ if is_dir(input) => Some(Effect::Moves),
+        // "No" is highlighted.
+        Input::Build | Input::Confirm | Input::CloseMenu => Some(Effect::Closes),
+        Input::Command(_) => Some(Effect::Acts),
+        _ => None,
+    });
+}
+
+#[test]
+fn building_in_the_inventory_chooses_the_item() {
+    let mut state = scene_in(Menu::Inventory);
+    state.on_input(Input::Dir(Dir::Down, IsShift::No));
+    let chosen = state.selected_item.clone();
+    assert!(chosen.is_some());
+    state.on_input(Input::Build);
+    assert_eq!(state.current_menu(), Menu::None);
+    assert_eq!(state.selected_item, chosen);
+    // Nothing was built behind the menu.
+    assert_eq!(state.get_tile((0, 1)), Tile::Trader);
+    assert_eq!(state.get_tile((0, -1)), Tile::Empty);
+}
+
+#[test]
+fn a_menu_opened_over_another_closes_back_to_it() {
+    let mut state = scene_in(Menu::Stats);
+    state.on_input(Input::OpenInventory);
+    assert_eq!(state.menus(), [Menu::Stats, Menu::Inventory]);
+    // Opening the menu on top again closes it.
+    state.on_input(Input::OpenInventory);
+    assert_eq!(state.menus(), [Menu::Stats]);
+    state.on_input(Input::CloseMenu);
+    assert_eq!(state.menus(), []);
+}
+
+#[test]
+fn the_menu_on_top_is_not_opened_twice() {
+    let mut state = scene_in(Menu::Stats);
+    state.push_menu(Menu::Stats);
+    assert_eq!(state.menus(), [Menu::Stats]);
+}
+
+#[test]
+fn moving_in_a_menu_keeps_the_ones_under_it() {
+    let mut state = scene_in(Menu::Stats);
+    state.push_menu(Menu::Pause(PauseEntry::Resume));
+    state.on_input(Input::Dir(Dir::Down, IsShift::No));
+    assert_eq!(state.menus(), [Menu::Stats, Menu::Pause(PauseEntry::Save)]);
+}
+
+#[test]
+fn saying_no_goes_back_and_yes_closes_everything() {
+    let mut state = scene_in(Menu::Pause(PauseEntry::NewWorld));
+    state.on_input(Input::Confirm);
+    let confirm = |yes| Menu::Confirm { action: ConfirmAction::NewWorld, yes };
+    assert_eq!(state.menus(), [Menu::Pause(PauseEntry::NewWorld), confirm(false)]);
+    state.on_input(Input::Confirm);
+    assert_eq!(state.menus(), [Menu::Pause(PauseEntry::NewWorld)]);
+    state.on_input(Input::Confirm);
+    state.on_input(Input::Dir(Dir::Left, IsShift::No));
+    assert_eq!(state.on_input(Input::Confirm), Outcome::NewWorld);
+    assert_eq!(state.menus(), []);
+}
+
+#[test]
+fn a_stack_of_menus_closes_one_at_a_time() {
+    let mut state = scene_in(Menu::Stats);
+    state.push_menu(Menu::Inventory);
+    state.push_menu(Menu::Waypoints(0));
+    for left in [2, 1, 0] {
+        state.on_input(Input::CloseMenu);
+        assert_eq!(state.menus(), &[Menu::Stats, Menu::Inventory][..left]);
+    }
+}
+
+#[test]
+fn the_world_is_left_alone_under_a_stack_of_menus() {
+    let world = |state: &State| {
+        let rocks = state.inventory().count_of(&Item::Rock);
+        (state.player_pos(), state.tiles_in_rect((-3, -3), 7, 7), rocks, state.is_walking())
+    };
+    // Other players play on, and quitting is not about the world.
+    let inputs = every_input().into_iter().filter(|input| {
+        !matches!(input, Input::ForPlayer(..) | Input::Quit | Input::Command(_))
+    });
+    for input in inputs {
+        let mut state = scene_in(Menu::Stats);
+        state.push_menu(Menu::Inventory);
+        let before = world(&state);
+        state.on_input(input.clone());
+        assert_eq!(world(&state), before, "{input:?}");
+        assert!(!state.menus().is_empty(), "{input:?}");
+    }
+}
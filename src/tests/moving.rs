@@ -0,0 +1,83 @@
+use super::{corridor, press};
+use crate::{Dir, Input, IsShift, MAX_SLIDE, State, Tile};
+
+fn run_right(state: &mut State) {
+    state.on_input(Input::Run(Dir::Right));
+}
+
+#[test]
+fn running_stops_at_a_wall() {
+    let mut state = corridor(4).build();
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (4, 0));
+    // Already at the wall.
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (4, 0));
+}
+
+#[test]
+fn running_stops_after_the_most_steps() {
+    let mut state = corridor(30).build();
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (10, 0));
+    state.set_run_steps(3);
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (13, 0));
+}
+
+#[test]
+fn running_along_a_corridor_stops_where_it_opens() {
+    let mut state = corridor(8).with_tile((5, -1), Tile::Empty).build();
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (5, 0));
+    // And then goes on past it.
+    run_right(&mut state);
+    assert_eq!(state.player_pos(), (6, 0));
+}
+
+#[test]
+fn only_passable_tiles_are_walked_into() {
+    for &tile in Tile::ALL {
+        let mut state = corridor(3).with_tile((1, 0), tile).build();
+        state.on_input(Input::Dir(Dir::Right, IsShift::Yes));
+        let walked = state.player_pos() != (0, 0);
+        assert_eq!(walked, tile.is_passable(), "{tile:?}");
+    }
+}
+
+/// A corridor with ice from 1 to `last_ice`.
+fn icy_corridor(length: i32, last_ice: i32) -> State {
+    let mut builder = corridor(length);
+    for x in 1..=last_ice {
+        builder = builder.with_tile((x, 0), Tile::Ice);
+    }
+    builder.build()
+}
+
+#[test]
+fn sliding_stops_at_a_wall() {
+    let mut state = icy_corridor(5, 5);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (5, 0));
+}
+
+#[test]
+fn sliding_stops_on_the_ground_after_the_ice() {
+    let mut state = icy_corridor(8, 3);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (4, 0));
+}
+
+#[test]
+fn sliding_stops_after_the_most_tiles() {
+    let mut state = icy_corridor(80, 80);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.player_pos(), (1 + MAX_SLIDE as i32, 0));
+}
+
+#[test]
+fn moving_with_shift_still_slides() {
+    let mut state = icy_corridor(8, 3);
+    state.on_input(Input::Dir(Dir::Right, IsShift::Yes));
+    assert_eq!(state.player_pos(), (4, 0));
+}
@@ -0,0 +1,53 @@
+use super::{open_scene, press};
+use crate::frame::{MIN_SIZE, RenderOptions, render_frame};
+use crate::{Dir, Input, Item, Menu, Tile};
+
+#[test]
+fn moving_in_the_inventory_from_a_missing_item() {
+    for dir in [Dir::Down, Dir::Up] {
+        let mut state = open_scene().with_item(Item::Torch, 1).with_item(Item::Bed, 1).build();
+        let items: Vec<_> = state.inventory().iter().map(|(item, _)| item).collect();
+        // Down goes to the first item, and up to the last.
+        let expected = if dir == Dir::Down { items[0].clone() } else { items[1].clone() };
+        state.on_input(Input::OpenInventory);
+        assert_eq!(state.current_menu(), Menu::Inventory);
+        // Selected, but not in the inventory.
+        state.selected_item = Some(Item::Wall);
+        press(&mut state, dir);
+        assert_eq!(state.selected_item, Some(expected));
+    }
+}
+
+#[test]
+fn moving_in_an_empty_inventory() {
+    let mut state = open_scene().build();
+    state.on_input(Input::OpenInventory);
+    state.selected_item = Some(Item::Wall);
+    press(&mut state, Dir::Down);
+    assert_eq!(state.selected_item, None);
+}
+
+#[test]
+fn building_what_ran_out_says_so() {
+    let mut state = open_scene().build();
+    state.selected_item = Some(Item::Wall);
+    state.on_input(Input::Build);
+    assert_eq!(state.message, "You are out of wall!");
+    assert_eq!(state.get_tile((0, 1)), Tile::Empty);
+    // The game goes on.
+    press(&mut state, Dir::Down);
+    assert_eq!(state.player_pos(), (0, 1));
+}
+
+#[test]
+fn drawing_on_a_tiny_screen_says_it_is_too_small() {
+    let state = open_scene().build();
+    let opts = RenderOptions { help: &["Some help"] };
+    for (width, height) in [(0, 0), (1, 1), (5, 30), (80, 2), (MIN_SIZE.0 - 1, MIN_SIZE.1)] {
+        let text = render_frame(&state, width, height, &opts).text();
+        let expected: String = "Terminal too small!".chars().take(width.into()).collect();
+        if height > 0 {
+            assert!(text.starts_with(&expected), "{width}x{height}: {text:?}");
+        }
+    }
+}
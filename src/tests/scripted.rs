@@ -0,0 +1,184 @@
+//! A platform that plays from a script instead of a player, to test the game
+//! loop with.
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::{Clock, Input, LoadResult, Platform, State, StateChanges};
+
+/// Something the player does, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Press(Input),
+    /// The player waits this long before the next step.
+    Wait(Duration),
+}
+
+/// A clock that only moves when the script waits. See `Step::Wait`.
+#[derive(Debug)]
+pub struct FakeClock {
+    start: Instant,
+    passed: Cell<Duration>,
+}
+
+impl FakeClock {
+    /// The Unix time the clock starts at.
+    pub const UNIX_START: u64 = 1_700_000_000;
+
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            passed: Cell::new(Duration::ZERO),
+        }
+    }
+
+    pub fn wait(&self, time: Duration) {
+        self.passed.set(self.passed.get() + time);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.passed.get()
+    }
+
+    fn unix_time(&self) -> u64 {
+        Self::UNIX_START + self.passed.get().as_secs()
+    }
+}
+
+/// Plays `steps`, and then quits (or fails, see `fail_at_end`). Every
+/// question is answered from the answers given, or with no and nothing when
+/// they run out. Remembers what the game did with it, to check in tests.
+#[derive(Debug, Default)]
+pub struct ScriptedPlatform<'a> {
+    pub clock: Option<&'a FakeClock>,
+    /// What `load` finds. Nothing, if not set.
+    pub save: Option<LoadResult>,
+    pub steps: VecDeque<Step>,
+    pub texts: VecDeque<Option<String>>,
+    pub confirms: VecDeque<bool>,
+    /// Every question that was asked, in order.
+    pub asked: Vec<String>,
+    /// Every state that was saved, in order.
+    pub saved: Vec<State>,
+    pub backed_up: bool,
+    pub cleaned_up: bool,
+    /// Fails when the steps run out, instead of quitting.
+    pub fail_at_end: bool,
+    pub draws: usize,
+    /// Makes `draw` panic, after drawing this many times.
+    pub panic_after_draws: Option<usize>,
+    /// Every change the game told of, in order.
+    pub changes: Vec<StateChanges>,
+}
+
+impl<'a> ScriptedPlatform<'a> {
+    pub fn new(steps: impl IntoIterator<Item = Step>) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Plays `inputs` one right after the other.
+    pub fn pressing(inputs: impl IntoIterator<Item = Input>) -> Self {
+        Self::new(inputs.into_iter().map(Step::Press))
+    }
+
+    pub fn loading(mut self, state: State) -> Self {
+        self.save = Some(LoadResult::Loaded(Box::new(state)));
+        self
+    }
+
+    pub fn with_clock(mut self, clock: &'a FakeClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// The last state that was saved.
+    pub fn last_save(&self) -> &State {
+        self.saved.last().expect("The game was saved")
+    }
+}
+
+impl Platform for ScriptedPlatform<'_> {
+    type Error = String;
+
+    fn init(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), String> {
+        self.cleaned_up = true;
+        Ok(())
+    }
+
+    fn ask_for_input(&mut self) -> Result<Option<Input>, String> {
+        loop {
+            match self.steps.pop_front() {
+                Some(Step::Press(input)) => return Ok(Some(input)),
+                Some(Step::Wait(time)) => {
+                    self.clock.expect("Waiting needs a `FakeClock`").wait(time)
+                }
+                None if self.fail_at_end => return Err("Out of inputs".to_string()),
+                None => return Ok(Some(Input::Quit)),
+            }
+        }
+    }
+
+    fn poll_input(&mut self, timeout: Duration) -> Result<Option<Input>, String> {
+        match self.steps.front() {
+            Some(Step::Wait(time)) if *time > timeout => {
+                self.clock.expect("Waiting needs a `FakeClock`").wait(timeout);
+                self.steps[0] = Step::Wait(*time - timeout);
+                Ok(None)
+            }
+            Some(_) => self.ask_for_input(),
+            // Walking is let to finish before quitting.
+            None => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, _state: &State) -> Result<(), String> {
+        if self.panic_after_draws == Some(self.draws) {
+            panic!("Drawing failed");
+        }
+        self.draws += 1;
+        Ok(())
+    }
+
+    fn save(&mut self, state: &State) -> Result<(), String> {
+        self.saved.push(state.clone());
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<LoadResult, String> {
+        Ok(self.save.take().unwrap_or(LoadResult::NoSave))
+    }
+
+    fn back_up_save(&mut self) -> Result<(), String> {
+        self.backed_up = true;
+        Ok(())
+    }
+
+    fn confirm(&mut self, msg: &str) -> Result<bool, String> {
+        self.asked.push(msg.to_string());
+        Ok(self.confirms.pop_front().unwrap_or(false))
+    }
+
+    fn run_command(&mut self, _state: &State, _command: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, String> {
+        self.asked.push(prompt.to_string());
+        Ok(self.texts.pop_front().flatten())
+    }
+
+    fn on_changes(&mut self, changes: &StateChanges) -> Result<(), String> {
+        self.changes.push(changes.clone());
+        Ok(())
+    }
+}
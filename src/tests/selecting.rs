@@ -0,0 +1,80 @@
+use super::{open_scene, press};
+use crate::{Achievement, Dir, Input, Item, State, StateBuilder, Tile};
+
+/// The player next to a low wall on their right, with `items`.
+fn by_a_wall(items: &[(Item, usize)]) -> State {
+    let mut builder = open_scene().facing(Dir::Right).with_tile((1, 0), Tile::WallLow);
+    for (item, count) in items {
+        builder = builder.with_item(item.clone(), *count);
+    }
+    let mut state = builder.build();
+    // So the message is not about the achievement.
+    state.achievements.insert(Achievement::FirstBreak);
+    state
+}
+
+#[test]
+fn the_first_item_got_is_selected() {
+    let mut state = by_a_wall(&[]);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.selected_item, Some(Item::Wall));
+    assert_eq!(state.message, "Selected wall");
+}
+
+#[test]
+fn a_new_item_does_not_replace_the_selection() {
+    let mut state = by_a_wall(&[(Item::Torch, 1)]);
+    state.selected_item = Some(Item::Torch);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.selected_item, Some(Item::Torch));
+    assert_eq!(state.message, "");
+}
+
+#[test]
+fn more_of_an_item_is_not_new() {
+    let mut state = by_a_wall(&[(Item::Wall, 1)]);
+    press(&mut state, Dir::Right);
+    assert_eq!(state.selected_item, None);
+}
+
+/// A state with a wall, a torch and a rock, with the item at `index` (in the
+/// order of the inventory) selected from the inventory, which is closed
+/// again.
+fn selected_at(index: usize) -> (State, Vec<Item>) {
+    let mut state = StateBuilder::new()
+        .with_item(Item::Wall, 1)
+        .with_item(Item::Torch, 1)
+        .with_item(Item::Rock, 1)
+        .build();
+    let items: Vec<Item> = state.inventory().iter().map(|(item, _)| item).collect();
+    state.on_input(Input::OpenInventory);
+    state.on_input(Input::ClickItem(index));
+    state.on_input(Input::CloseMenu);
+    assert_eq!(state.selected_item.as_ref(), Some(&items[index]));
+    (state, items)
+}
+
+#[test]
+fn the_selection_stays_when_the_inventory_opens_and_closes() {
+    let (mut state, items) = selected_at(1);
+    state.on_input(Input::OpenInventory);
+    state.on_input(Input::CloseMenu);
+    state.on_input(Input::OpenInventory);
+    assert_eq!(state.selected_item.as_ref(), Some(&items[1]));
+}
+
+#[test]
+fn an_item_that_ran_out_is_replaced_by_the_one_where_it_was() {
+    let (mut state, items) = selected_at(1);
+    state.inventory.remove(&items[1]).unwrap();
+    state.on_input(Input::OpenInventory);
+    assert_eq!(state.selected_item.as_ref(), Some(&items[2]));
+}
+
+#[test]
+fn the_last_item_running_out_selects_the_one_before_it() {
+    let (mut state, items) = selected_at(2);
+    state.inventory.remove(&items[2]).unwrap();
+    state.on_input(Input::OpenInventory);
+    assert_eq!(state.selected_item.as_ref(), Some(&items[1]));
+}
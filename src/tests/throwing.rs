@@ -0,0 +1,27 @@
+use super::corridor;
+use crate::{Input, Item, Tile};
+
+#[test]
+fn a_thrown_rock_hits_the_first_wall() {
+    let mut state = corridor(4).with_item(Item::Rock, 2).build();
+    state.on_input(Input::Throw);
+    assert_eq!(state.get_tile((5, 0)), Tile::WallHalf);
+    assert_eq!(state.inventory().count_of(&Item::Rock), 1);
+    assert_eq!(state.message, "Your rock hit a wall.");
+}
+
+#[test]
+fn a_rock_that_hits_nothing_lands_on_the_ground() {
+    let mut state = corridor(20).with_item(Item::Rock, 1).build();
+    state.on_input(Input::Throw);
+    assert_eq!(state.drops_at((10, 0)), [Item::Rock]);
+    assert_eq!(state.inventory().count_of(&Item::Rock), 0);
+}
+
+#[test]
+fn throwing_without_rocks_says_so() {
+    let mut state = corridor(4).build();
+    state.on_input(Input::Throw);
+    assert_eq!(state.message, "You are out of rock!");
+    assert_eq!(state.get_tile((5, 0)), Tile::WallFull);
+}
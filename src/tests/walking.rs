@@ -0,0 +1,59 @@
+use super::{open_scene, press};
+use crate::{Dir, Input, Pos, State, Tile};
+
+fn walk_all_the_way(state: &mut State) {
+    while state.is_walking() {
+        state.walk_step();
+    }
+}
+
+#[test]
+fn walking_goes_around_a_u_shaped_wall() {
+    // The player is in a cup that opens up, and the target is below it.
+    let mut builder = open_scene();
+    for y in -1..=1 {
+        builder = builder.with_tile((-1, y), Tile::WallFull).with_tile((1, y), Tile::WallFull);
+    }
+    let mut state = builder.with_tile((0, 1), Tile::WallFull).build();
+    let target: Pos = (0, 3);
+    // Up out of the cup, two to the side, down past it, and back.
+    assert_eq!(state.path_to(target, 100).map(|path| path.len()), Some(11));
+    state.on_input(Input::MoveTo(target));
+    walk_all_the_way(&mut state);
+    assert_eq!(state.player_pos(), target);
+}
+
+#[test]
+fn clicking_a_tile_that_cannot_be_reached_does_not_walk() {
+    let target: Pos = (2, 2);
+    let mut builder = open_scene();
+    for dir in Dir::ALL {
+        builder = builder.with_tile(target + dir, Tile::WallFull);
+    }
+    let mut state = builder.build();
+    assert_eq!(state.path_to(target, 100), None);
+    state.on_input(Input::ClickTile(target));
+    assert!(!state.is_walking());
+    assert_eq!(state.message, "You can't find a way there.");
+    assert_eq!(state.player_pos(), (0, 0));
+}
+
+#[test]
+fn walking_stops_when_something_is_in_the_way() {
+    let mut state = open_scene().build();
+    state.on_input(Input::MoveTo((0, 3)));
+    state.walk_step();
+    state.set_tile((0, 2), Tile::WallFull);
+    state.walk_step();
+    assert!(!state.is_walking());
+    assert_eq!(state.message, "Something is in the way.");
+    assert_eq!(state.player_pos(), (0, 1));
+}
+
+#[test]
+fn walking_by_hand_stops_walking_by_itself() {
+    let mut state = open_scene().build();
+    state.on_input(Input::MoveTo((0, 3)));
+    press(&mut state, Dir::Left);
+    assert!(!state.is_walking());
+}
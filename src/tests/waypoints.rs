@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use super::open_scene;
+use crate::{Dir, Input, Pos, State, StateBuilder, Tile};
+
+fn waypoints(list: &[(&str, Pos)]) -> BTreeMap<String, Pos> {
+    list.iter().map(|&(name, pos)| (name.to_string(), pos)).collect()
+}
+
+/// Goes to the waypoint first in the list, from the menu.
+fn go_to_first(state: &mut State) {
+    state.on_input(Input::OpenWaypoints);
+    state.on_input(Input::Confirm);
+}
+
+#[test]
+fn using_a_waystone_saves_it_once() {
+    let mut state = open_scene()
+        .with_tile((0, 1), Tile::Waystone)
+        .with_tile((-1, 0), Tile::Waystone)
+        .build();
+    state.on_input(Input::Interact);
+    assert_eq!(state.waypoints, waypoints(&[("Waypoint 1", (0, 1))]));
+    assert!(state.message.starts_with("Saved this waystone as Waypoint 1."));
+    state.on_input(Input::Interact);
+    assert_eq!(state.waypoints.len(), 1);
+    assert_eq!(state.message, "This waystone is Waypoint 1.");
+    // Another one gets the next name.
+    state.player_dir = Dir::Left;
+    state.on_input(Input::Interact);
+    let expected = waypoints(&[("Waypoint 1", (0, 1)), ("Waypoint 2", (-1, 0))]);
+    assert_eq!(state.waypoints, expected);
+}
+
+#[test]
+fn going_to_an_enclosed_waystone_finds_the_closest_room() {
+    // Walls all around the waystone, but for a tile two to its right.
+    let mut builder = StateBuilder::new();
+    for y in 8..=12 {
+        for x in 8..=12 {
+            builder = builder.with_tile((x, y), Tile::WallFull);
+        }
+    }
+    let mut state = builder
+        .with_tile((10, 10), Tile::Waystone)
+        .with_tile((12, 10), Tile::Empty)
+        .build();
+    state.waypoints = waypoints(&[("Home", (10, 10))]);
+    go_to_first(&mut state);
+    assert_eq!(state.player_pos(), (12, 10));
+    assert_eq!(state.message, "You went to Home.");
+}
+
+#[test]
+fn going_to_a_broken_waystone_forgets_it() {
+    let mut state = open_scene()
+        .with_tile((2, 2), Tile::Waystone)
+        .with_tile((-2, -2), Tile::Waystone)
+        .build();
+    state.waypoints = waypoints(&[("A", (2, 2)), ("B", (-2, -2)), ("C", (3, 3))]);
+    state.set_tile((2, 2), Tile::Empty);
+    go_to_first(&mut state);
+    assert_eq!(state.message, "The waystone of A is gone.");
+    assert_eq!(state.player_pos(), (0, 0));
+    // Every waypoint without its waystone is gone, not just the one chosen.
+    assert_eq!(state.waypoints, waypoints(&[("B", (-2, -2))]));
+}
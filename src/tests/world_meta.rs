@@ -0,0 +1,98 @@
+use super::{open_scene, press};
+use crate::meta::{Difficulty, WorldMeta};
+use crate::{
+    Dir, HUNGER_TICKS, Input, MAX_HUNGER, RenderOptions, State, StateBuilder, Tile, render_frame,
+};
+
+/// Saves `state` as TOML, and loads it back after changing the save with
+/// `edit`.
+fn reloaded(state: &State, edit: impl FnOnce(&mut toml::Table)) -> State {
+    let mut save: toml::Table = toml::from_str(&toml::to_string(state).unwrap()).unwrap();
+    edit(&mut save);
+    toml::from_str(&toml::to_string(&save).unwrap()).unwrap()
+}
+
+fn cheat(state: &mut State) {
+    state.on_input(Input::Command("fog".to_string()));
+}
+
+#[test]
+fn old_saves_get_the_default_meta() {
+    let mut state = StateBuilder::new().build();
+    state.meta_mut().name = "Home".to_string();
+    state.meta_mut().difficulty = Difficulty::Hard;
+    let old = reloaded(&state, |save| drop(save.remove("meta")));
+    assert_eq!(old.meta(), &WorldMeta::default());
+    assert_eq!(old.meta().difficulty, Difficulty::Normal);
+    // Or only some of it, from a newer save.
+    let newer = reloaded(&state, |save| {
+        save["meta"].as_table_mut().unwrap().remove("difficulty");
+    });
+    assert_eq!(newer.meta().name, "Home");
+    assert_eq!(newer.meta().difficulty, Difficulty::Normal);
+}
+
+#[test]
+fn cheating_is_remembered_in_the_save() {
+    let mut state = StateBuilder::new().build();
+    assert!(!state.meta().cheats_used);
+    cheat(&mut state);
+    assert!(state.meta().cheats_used);
+    let mut state = reloaded(&state, |_| ());
+    assert!(state.meta().cheats_used);
+    // Turning fog back on is not playing fairly again.
+    cheat(&mut state);
+    assert!(state.meta().cheats_used);
+}
+
+#[test]
+fn the_stats_say_if_cheats_were_used() {
+    let stats = |state: &State| render_frame(state, 100, 40, &RenderOptions::default()).text();
+    let mut state = StateBuilder::new().build();
+    state.on_input(Input::Command("stats".to_string()));
+    assert!(stats(&state).contains("Difficulty: Normal"));
+    assert!(!stats(&state).contains("(cheats used)"));
+    state.meta_mut().cheats_used = true;
+    assert!(stats(&state).contains("Difficulty: Normal (cheats used)"));
+}
+
+fn at(difficulty: Difficulty, builder: StateBuilder) -> State {
+    let mut state = builder.build();
+    state.meta_mut().difficulty = difficulty;
+    state
+}
+
+#[test]
+fn hard_walls_take_two_hits_a_stage() {
+    let by_a_wall = || open_scene().facing(Dir::Right).with_tile((1, 0), Tile::WallFull);
+    let mut normal = at(Difficulty::Normal, by_a_wall());
+    press(&mut normal, Dir::Right);
+    assert_eq!(normal.get_tile((1, 0)), Tile::WallHalf);
+    let mut hard = at(Difficulty::Hard, by_a_wall());
+    press(&mut hard, Dir::Right);
+    assert_eq!(hard.get_tile((1, 0)), Tile::WallFull);
+    press(&mut hard, Dir::Right);
+    assert_eq!(hard.get_tile((1, 0)), Tile::WallHalf);
+}
+
+/// How much hungrier the player got at `difficulty`, after `ticks`.
+fn hunger_lost(difficulty: Difficulty, ticks: u64) -> u8 {
+    let mut state = at(difficulty, StateBuilder::new());
+    assert_eq!(state.hunger(), MAX_HUNGER);
+    for _ in 0..ticks {
+        state.tick();
+    }
+    MAX_HUNGER - state.hunger()
+}
+
+#[test]
+fn peaceful_players_never_get_hungry() {
+    assert_eq!(hunger_lost(Difficulty::Peaceful, HUNGER_TICKS * 3), 0);
+    assert_eq!(hunger_lost(Difficulty::Normal, HUNGER_TICKS * 3), 3);
+}
+
+#[test]
+fn hard_players_get_hungry_twice_as_fast() {
+    assert_eq!(hunger_lost(Difficulty::Normal, HUNGER_TICKS), 1);
+    assert_eq!(hunger_lost(Difficulty::Hard, HUNGER_TICKS), 2);
+}
@@ -1,5 +1,8 @@
-use crate::Item;
-use serde::{Deserialize, Serialize};
+use crate::{Item, Language, Rules, Weather};
+use crate::items::WoodKind;
+use crate::light::MAX_LIGHT;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Tile {
@@ -7,13 +10,69 @@ pub enum Tile {
     WallFull,
     WallHalf,
     WallLow,
-    Wood(u8),
+    /// Wood, and how much of it is left, from 0 to `WOOD_MAX`. Every hit takes
+    /// one off, and hitting 0 breaks it. The same number picks how it is
+    /// drawn, so it must never be more than `WOOD_MAX`: make wood with
+    /// `Tile::wood`, which makes sure of that.
+    #[serde(deserialize_with = "deserialize_wood")]
+    Wood(WoodKind, u8),
+    /// Using a bed sets where the player respawns.
+    Bed,
+    /// Lights up the area around it at night.
+    Torch,
+    /// Falls down when there is nothing below it.
+    Sand,
+    /// Grows into a log after a while.
+    Sapling(WoodKind),
+    /// Can be walked on, but whoever steps on it slides. See `is_slippery`.
+    Ice,
+    /// Using it saves it as a waypoint, which the player can go back to.
+    Waystone,
+    /// Everything outside the bounds of a world that has them. Can't be
+    /// broken or walked through. See `State::bounds`.
+    Barrier,
+    /// Sells things for other things. See the `trades` module.
+    Trader,
+    /// A bush, and whether it has berries on it. Picking them leaves the bush
+    /// bare until they grow back.
+    Bush(bool),
+}
+
+/// The chance of a sapling growing into a log every tick. Twice that in the
+/// rain.
+const SAPLING_GROWTH_CHANCE: f64 = 0.01;
+/// The chance of the berries of a bare bush growing back every tick. Twice
+/// that in the rain.
+const BERRY_GROWTH_CHANCE: f64 = 0.02;
+
+/// The most a wood tile can have left. See `Tile::Wood`.
+pub const WOOD_MAX: u8 = 3;
+
+/// Wood used to be saved without its kind, as just a number. Values above
+/// `WOOD_MAX` (placed wood used to start at 5) are brought down to it.
+fn deserialize_wood<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(WoodKind, u8), D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Wood {
+        WithKind(WoodKind, u8),
+        Old(u8),
+    }
+    let (kind, n) = match Wood::deserialize(deserializer)? {
+        Wood::WithKind(kind, n) => (kind, n),
+        Wood::Old(n) => (WoodKind::default(), n),
+    };
+    // Like `Tile::wood`, so a bad save can't break the invariant.
+    Ok((kind, n.min(WOOD_MAX)))
 }
 
 /// What does a tile break into?
 pub enum BreakResult {
     Tile(Tile),
     Item(Item),
+    /// Gives an item, but leaves a tile behind, like a bush that was picked.
+    Harvest(Tile, Item),
     CannotBeBroken,
 }
 
@@ -29,27 +88,363 @@ impl From<Item> for BreakResult {
     }
 }
 
+/// What happens when the player uses a tile? See `Tile::on_interact`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interaction {
+    /// The tile turns into another one.
+    Become(Tile),
+    /// The player will respawn next to the tile.
+    SetSpawn,
+    /// The tile is saved as a waypoint, to go back to.
+    SaveWaypoint,
+    /// The offers of the trader are shown.
+    Trade,
+    /// Nothing happens.
+    Nothing,
+}
+
 impl Tile {
+    /// Every tile there is, with every stage of wood.
+    pub const ALL: &[Tile] = &[
+        Tile::Empty,
+        Tile::WallFull,
+        Tile::WallHalf,
+        Tile::WallLow,
+        Tile::Wood(WoodKind::Oak, 0),
+        Tile::Wood(WoodKind::Oak, 1),
+        Tile::Wood(WoodKind::Oak, 2),
+        Tile::Wood(WoodKind::Oak, WOOD_MAX),
+        Tile::Wood(WoodKind::Birch, 0),
+        Tile::Wood(WoodKind::Birch, 1),
+        Tile::Wood(WoodKind::Birch, 2),
+        Tile::Wood(WoodKind::Birch, WOOD_MAX),
+        Tile::Bed,
+        Tile::Torch,
+        Tile::Sand,
+        Tile::Sapling(WoodKind::Oak),
+        Tile::Sapling(WoodKind::Birch),
+        Tile::Ice,
+        Tile::Waystone,
+        Tile::Barrier,
+        Tile::Trader,
+        Tile::Bush(true),
+        Tile::Bush(false),
+    ];
+
+    /// The tile with this name in any language. Ignores case. The opposite
+    /// of `name`.
+    pub fn from_name(name: &str) -> Option<Tile> {
+        // Lowercase and not `eq_ignore_ascii_case`, for names like "vacío".
+        let name = name.trim().to_lowercase();
+        let is_named =
+            |tile: &Tile| Language::ALL.iter().any(|&lang| tile.name(lang).to_lowercase() == name);
+        Tile::ALL.iter().copied().find(is_named)
+    }
+
+    /// Wood with `stage` left, brought down to `WOOD_MAX` if it is more. See
+    /// `Tile::Wood`.
+    pub const fn wood(kind: WoodKind, stage: u8) -> Tile {
+        Tile::Wood(kind, if stage > WOOD_MAX { WOOD_MAX } else { stage })
+    }
+
     /// What does this tile break into?
     pub fn breaks_into(self) -> BreakResult {
         match self {
             Tile::WallFull => Tile::WallHalf.into(),
             Tile::WallHalf => Tile::WallLow.into(),
             Tile::WallLow => Item::Wall.into(),
-            Tile::Empty => BreakResult::CannotBeBroken,
-            Tile::Wood(0) => Item::Wood.into(),
-            Tile::Wood(n) => Tile::Wood(n - 1).into(),
+            Tile::Empty | Tile::Barrier | Tile::Trader => BreakResult::CannotBeBroken,
+            Tile::Wood(kind, 0) => Item::Wood(kind).into(),
+            Tile::Wood(kind, n) => Tile::wood(kind, n - 1).into(),
+            Tile::Bed => Item::Bed.into(),
+            Tile::Torch => Item::Torch.into(),
+            Tile::Sand => Item::Sand.into(),
+            Tile::Sapling(kind) => Item::Sapling(kind).into(),
+            Tile::Ice => Tile::Empty.into(),
+            Tile::Waystone => Item::Waystone.into(),
+            Tile::Bush(true) => BreakResult::Harvest(Tile::Bush(false), Item::Berry),
+            Tile::Bush(false) => Tile::Empty.into(),
+        }
+    }
+
+    /// How many times the player digs at this tile before it breaks (into
+    /// what `breaks_into` says). 0 if it can't be broken. Tools are meant to
+    /// make this faster, but there are no tools yet.
+    pub const fn hits_to_break(&self, tool: Option<&Item>, rules: &Rules) -> u8 {
+        let _ = tool;
+        match self {
+            Tile::Empty | Tile::Barrier | Tile::Trader => 0,
+            // These already break a stage at a time.
+            Tile::WallFull | Tile::WallHalf | Tile::WallLow => rules.wall_hits,
+            Tile::Wood(..) => 1,
+            Tile::Torch | Tile::Sand | Tile::Sapling(_) | Tile::Ice | Tile::Bush(_) => 1,
+            Tile::Bed => 2,
+            Tile::Waystone => 3,
+        }
+    }
+
+    /// Can the player (or anything else) walk through this tile?
+    ///
+    /// NOTE: Keep these matches without a wildcard, so every new tile has to
+    /// decide.
+    pub const fn is_passable(&self) -> bool {
+        match self {
+            Tile::Empty | Tile::Ice => true,
+            Tile::WallFull
+            | Tile::WallHalf
+            | Tile::WallLow
+            | Tile::Wood(..)
+            | Tile::Bed
+            | Tile::Torch
+            | Tile::Sand
+            | Tile::Sapling(_)
+            | Tile::Waystone
+            | Tile::Barrier
+            | Tile::Trader
+            | Tile::Bush(_) => false,
+        }
+    }
+
+    /// Is this tile too tall to see past?
+    pub const fn blocks_sight(&self) -> bool {
+        match self {
+            Tile::WallFull | Tile::WallHalf | Tile::Wood(..) | Tile::Sand | Tile::Barrier => true,
+            Tile::Empty
+            | Tile::WallLow
+            | Tile::Bed
+            | Tile::Torch
+            | Tile::Sapling(_)
+            | Tile::Ice
+            | Tile::Waystone
+            | Tile::Trader
+            | Tile::Bush(_) => false,
+        }
+    }
+
+    /// Does this tile fall down when there is nothing below it?
+    pub const fn has_gravity(&self) -> bool {
+        matches!(self, Tile::Sand)
+    }
+
+    /// Does the player keep sliding after stepping on this tile?
+    pub const fn is_slippery(&self) -> bool {
+        matches!(self, Tile::Ice)
+    }
+
+    /// Do rocks sometimes come out of this tile when it is hit?
+    pub const fn drops_rocks(&self) -> bool {
+        matches!(self, Tile::WallFull | Tile::WallHalf | Tile::WallLow)
+    }
+
+    /// How much light this tile gives off. See the `light` module.
+    pub const fn light_emission(&self) -> u8 {
+        match self {
+            Tile::Torch => MAX_LIGHT,
+            Tile::Empty
+            | Tile::WallFull
+            | Tile::WallHalf
+            | Tile::WallLow
+            | Tile::Wood(..)
+            | Tile::Bed
+            | Tile::Sand
+            | Tile::Sapling(_)
+            | Tile::Ice
+            | Tile::Waystone
+            | Tile::Barrier
+            | Tile::Trader
+            | Tile::Bush(_) => 0,
         }
     }
 
-    pub const fn name(&self) -> &'static str {
+    /// What happens when the player uses this tile. New tiles that do
+    /// something when used say what here, and `State` does it.
+    pub const fn on_interact(self) -> Interaction {
         match self {
-            Tile::Empty => "empty",
-            Tile::WallFull => "wall",
-            Tile::WallHalf => "broken wall",
-            Tile::WallLow => "very broken wall",
-            Tile::Wood(_) => "wood",
+            Tile::Bed => Interaction::SetSpawn,
+            Tile::Waystone => Interaction::SaveWaypoint,
+            Tile::Trader => Interaction::Trade,
+            Tile::Empty
+            | Tile::WallFull
+            | Tile::WallHalf
+            | Tile::WallLow
+            | Tile::Wood(..)
+            | Tile::Torch
+            | Tile::Sand
+            | Tile::Sapling(_)
+            | Tile::Ice
+            | Tile::Barrier
+            | Tile::Bush(_) => Interaction::Nothing,
         }
     }
+
+    /// Does this tile change over time? Only tiles that do are ticked, see
+    /// `tick`.
+    pub const fn needs_tick(&self) -> bool {
+        match self {
+            Tile::Sapling(_) | Tile::Bush(false) => true,
+            Tile::Empty
+            | Tile::WallFull
+            | Tile::WallHalf
+            | Tile::WallLow
+            | Tile::Wood(..)
+            | Tile::Bed
+            | Tile::Torch
+            | Tile::Sand
+            | Tile::Ice
+            | Tile::Waystone
+            | Tile::Barrier
+            | Tile::Trader
+            | Tile::Bush(true) => false,
+        }
+    }
+
+    /// What this tile turns into after a tick. Only called for tiles that
+    /// `needs_tick`.
+    pub fn tick(self, rng: &mut impl Rng, weather: Weather) -> Tile {
+        let rain = match weather {
+            Weather::Clear => 1.0,
+            Weather::Rain => 2.0,
+        };
+        match self {
+            Tile::Sapling(kind) if rng.gen_bool(SAPLING_GROWTH_CHANCE * rain) => {
+                Tile::wood(kind, WOOD_MAX)
+            }
+            Tile::Bush(false) if rng.gen_bool(BERRY_GROWTH_CHANCE * rain) => Tile::Bush(true),
+            Tile::Sapling(_)
+            | Tile::Bush(_)
+            | Tile::Empty
+            | Tile::WallFull
+            | Tile::WallHalf
+            | Tile::WallLow
+            | Tile::Wood(..)
+            | Tile::Bed
+            | Tile::Torch
+            | Tile::Sand
+            | Tile::Ice
+            | Tile::Waystone
+            | Tile::Barrier
+            | Tile::Trader => self,
+        }
+    }
+
+    pub const fn name(&self, lang: Language) -> &'static str {
+        lang.tile_name(*self)
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_tile_is_found_by_its_names() {
+        for &tile in Tile::ALL {
+            for lang in Language::ALL {
+                assert_eq!(Tile::from_name(tile.name(lang)), Some(tile), "{lang:?}");
+                let shouted = tile.name(lang).to_uppercase();
+                assert_eq!(Tile::from_name(&format!(" {shouted} ")), Some(tile));
+            }
+        }
+        assert_eq!(Tile::from_name("no such tile"), None);
+    }
+
+    #[test]
+    fn what_can_be_walked_through_and_seen_past() {
+        for &tile in Tile::ALL {
+            // Whether it can be walked through, and whether it blocks sight.
+            let expected = match tile {
+                Tile::Empty | Tile::Ice => (true, false),
+                Tile::WallFull | Tile::WallHalf | Tile::Wood(..) | Tile::Sand | Tile::Barrier => {
+                    (false, true)
+                }
+                Tile::WallLow
+                | Tile::Bed
+                | Tile::Torch
+                | Tile::Sapling(_)
+                | Tile::Waystone
+                | Tile::Trader
+                | Tile::Bush(_) => (false, false),
+            };
+            assert_eq!((tile.is_passable(), tile.blocks_sight()), expected, "{tile:?}");
+        }
+    }
+
+    #[test]
+    fn wood_breaks_a_stage_a_hit_and_then_into_an_item() {
+        let mut tile = Item::Wood(WoodKind::Birch).to_tile().unwrap();
+        assert_eq!(tile, Tile::Wood(WoodKind::Birch, WOOD_MAX));
+        let mut names = vec![tile.name(Language::English)];
+        for stage in (0..WOOD_MAX).rev() {
+            let BreakResult::Tile(next) = tile.breaks_into() else {
+                panic!("{tile:?} broke into an item");
+            };
+            assert_eq!(next, Tile::Wood(WoodKind::Birch, stage));
+            tile = next;
+            names.push(tile.name(Language::English));
+        }
+        let expected = ["birch log", "chopped birch log", "split birch wood", "birch splinters"];
+        assert_eq!(names, expected);
+        assert!(matches!(tile.breaks_into(), BreakResult::Item(Item::Wood(WoodKind::Birch))));
+    }
+
+    #[test]
+    fn every_stage_of_wood_can_be_drawn_and_named() {
+        for stage in 0..=u8::MAX {
+            let tile = Tile::Wood(WoodKind::Oak, stage);
+            crate::render::draw_tile(tile);
+            assert!(!tile.name(Language::English).is_empty());
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Saved {
+        tile: Tile,
+    }
+
+    #[test]
+    fn too_much_wood_is_brought_down_when_loaded() {
+        let saved: Saved = toml::from_str("tile = { Wood = [\"Birch\", 200] }").unwrap();
+        assert_eq!(saved.tile, Tile::Wood(WoodKind::Birch, WOOD_MAX));
+        // From before there were kinds of wood.
+        let saved: Saved = toml::from_str("tile = { Wood = 5 }").unwrap();
+        assert_eq!(saved.tile, Tile::Wood(WoodKind::Oak, WOOD_MAX));
+        let saved: Saved = toml::from_str("tile = { Wood = 1 }").unwrap();
+        assert_eq!(saved.tile, Tile::Wood(WoodKind::Oak, 1));
+    }
+
+    #[test]
+    fn what_every_tile_does() {
+        for &tile in Tile::ALL {
+            // What using it does, whether it ticks, and how much light it
+            // gives.
+            let expected = match tile {
+                Tile::Bed => (Interaction::SetSpawn, false, 0),
+                Tile::Waystone => (Interaction::SaveWaypoint, false, 0),
+                Tile::Trader => (Interaction::Trade, false, 0),
+                Tile::Torch => (Interaction::Nothing, false, MAX_LIGHT),
+                Tile::Sapling(_) | Tile::Bush(false) => (Interaction::Nothing, true, 0),
+                Tile::Empty
+                | Tile::WallFull
+                | Tile::WallHalf
+                | Tile::WallLow
+                | Tile::Wood(..)
+                | Tile::Sand
+                | Tile::Ice
+                | Tile::Barrier
+                | Tile::Bush(true) => (Interaction::Nothing, false, 0),
+            };
+            let got = (tile.on_interact(), tile.needs_tick(), tile.light_emission());
+            assert_eq!(got, expected, "{tile:?}");
+        }
+    }
+
+    #[test]
+    fn only_tiles_that_need_a_tick_change_in_one() {
+        for &tile in Tile::ALL {
+            let mut rng = crate::random::hash_rng(0, (0, 0), 0, 0);
+            let changed = (0..1000).any(|_| tile.tick(&mut rng, Weather::Rain) != tile);
+            assert_eq!(changed, tile.needs_tick(), "{tile:?}");
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! What traders sell. Every trader has a few of the offers in `TRADES`,
+//! picked by where it is, so different traders sell different things but a
+//! trader always sells the same.
+
+use rand::Rng;
+
+use crate::items::WoodKind;
+use crate::random::{hash_rng, salt};
+use crate::{Item, Pos};
+
+/// An offer of a trader: some of one item for some of another.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Trade {
+    /// What the player gives, and how many.
+    pub price: (Item, usize),
+    /// What the player gets for it, and how many.
+    pub goods: (Item, usize),
+}
+
+/// Every offer a trader can have.
+pub const TRADES: &[Trade] = &[
+    Trade { price: (Item::Wood(WoodKind::Oak), 8), goods: (Item::Waystone, 1) },
+    Trade { price: (Item::Wall, 16), goods: (Item::Compass, 1) },
+    Trade { price: (Item::Rock, 4), goods: (Item::Torch, 2) },
+    Trade { price: (Item::Sand, 12), goods: (Item::Bed, 1) },
+    Trade { price: (Item::Wall, 10), goods: (Item::Wood(WoodKind::Birch), 4) },
+    Trade { price: (Item::Wood(WoodKind::Birch), 6), goods: (Item::Sapling(WoodKind::Oak), 2) },
+    Trade { price: (Item::Torch, 2), goods: (Item::Sapling(WoodKind::Birch), 1) },
+];
+
+/// How many offers every trader has.
+pub const OFFERS_PER_TRADER: usize = 3;
+
+/// The offers of the trader at `pos`, in a world with this seed.
+pub fn offers_at(seed: u32, pos: Pos) -> Vec<&'static Trade> {
+    let mut rng = hash_rng(seed as u64, pos, 0, salt::TRADE_OFFERS);
+    let mut left: Vec<&Trade> = TRADES.iter().collect();
+    (0..OFFERS_PER_TRADER)
+        .map(|_| left.remove(rng.gen_range(0..left.len())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_trader_always_has_the_same_offers() {
+        for pos in [(0, 0), (5, -3), (-100, 42)] {
+            assert_eq!(offers_at(1, pos), offers_at(1, pos));
+        }
+    }
+
+    #[test]
+    fn traders_have_different_offers() {
+        let first = offers_at(1, (0, 0));
+        assert!((1..20).any(|x| offers_at(1, (x, 0)) != first));
+        assert!((2..20).any(|seed| offers_at(seed, (0, 0)) != first));
+    }
+
+    #[test]
+    fn a_trader_has_no_offer_twice() {
+        let offers = offers_at(1, (3, 3));
+        assert_eq!(offers.len(), OFFERS_PER_TRADER);
+        for (i, offer) in offers.iter().enumerate() {
+            assert!(!offers[i + 1..].contains(offer));
+        }
+    }
+}
@@ -10,6 +10,40 @@ pub enum Dir {
     Right,
 }
 
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+
+    pub const fn opposite(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Down => Dir::Up,
+            Dir::Left => Dir::Right,
+            Dir::Right => Dir::Left,
+        }
+    }
+
+    /// The two directions at a right angle to this one.
+    pub const fn sides(self) -> [Dir; 2] {
+        match self {
+            Dir::Up | Dir::Down => [Dir::Left, Dir::Right],
+            Dir::Left | Dir::Right => [Dir::Up, Dir::Down],
+        }
+    }
+
+    /// The direction that mostly points from one position to another, or
+    /// `None` if they are the same position.
+    pub fn towards(from: Pos, to: Pos) -> Option<Dir> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        if (dx, dy) == (0, 0) {
+            None
+        } else if dx.abs() >= dy.abs() {
+            Some(if dx > 0 { Dir::Right } else { Dir::Left })
+        } else {
+            Some(if dy > 0 { Dir::Down } else { Dir::Up })
+        }
+    }
+}
+
 impl std::ops::Add<Dir> for Pos {
     type Output = Self;
 
@@ -0,0 +1,69 @@
+//! Weather, which changes every now and then. Rain makes it darker and
+//! saplings grow faster.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::random::{hash_rng, salt};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+}
+
+/// The chance every tick that clear weather turns to rain. About once every
+/// few days.
+const RAIN_CHANCE: f64 = 1.0 / 500.0;
+/// The chance every tick that rain stops. Rain doesn't last as long as clear
+/// weather does.
+const CLEAR_CHANCE: f64 = 1.0 / 150.0;
+
+/// The weather after `current` at a tick. Always the same for the same
+/// world and tick, so a world plays the same way every time.
+pub fn next_weather(current: Weather, tick: u64, seed: u32) -> Weather {
+    // The weather is the same everywhere, so it is not random by position.
+    let mut rng = hash_rng(seed as u64, (0, 0), tick, salt::WEATHER);
+    match current {
+        Weather::Clear if rng.gen_bool(RAIN_CHANCE) => Weather::Rain,
+        Weather::Rain if rng.gen_bool(CLEAR_CHANCE) => Weather::Clear,
+        Weather::Clear | Weather::Rain => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The weather at every tick from the first one, in a world.
+    fn forecast(seed: u32, ticks: u64) -> Vec<Weather> {
+        let mut weather = Weather::Clear;
+        (0..ticks)
+            .map(|tick| {
+                weather = next_weather(weather, tick, seed);
+                weather
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_same_world_has_the_same_weather() {
+        assert_eq!(forecast(7, 5000), forecast(7, 5000));
+        assert_ne!(forecast(7, 5000), forecast(8, 5000));
+    }
+
+    #[test]
+    fn rain_comes_every_now_and_then_and_is_shorter() {
+        let weather = forecast(7, 500_000);
+        // How long the weather stayed each time, by weather.
+        let lasted = |kind: Weather| {
+            let spells = weather.chunk_by(|a, b| a == b).filter(|spell| spell[0] == kind);
+            let lengths: Vec<usize> = spells.map(<[_]>::len).collect();
+            lengths.iter().sum::<usize>() / lengths.len()
+        };
+        let (clear, rain) = (lasted(Weather::Clear), lasted(Weather::Rain));
+        assert!((350..700).contains(&clear), "Clear for {clear} ticks");
+        assert!((100..200).contains(&rain), "Rain for {rain} ticks");
+    }
+}
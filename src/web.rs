@@ -0,0 +1,290 @@
+//! Playing in a browser, as WebAssembly.
+//!
+//! A browser can't wait for input, so `start_game` can't run here. Instead,
+//! the page calls `push_key` for every key the player presses, which plays
+//! one step of the game, `walk` every so often for the player to walk by
+//! themselves, and `render` to get the screen to show.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+
+use crate::game_loop::replace_world;
+use crate::render::{self, Chars};
+use crate::{Dir, Input, IsShift, LoadResult, Outcome, Platform, State};
+
+/// How many tiles wide and tall the world is drawn.
+const VIEW_SIZE: (i32, i32) = (40, 20);
+
+/// Where the game is kept in the browser's `localStorage`.
+const SAVE_KEY: &str = "minecraft-2d/save";
+/// Where a save that could not be loaded is moved to.
+const CORRUPT_SAVE_KEY: &str = "minecraft-2d/save.corrupt";
+
+pub struct WebPlatform {
+    /// Keys the page pushed that the game did not handle yet.
+    keys: VecDeque<Input>,
+    /// The screen, as it was last drawn. See `render`.
+    screen: String,
+}
+
+thread_local! {
+    static GAME: RefCell<Option<(WebPlatform, State)>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the game, loading it first if this is the first time.
+fn with_game<T>(f: impl FnOnce(&mut WebPlatform, &mut State) -> T) -> Result<T, JsValue> {
+    GAME.with_borrow_mut(|game| {
+        if game.is_none() {
+            let mut p = WebPlatform::new();
+            let state = p.load_or_start()?;
+            *game = Some((p, state));
+        }
+        let (p, state) = game.as_mut().expect("We just loaded it");
+        Ok(f(p, state))
+    })
+}
+
+/// Handles a key the player pressed, by its name in `KeyboardEvent.key`.
+/// Keys that do nothing are ignored.
+#[wasm_bindgen]
+pub fn push_key(key: &str) -> Result<(), JsValue> {
+    with_game(|p, state| {
+        p.keys.extend(key_to_input(key));
+        while let Some(input) = p.ask_for_input()? {
+            p.step(state, input)?;
+        }
+        Ok(())
+    })?
+}
+
+/// Takes the next step of walking by themselves, if the player is (see
+/// `State::is_walking`). The page calls this every 100 milliseconds, which is
+/// how often `start_game` takes a step. Returns whether the player stepped,
+/// so the screen should be drawn again.
+#[wasm_bindgen]
+pub fn walk() -> Result<bool, JsValue> {
+    with_game(|p, state| {
+        if !state.is_walking() {
+            return Ok(false);
+        }
+        state.walk_step();
+        for event in state.take_events() {
+            p.notify(event)?;
+        }
+        p.save(state)?;
+        Ok(true)
+    })?
+}
+
+/// The screen as text, one line for every row. Every tile is two
+/// characters, like in the terminal.
+#[wasm_bindgen]
+pub fn render() -> Result<String, JsValue> {
+    with_game(|p, state| {
+        p.draw(state)?;
+        Ok(p.screen.clone())
+    })?
+}
+
+fn key_to_input(key: &str) -> Option<Input> {
+    Some(match key {
+        "w" | "ArrowUp" => Input::Dir(Dir::Up, IsShift::No),
+        "s" | "ArrowDown" => Input::Dir(Dir::Down, IsShift::No),
+        "a" | "ArrowLeft" => Input::Dir(Dir::Left, IsShift::No),
+        "d" | "ArrowRight" => Input::Dir(Dir::Right, IsShift::No),
+        "W" => Input::Dir(Dir::Up, IsShift::Yes),
+        "S" => Input::Dir(Dir::Down, IsShift::Yes),
+        "A" => Input::Dir(Dir::Left, IsShift::Yes),
+        "D" => Input::Dir(Dir::Right, IsShift::Yes),
+        "b" | "B" => Input::Build,
+        "e" | "E" => Input::Interact,
+        "f" | "F" => Input::Throw,
+        "r" | "R" => Input::Eat,
+        "i" | "I" => Input::OpenInventory,
+        "n" | "N" => Input::OpenWaypoints,
+        "z" | "Z" => Input::CycleZoom,
+        "p" | "P" => Input::ToggleAutoPlace,
+        "m" | "M" => Input::ToggleLog,
+        "v" | "V" => Input::CycleVariant,
+        "q" => Input::Quit,
+        // The command itself is typed in a prompt. See `ask_for_input`.
+        "/" => Input::Command(String::new()),
+        "Escape" => Input::CloseMenu,
+        "Enter" => Input::Confirm,
+        _ => return None,
+    })
+}
+
+fn storage() -> Result<web_sys::Storage, JsValue> {
+    let window = web_sys::window().ok_or("There is no window")?;
+    window.local_storage()?.ok_or_else(|| "There is no localStorage".into())
+}
+
+impl WebPlatform {
+    fn new() -> Self {
+        Self {
+            keys: VecDeque::new(),
+            screen: String::new(),
+        }
+    }
+
+    fn load_or_start(&mut self) -> Result<State, JsValue> {
+        self.init()?;
+        Ok(match self.load()? {
+            LoadResult::Loaded(state) => *state,
+            LoadResult::NoSave => State::new(),
+            LoadResult::Corrupt { reason } => {
+                let msg = format!("Your save could not be loaded ({reason}). Start a new world?");
+                if !self.confirm(&msg)? {
+                    return Err(reason.into());
+                }
+                self.back_up_save()?;
+                State::new()
+            }
+        })
+    }
+
+    /// Like one round of the loop in `start_game`.
+    fn step(&mut self, state: &mut State, input: Input) -> Result<(), JsValue> {
+        if let Input::Command(command) = &input
+            && let Some(message) = self.run_command(state, command)?
+        {
+            state.message = message;
+            return Ok(());
+        }
+        let outcome = state.on_input(input);
+        for event in state.take_events() {
+            self.notify(event)?;
+        }
+        // There is no telling when the page is closed, so we can't save
+        // every so often like `start_game` does. Instead, we save every time.
+        match outcome {
+            Outcome::Quit { save: false } => Ok(()),
+            Outcome::NewWorld => {
+                // There is no `SystemTime` in a browser, so the page tells
+                // the time.
+                let unix_time = (web_sys::js_sys::Date::now() / 1000.0) as u64;
+                replace_world(self, state, unix_time)?;
+                self.save(state)
+            }
+            // A page can't be quit, so the player just keeps playing.
+            Outcome::Continue | Outcome::Save | Outcome::Quit { save: true } => {
+                state.compact_tiles();
+                self.save(state)
+            }
+        }
+    }
+}
+
+impl Platform for WebPlatform {
+    type Error = JsValue;
+
+    fn init(&mut self) -> Result<(), JsValue> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), JsValue> {
+        Ok(())
+    }
+
+    fn ask_for_input(&mut self) -> Result<Option<Input>, JsValue> {
+        match self.keys.pop_front() {
+            Some(Input::Command(command)) if command.is_empty() => {
+                Ok(self.ask_for_text("Command:")?.map(Input::Command))
+            }
+            input => Ok(input),
+        }
+    }
+
+    /// A page can't wait, so this only takes a key the page already pushed.
+    /// Walking is timed by the page instead. See `walk`.
+    fn poll_input(&mut self, _timeout: Duration) -> Result<Option<Input>, JsValue> {
+        self.ask_for_input()
+    }
+
+    fn draw(&mut self, state: &State) -> Result<(), JsValue> {
+        let viewport = render::viewport(state, VIEW_SIZE.0, VIEW_SIZE.1);
+        self.screen.clear();
+        for row in render::world_cells(state, viewport) {
+            self.screen.extend(row.into_iter().flat_map(|c: Chars| [c.left, c.right]));
+            self.screen.push('\n');
+        }
+        self.screen.push_str(&state.message);
+        Ok(())
+    }
+
+    fn save(&mut self, state: &State) -> Result<(), JsValue> {
+        let text = toml::to_string(state).map_err(|e| e.to_string())?;
+        storage()?.set_item(SAVE_KEY, &text)
+    }
+
+    fn load(&mut self) -> Result<LoadResult, JsValue> {
+        let Some(text) = storage()?.get_item(SAVE_KEY)? else {
+            return Ok(LoadResult::NoSave);
+        };
+        Ok(match toml::from_str(&text) {
+            Ok(state) => LoadResult::Loaded(Box::new(state)),
+            Err(e) => LoadResult::Corrupt {
+                reason: e.message().to_string(),
+            },
+        })
+    }
+
+    fn back_up_save(&mut self) -> Result<(), JsValue> {
+        let storage = storage()?;
+        if let Some(text) = storage.get_item(SAVE_KEY)? {
+            storage.set_item(CORRUPT_SAVE_KEY, &text)?;
+        }
+        storage.remove_item(SAVE_KEY)
+    }
+
+    fn confirm(&mut self, msg: &str) -> Result<bool, JsValue> {
+        let window = web_sys::window().ok_or("There is no window")?;
+        window.confirm_with_message(msg)
+    }
+
+    fn run_command(&mut self, _state: &State, _command: &str) -> Result<Option<String>, JsValue> {
+        // Nothing the browser does that the game can't.
+        Ok(None)
+    }
+
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, JsValue> {
+        let window = web_sys::window().ok_or("There is no window")?;
+        let text = window.prompt_with_message(prompt)?;
+        Ok(text.filter(|text| !text.trim().is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StateBuilder;
+
+    #[test]
+    fn the_screen_is_the_view_and_then_the_message() {
+        let mut state = StateBuilder::new().facing(Dir::Left).build();
+        state.message = "Hello".to_string();
+        let mut p = WebPlatform::new();
+        p.draw(&state).unwrap();
+        let lines: Vec<&str> = p.screen.lines().collect();
+        assert_eq!(lines.len(), VIEW_SIZE.1 as usize + 1);
+        for line in &lines[..VIEW_SIZE.1 as usize] {
+            assert_eq!(line.chars().count(), 2 * VIEW_SIZE.0 as usize);
+        }
+        assert_eq!(lines[VIEW_SIZE.1 as usize], "Hello");
+    }
+
+    #[test]
+    fn the_player_is_in_the_middle_of_the_screen() {
+        let state = StateBuilder::new().facing(Dir::Left).build();
+        let mut p = WebPlatform::new();
+        p.draw(&state).unwrap();
+        let row = p.screen.lines().nth(VIEW_SIZE.1 as usize / 2).unwrap();
+        let cell: String = row.chars().skip(VIEW_SIZE.0 as usize).take(2).collect();
+        let player = render::player(Dir::Left);
+        assert_eq!(cell, String::from_iter([player.left, player.right]));
+    }
+}
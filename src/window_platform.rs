@@ -0,0 +1,303 @@
+//! Playing in a window of its own, instead of in a terminal.
+//!
+//! Every tile is a square of colors, drawn with the same colors as in the
+//! terminal (see `render`). There is no text in the window, so messages and
+//! menus are shown in its title.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use minifb::{InputCallback, Key, KeyRepeat, Window, WindowOptions};
+
+use crate::render::{self, Chars};
+use crate::{Dir, FileSaveStore, GameError, Input, IsShift, LoadResult, Menu, Platform, State};
+
+/// How many pixels wide and tall every tile is.
+pub const TILE_SIZE: usize = 16;
+/// How many tiles wide and tall the window is.
+const VIEW_SIZE: (usize, usize) = (40, 25);
+
+const TITLE: &str = "minecraft-2d";
+
+/// Plays the game in a window. Saves like `TerminalPlatform` does, so the
+/// same world can be played in both.
+pub struct WindowPlatform {
+    /// `None` until `init`.
+    window: Option<Window>,
+    store: FileSaveStore,
+    /// The characters typed since this was last cleared. See `ask_for_text`.
+    typed: Rc<RefCell<String>>,
+}
+
+/// Collects what is typed in the window.
+struct Typed(Rc<RefCell<String>>);
+
+impl InputCallback for Typed {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char).filter(|c| !c.is_control()) {
+            self.0.borrow_mut().push(c);
+        }
+    }
+}
+
+impl WindowPlatform {
+    pub fn new(store: FileSaveStore) -> Self {
+        Self {
+            window: None,
+            store,
+            typed: Rc::default(),
+        }
+    }
+
+    fn window(&mut self) -> &mut Window {
+        self.window.as_mut().expect("The window is made in `init`")
+    }
+
+    /// Waits for the next keys that are pressed, but no longer than
+    /// `timeout` if there is one (then there might be no keys). If the
+    /// window is closed, returns `None`.
+    fn wait_for_keys(&mut self, timeout: Option<Duration>) -> Option<Vec<Key>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let window = self.window();
+        while window.is_open() {
+            window.update();
+            let keys = window.get_keys_pressed(KeyRepeat::Yes);
+            if !keys.is_empty() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Some(keys);
+            }
+        }
+        None
+    }
+
+    /// Reads the input of the next keys that are pressed. See
+    /// `wait_for_keys`.
+    fn read_input(&mut self, timeout: Option<Duration>) -> Result<Option<Input>, GameError> {
+        let Some(keys) = self.wait_for_keys(timeout) else {
+            return Ok(Some(Input::Quit));
+        };
+        // Only text prompts need what was typed.
+        self.typed.borrow_mut().clear();
+        let window = self.window();
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let alt = window.is_key_down(Key::LeftAlt) || window.is_key_down(Key::RightAlt);
+        match keys.into_iter().find_map(|key| key_to_input(key, shift, ctrl, alt)) {
+            Some(Input::Command(command)) if command.is_empty() => {
+                Ok(self.ask_for_text("Command:")?.map(Input::Command))
+            }
+            input => Ok(input),
+        }
+    }
+}
+
+fn key_to_input(key: Key, shift: bool, ctrl: bool, alt: bool) -> Option<Input> {
+    let dir = match key {
+        Key::W | Key::Up => Some(Dir::Up),
+        Key::S | Key::Down => Some(Dir::Down),
+        Key::A | Key::Left => Some(Dir::Left),
+        Key::D | Key::Right => Some(Dir::Right),
+        _ => None,
+    };
+    if let Some(dir) = dir {
+        return Some(match (ctrl, alt, shift) {
+            (true, _, _) => Input::Run(dir),
+            (false, true, _) => Input::Look(dir),
+            (false, false, true) => Input::Dir(dir, IsShift::Yes),
+            (false, false, false) => Input::Dir(dir, IsShift::No),
+        });
+    }
+    Some(match key {
+        Key::B => Input::Build,
+        Key::E => Input::Interact,
+        Key::F => Input::Throw,
+        Key::R => Input::Eat,
+        Key::I => Input::OpenInventory,
+        Key::N => Input::OpenWaypoints,
+        Key::Z => Input::CycleZoom,
+        Key::P => Input::ToggleAutoPlace,
+        Key::M => Input::ToggleLog,
+        Key::V => Input::CycleVariant,
+        Key::Q => Input::Quit,
+        Key::C if ctrl => Input::Quit,
+        // The command itself is typed in a prompt. See `ask_for_input`.
+        Key::Slash => Input::Command(String::new()),
+        Key::Escape => Input::CloseMenu,
+        Key::Enter => Input::Confirm,
+        _ => return None,
+    })
+}
+
+/// Turns rows of cells (see `render::world_cells`) into the pixels of an
+/// image that is `TILE_SIZE` pixels for every cell, row by row, as `0RGB`.
+/// The left and right halves of a tile are the colors of its two characters.
+pub fn rasterize(cells: &[Vec<Chars>]) -> Vec<u32> {
+    let width = cells.first().map_or(0, Vec::len) * TILE_SIZE;
+    let mut pixels = Vec::with_capacity(width * cells.len() * TILE_SIZE);
+    for row in cells {
+        let colors: Vec<[u32; 2]> = row.iter().map(|chars| chars.rgb().map(pixel)).collect();
+        for _ in 0..TILE_SIZE {
+            for [left, right] in &colors {
+                pixels.extend([*left; TILE_SIZE / 2]);
+                pixels.extend([*right; TILE_SIZE - TILE_SIZE / 2]);
+            }
+        }
+    }
+    pixels
+}
+
+fn pixel([r, g, b]: [u8; 3]) -> u32 {
+    u32::from_be_bytes([0, r, g, b])
+}
+
+/// What to show in the title of the window.
+fn title(state: &State) -> String {
+    match state.current_menu() {
+        Menu::None if state.message.is_empty() => TITLE.to_string(),
+        Menu::None => format!("{TITLE} - {}", state.message),
+        Menu::Inventory => match &state.selected_item {
+            Some(item) => {
+                let count = state.inventory.count_of(item);
+                format!("Inventory - {} ✗ {count}", item.name(state.language))
+            }
+            None => "Inventory - empty".to_string(),
+        },
+        Menu::Stats => "Stats".to_string(),
+        Menu::Pause(entry) => format!("Paused - {}", entry.name()),
+        Menu::Waypoints(index) => match state.waypoints().iter().nth(index) {
+            Some((name, (x, y))) => format!("Waypoints - {name} ({x}, {y})"),
+            None => "Waypoints".to_string(),
+        },
+        Menu::Trade { pos, offer } => match state.trade_offers(pos).get(offer) {
+            Some(trade) => {
+                let ((price, cost), (goods, count)) = (&trade.price, &trade.goods);
+                let (price, goods) = (price.name(state.language), goods.name(state.language));
+                format!("Trader - {cost} {price} for {count} {goods}")
+            }
+            None => "Trader".to_string(),
+        },
+        Menu::Confirm { action, yes } => {
+            let answer = if yes { "Yes" } else { "No" };
+            format!("{} - {answer}", action.prompt())
+        }
+    }
+}
+
+impl Platform for WindowPlatform {
+    type Error = GameError;
+
+    fn init(&mut self) -> Result<(), GameError> {
+        let (width, height) = (VIEW_SIZE.0 * TILE_SIZE, VIEW_SIZE.1 * TILE_SIZE);
+        let mut window = Window::new(TITLE, width, height, WindowOptions::default())
+            .map_err(GameError::Window)?;
+        window.set_target_fps(60);
+        window.set_input_callback(Box::new(Typed(self.typed.clone())));
+        self.window = Some(window);
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), GameError> {
+        // Dropping the window closes it.
+        self.window = None;
+        Ok(())
+    }
+
+    fn ask_for_input(&mut self) -> Result<Option<Input>, GameError> {
+        self.read_input(None)
+    }
+
+    fn poll_input(&mut self, timeout: Duration) -> Result<Option<Input>, GameError> {
+        self.read_input(Some(timeout))
+    }
+
+    fn draw(&mut self, state: &State) -> Result<(), GameError> {
+        let viewport = render::viewport(state, VIEW_SIZE.0 as i32, VIEW_SIZE.1 as i32);
+        let pixels = rasterize(&render::world_cells(state, viewport));
+        let (width, height) = (VIEW_SIZE.0 * TILE_SIZE, VIEW_SIZE.1 * TILE_SIZE);
+        let window = self.window();
+        window.set_title(&title(state));
+        window
+            .update_with_buffer(&pixels, width, height)
+            .map_err(GameError::Window)?;
+        Ok(())
+    }
+
+    fn save(&mut self, state: &State) -> Result<(), GameError> {
+        Ok(self.store.save_state(state)?)
+    }
+
+    fn load(&mut self) -> Result<LoadResult, GameError> {
+        Ok(self.store.load()?)
+    }
+
+    fn back_up_save(&mut self) -> Result<(), GameError> {
+        Ok(self.store.back_up()?)
+    }
+
+    fn confirm(&mut self, msg: &str) -> Result<bool, GameError> {
+        self.window().set_title(&format!("{msg} (y/n)"));
+        while let Some(keys) = self.wait_for_keys(None) {
+            for key in keys {
+                match key {
+                    Key::Y => return Ok(true),
+                    Key::N | Key::Escape => return Ok(false),
+                    _ => (),
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn run_command(&mut self, _state: &State, _command: &str) -> Result<Option<String>, GameError> {
+        Ok(None)
+    }
+
+    fn ask_for_text(&mut self, prompt: &str) -> Result<Option<String>, GameError> {
+        self.typed.borrow_mut().clear();
+        loop {
+            let title = format!("{prompt} {}_", self.typed.borrow());
+            self.window().set_title(&title);
+            let Some(keys) = self.wait_for_keys(None) else {
+                return Ok(None);
+            };
+            for key in keys {
+                match key {
+                    Key::Enter => {
+                        let text = self.typed.take();
+                        return Ok(Some(text).filter(|text| !text.trim().is_empty()));
+                    }
+                    Key::Escape => return Ok(None),
+                    Key::Backspace => _ = self.typed.borrow_mut().pop(),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::render::Color;
+
+    #[test]
+    fn every_cell_is_a_tile_of_pixels() {
+        let cells = vec![vec![Chars::new(' ', ' '); 3]; 2];
+        assert_eq!(rasterize(&cells).len(), 3 * TILE_SIZE * 2 * TILE_SIZE);
+        assert!(rasterize(&[]).is_empty());
+    }
+
+    #[test]
+    fn the_halves_of_a_tile_are_the_colors_of_its_characters() {
+        let red_on_green = Chars { left: '█', right: ' ', fg: Color::Red, bg: Color::Green };
+        let cells = vec![vec![Chars::new(' ', ' '), red_on_green]];
+        let pixels = rasterize(&cells);
+        let width = 2 * TILE_SIZE;
+        for y in 0..TILE_SIZE {
+            let row = &pixels[y * width..(y + 1) * width];
+            assert!(row[..TILE_SIZE].iter().all(|&p| p == 0x000000), "{y}");
+            assert!(row[TILE_SIZE..TILE_SIZE * 3 / 2].iter().all(|&p| p == 0xff0000), "{y}");
+            assert!(row[TILE_SIZE * 3 / 2..].iter().all(|&p| p == 0x00ff00), "{y}");
+        }
+    }
+}